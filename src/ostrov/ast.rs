@@ -1,8 +1,36 @@
+use crate::values::{fmt_char, fmt_dotted_list, fmt_list, fmt_string};
+use std::fmt::{Display, Error, Formatter};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum AST {
     Atom(String),
     Bool(bool),
+    Char(char),
     DottedList(Vec<AST>, Box<AST>),
     Integer(i64),
     List(Vec<AST>),
+    Str(String),
+    Vector(Vec<AST>),
+}
+
+impl Display for AST {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            AST::Atom(ref atom) => write!(f, "{}", atom),
+            AST::Bool(b) => write!(f, "{}", if b { "#t" } else { "#f" }),
+            AST::Char(c) => fmt_char(c, f),
+            AST::DottedList(ref items, ref tail) => {
+                fmt_dotted_list(items, &**tail, f)
+            }
+            AST::Integer(i) => write!(f, "{}", i),
+            AST::List(ref items) => fmt_list(items, f),
+            AST::Str(ref string) => {
+                fmt_string(&string.chars().collect::<Vec<_>>(), f)
+            }
+            AST::Vector(ref items) => {
+                write!(f, "#")?;
+                fmt_list(items, f)
+            }
+        }
+    }
 }
@@ -1,46 +1,64 @@
 use crate::{
     ast::AST::{self, *},
     errors::RuntimeError,
+    expander::Identifiers,
     instructions::{
         ArgumentsType, Bytecode,
         Instruction::{self, *},
     },
 };
 
-pub fn compile(ast: &[AST]) -> Result<Bytecode, RuntimeError> {
+pub fn compile(
+    ast: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     let mut instructions = vec![];
 
     for ast_value in ast {
-        instructions.append(&mut compile_single(ast_value)?);
+        instructions.append(&mut compile_single(ast_value, identifiers)?);
     }
 
     Ok(instructions)
 }
 
-pub fn compile_single(ast: &AST) -> Result<Bytecode, RuntimeError> {
+pub fn compile_single(
+    ast: &AST,
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     match *ast {
-        Integer(..) | Bool(..) => emit_constant(ast),
+        Integer(..) | Bool(..) | Char(..) | Str(..) | Vector(..) => {
+            emit_constant(ast)
+        }
         Atom(ref atom) => emit_reference(atom),
-        List(ref list) => emit_application(list),
+        List(ref list) => emit_application(list, identifiers),
         _ => Err(RuntimeError::MalformedExpression),
     }
 }
 
 fn emit_single_instr(instruction: Instruction) -> Result<Bytecode, RuntimeError> {
-    let mut bytecode = vec![];
-    bytecode.push(instruction);
-    Ok(bytecode)
+    Ok(vec![instruction])
 }
 
 fn emit_constant(value: &AST) -> Result<Bytecode, RuntimeError> {
     emit_single_instr(LoadConstant(value.clone()))
 }
 
+fn emit_quote(args: &[AST]) -> Result<Bytecode, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::BadArity(Some("quote".into())));
+    }
+
+    emit_constant(&args[0])
+}
+
 fn emit_reference(atom: &str) -> Result<Bytecode, RuntimeError> {
     emit_single_instr(LoadReference(atom.into()))
 }
 
-fn emit_application(list: &[AST]) -> Result<Bytecode, RuntimeError> {
+fn emit_application(
+    list: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     if list.is_empty() {
         return Err(RuntimeError::MalformedExpression);
     }
@@ -52,58 +70,81 @@ fn emit_application(list: &[AST]) -> Result<Bytecode, RuntimeError> {
         let args = tail;
 
         match special_form.as_ref() {
-            "if" => return emit_if(args),
-            "and" => return emit_and(args),
-            "or" => return emit_or(args),
-            "quote" => return emit_constant(&args[0]),
-            "set!" => return emit_set(args),
-            "define" => return emit_define(args),
-            "lambda" => return emit_lambda(args),
-            "let" => return emit_let(args),
+            "if" => return emit_if(args, identifiers),
+            "and" => return emit_and(args, identifiers),
+            "or" => return emit_or(args, identifiers),
+            "quote" => return emit_quote(args),
+            "set!" => return emit_set(args, identifiers),
+            "define" => return emit_define(args, identifiers),
+            "lambda" => return emit_lambda(args, identifiers),
+            "case-lambda" => return emit_case_lambda(args, identifiers),
+            "let" => return emit_let(args, identifiers),
+            "let*" => return emit_let_star(args, identifiers),
+            "letrec" => return emit_letrec(args, false, identifiers),
+            "letrec*" => return emit_letrec(args, true, identifiers),
+            "do" => return emit_do(args, identifiers),
+            "begin" => return emit_begin(args, identifiers),
+            "when" => return emit_when(args, true, identifiers),
+            "unless" => return emit_when(args, false, identifiers),
+            "cond" => return emit_cond(args, identifiers),
+            "case" => return emit_case(args, identifiers),
+            "let-values" => return emit_let_values(args, identifiers),
+            "let*-values" => return emit_let_star_values(args, identifiers),
+            "define-values" => return emit_define_values(args, identifiers),
+            "receive" => return emit_receive(args, identifiers),
+            "parameterize" => return emit_parameterize(args, identifiers),
+            "guard" => return emit_guard(args, identifiers),
+            "assert" => return emit_assert(args, identifiers),
+            "define-record-type" => {
+                return emit_define_record_type(args, identifiers)
+            }
+            "delay" => return emit_delay(args, Delay, identifiers),
+            "delay-force" => return emit_delay(args, DelayForce, identifiers),
+            "stream-cons" => return emit_stream_cons(args, identifiers),
             _ => (),
         }
     }
 
-    emit_apply(head, tail)
+    emit_apply(head, tail, identifiers)
 }
 
-fn emit_if(args: &[AST]) -> Result<Bytecode, RuntimeError> {
+fn emit_if(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     if args.len() < 2 || args.len() > 3 {
         return Err(RuntimeError::BadArity(Some("if".into())));
     }
 
-    let mut instructions = vec![];
-
-    instructions.append(&mut compile_single(&args[0])?);
-
-    let mut then = compile_single(&args[1])?;
-    instructions.push(JumpOnFalse(then.len() + 1));
-    instructions.append(&mut then);
-
-    if args.len() == 3 {
-        let mut else_ = compile_single(&args[2])?;
-        instructions.push(Jump(else_.len()));
-        instructions.append(&mut else_);
-    } else {
-        instructions.push(Jump(1usize));
-        instructions.push(LoadUnspecified);
-    }
+    let test = compile_single(&args[0], identifiers)?;
+    let then = compile_single(&args[1], identifiers)?;
+    let else_ = match args.get(2) {
+        Some(else_) => compile_single(else_, identifiers)?,
+        None => vec![LoadUnspecified],
+    };
 
-    Ok(instructions)
+    Ok(branches(test, then, else_))
 }
 
-fn emit_and(args: &[AST]) -> Result<Bytecode, RuntimeError> {
-    emit_logical_op(args, true, JumpOnFalse)
+fn emit_and(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    emit_logical_op(args, true, JumpOnFalse, identifiers)
 }
 
-fn emit_or(args: &[AST]) -> Result<Bytecode, RuntimeError> {
-    emit_logical_op(args, false, JumpOnTrue)
+fn emit_or(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    emit_logical_op(args, false, JumpOnTrue, identifiers)
 }
 
 fn emit_logical_op<F>(
     args: &[AST],
     default: bool,
     instruction: F,
+    identifiers: &mut Identifiers,
 ) -> Result<Bytecode, RuntimeError>
 where
     F: Fn(usize) -> Instruction,
@@ -117,7 +158,7 @@ where
         let mut sizes = Vec::with_capacity(args.len());
 
         for arg in args {
-            let compiled_single = compile_single(arg)?;
+            let compiled_single = compile_single(arg, identifiers)?;
             sizes.push(compiled_single.len());
             compiled_args.push(compiled_single);
         }
@@ -137,9 +178,7 @@ where
 
         jumps.push(0);
 
-        for (mut compiled_arg, jump) in
-            compiled_args.into_iter().zip(jumps.into_iter())
-        {
+        for (mut compiled_arg, jump) in compiled_args.into_iter().zip(jumps) {
             instructions.append(&mut compiled_arg);
             instructions.push(instruction(jump));
         }
@@ -149,13 +188,167 @@ where
     Ok(instructions)
 }
 
-fn emit_set(args: &[AST]) -> Result<Bytecode, RuntimeError> {
+/// `(begin expr ...)`. At top level the definitions inside it are top-level
+/// definitions, since they are compiled in place.
+fn emit_begin(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    compile_sequence(args, identifiers)
+}
+
+fn emit_when(
+    args: &[AST],
+    when: bool,
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        let name = if when { "when" } else { "unless" };
+        return Err(RuntimeError::BadArity(Some(name.into())));
+    }
+
+    let test = compile_single(&args[0], identifiers)?;
+    let body = compile_sequence(&args[1..], identifiers)?;
+
+    if when {
+        Ok(branches(test, body, vec![LoadUnspecified]))
+    } else {
+        Ok(branches(test, vec![LoadUnspecified], body))
+    }
+}
+
+fn emit_cond(
+    clauses: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    let (clause, rest) = match clauses.split_first() {
+        Some(split) => split,
+        None => return Ok(vec![LoadUnspecified]),
+    };
+
+    let clause = match *clause {
+        List(ref clause) if !clause.is_empty() => clause,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    if clause[0] == Atom("else".into()) {
+        if !rest.is_empty() || clause.len() < 2 {
+            return Err(RuntimeError::MalformedExpression);
+        }
+
+        return compile_sequence(&clause[1..], identifiers);
+    }
+
+    let mut test = compile_single(&clause[0], identifiers)?;
+    let mut rest = emit_cond(rest, identifiers)?;
+
+    match clause[1..] {
+        [] => {
+            test.push(JumpOnTrue(rest.len()));
+            test.append(&mut rest);
+            Ok(test)
+        }
+        [Atom(ref arrow), ref receiver] if arrow == "=>" => {
+            Ok(branches(test, apply_to_acc(receiver, identifiers)?, rest))
+        }
+        ref body => {
+            Ok(branches(test, compile_sequence(body, identifiers)?, rest))
+        }
+    }
+}
+
+/// `(case key clause ...)` binds the key and tries each clause's datums with
+/// `%memv`, so that programs rebinding `memv` don't change its meaning.
+fn emit_case(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.is_empty() {
+        return Err(RuntimeError::BadArity(Some("case".into())));
+    }
+
+    let key = Atom(identifiers.fresh("%case-key"));
+    let mut clauses = vec![Atom("cond".into())];
+
+    for clause in &args[1..] {
+        let clause = match *clause {
+            List(ref clause) if clause.len() >= 2 => clause,
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let test = match clause[0] {
+            Atom(ref else_) if else_ == "else" => clause[0].clone(),
+            List(ref datums) => {
+                call("%memv", vec![key.clone(), quote(List(datums.clone()))])
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let body = match clause[1..] {
+            [Atom(ref arrow), ref receiver] if arrow == "=>" => {
+                vec![List(vec![receiver.clone(), key.clone()])]
+            }
+            ref body => body.to_vec(),
+        };
+
+        let mut cond_clause = vec![test];
+        cond_clause.extend(body);
+        clauses.push(List(cond_clause));
+    }
+
+    emit_let(
+        &[List(vec![List(vec![key, args[0].clone()])]), List(clauses)],
+        identifiers,
+    )
+}
+
+/// Applies `receiver` to the value in the accumulator.
+fn apply_to_acc(
+    receiver: &AST,
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    let mut instructions = vec![Frame, Argument];
+    instructions.append(&mut compile_single(receiver, identifiers)?);
+    instructions.push(Apply);
+    Ok(instructions)
+}
+
+/// Runs `then` if the value of `test` is true, `else_` otherwise.
+fn branches(
+    mut test: Bytecode,
+    mut then: Bytecode,
+    mut else_: Bytecode,
+) -> Bytecode {
+    test.push(JumpOnFalse(then.len() + 1));
+    test.append(&mut then);
+    test.push(Jump(else_.len()));
+    test.append(&mut else_);
+    test
+}
+
+/// Expressions evaluated in order, the value of the last one being the value of
+/// the sequence.
+fn compile_sequence(
+    exprs: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if exprs.is_empty() {
+        return Ok(vec![LoadUnspecified]);
+    }
+
+    compile(exprs, identifiers)
+}
+
+fn emit_set(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::BadArity(Some("set!".into())));
     }
 
     if let Atom(ref name) = args[0] {
-        let mut argument = compile_single(&args[1])?;
+        let mut argument = compile_single(&args[1], identifiers)?;
         let mut instructions = vec![];
         instructions.append(&mut argument);
         instructions.push(Replace(name.clone()));
@@ -165,7 +358,10 @@ fn emit_set(args: &[AST]) -> Result<Bytecode, RuntimeError> {
     }
 }
 
-fn emit_define(args: &[AST]) -> Result<Bytecode, RuntimeError> {
+fn emit_define(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     if args.is_empty() {
         return Err(RuntimeError::MalformedExpression);
     }
@@ -175,7 +371,7 @@ fn emit_define(args: &[AST]) -> Result<Bytecode, RuntimeError> {
     match args[0] {
         Atom(ref name) => {
             if args.len() == 2 {
-                instructions.append(&mut compile_single(&args[1])?);
+                instructions.append(&mut compile_single(&args[1], identifiers)?);
             } else {
                 instructions.push(LoadUnspecified);
             }
@@ -191,7 +387,7 @@ fn emit_define(args: &[AST]) -> Result<Bytecode, RuntimeError> {
             for x in body {
                 lambda.push(x.clone());
             }
-            instructions.append(&mut emit_lambda(&lambda)?);
+            instructions.append(&mut emit_lambda(&lambda, identifiers)?);
 
             instructions.push(Assignment(name));
         }
@@ -211,7 +407,7 @@ fn emit_define(args: &[AST]) -> Result<Bytecode, RuntimeError> {
             for x in body {
                 lambda.push(x.clone());
             }
-            instructions.append(&mut emit_lambda(&lambda)?);
+            instructions.append(&mut emit_lambda(&lambda, identifiers)?);
 
             instructions.push(Assignment(name));
         }
@@ -221,29 +417,36 @@ fn emit_define(args: &[AST]) -> Result<Bytecode, RuntimeError> {
     Ok(instructions)
 }
 
-fn emit_apply(head: &AST, args: &[AST]) -> Result<Bytecode, RuntimeError> {
+fn emit_apply(
+    head: &AST,
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
     let mut instructions = vec![];
     instructions.push(Frame);
 
     for arg in args {
-        instructions.append(&mut compile_single(arg)?);
+        instructions.append(&mut compile_single(arg, identifiers)?);
         instructions.push(Argument);
     }
 
-    instructions.append(&mut compile_single(head)?);
+    instructions.append(&mut compile_single(head, identifiers)?);
     instructions.push(Apply);
     Ok(instructions)
 }
 
-fn emit_lambda(args_: &[AST]) -> Result<Bytecode, RuntimeError> {
-    let body = &args_[1..];
-    if body.is_empty() {
+fn emit_lambda(
+    args_: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args_.len() < 2 {
         return Err(RuntimeError::MalformedExpression);
     }
+    let body = &args_[1..];
 
     let mut instructions = vec![];
 
-    let compiled_body = compile(body)?;
+    let compiled_body = compile_body(body, identifiers)?;
     let (args, args_type) = function_arguments(&args_[0])?;
     instructions.push(Close {
         args,
@@ -254,49 +457,845 @@ fn emit_lambda(args_: &[AST]) -> Result<Bytecode, RuntimeError> {
     Ok(instructions)
 }
 
-fn emit_let(args_: &[AST]) -> Result<Bytecode, RuntimeError> {
-    if args_.len() < 2 {
+/// `(case-lambda (formals body ...) ...)` hands a closure of each clause to
+/// `%case-lambda`.
+fn emit_case_lambda(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    let clauses = args
+        .iter()
+        .map(|clause| match *clause {
+            List(ref clause) if clause.len() > 1 => {
+                let mut lambda = vec![Atom("lambda".into())];
+                lambda.extend_from_slice(clause);
+                Ok(List(lambda))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        })
+        .collect::<Result<_, _>>()?;
+
+    compile_single(&call("%case-lambda", clauses), identifiers)
+}
+
+/// Compiles the body of a procedure, whose calls in tail position reuse the
+/// procedure's frame.
+///
+/// Definitions at the start of the body, `begin`s being spliced into it, have
+/// `letrec*` semantics: every variable they define is declared before the
+/// first of them is evaluated, in the frame of the call.
+fn compile_body(
+    body: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    let mut forms = vec![];
+    splice_begins(body, &mut forms);
+
+    let expressions = forms
+        .iter()
+        .position(|form| !is_definition(form))
+        .ok_or(RuntimeError::MalformedExpression)?;
+
+    if forms[expressions..].iter().any(is_definition) {
+        return Err(RuntimeError::MalformedExpression);
+    }
+
+    let mut names: Vec<String> = vec![];
+    let mut definitions = vec![];
+
+    for definition in &forms[..expressions] {
+        let mut compiled = compile_single(definition, identifiers)?;
+
+        for instruction in &compiled {
+            if let Assignment(ref name) = *instruction {
+                if names.contains(name) {
+                    return Err(RuntimeError::MalformedExpression);
+                }
+                names.push(name.clone());
+            }
+        }
+
+        definitions.append(&mut compiled);
+    }
+
+    let mut instructions: Bytecode = names.into_iter().map(Declare).collect();
+    instructions.append(&mut definitions);
+    instructions.append(&mut compile(&forms[expressions..], identifiers)?);
+
+    mark_tail_calls(&mut instructions);
+    Ok(instructions)
+}
+
+fn splice_begins(body: &[AST], forms: &mut Vec<AST>) {
+    for form in body {
+        match *form {
+            List(ref list) if list.first() == Some(&Atom("begin".into())) => {
+                splice_begins(&list[1..], forms)
+            }
+            _ => forms.push(form.clone()),
+        }
+    }
+}
+
+fn is_definition(form: &AST) -> bool {
+    match *form {
+        List(ref list) => matches!(
+            list.first(),
+            Some(Atom(ref head))
+                if head == "define"
+                    || head == "define-values"
+                    || head == "define-record-type"
+        ),
+        _ => false,
+    }
+}
+
+/// Turns every `Apply` after which the body returns into a `TailApply`. A call
+/// is in tail position if nothing but jumps separate it from the end of the
+/// body, which covers the branches of `if` and the last operand of `and` and
+/// `or`. Nested procedure bodies are marked when they are compiled.
+fn mark_tail_calls(instructions: &mut Bytecode) {
+    let returns_after = |mut pc: usize| loop {
+        match instructions.get(pc) {
+            None => return true,
+            Some(Jump(offset)) => pc += offset + 1,
+            Some(_) => return false,
+        }
+    };
+
+    let tail_calls: Vec<usize> = (0..instructions.len())
+        .filter(|&pc| instructions[pc] == Apply && returns_after(pc + 1))
+        .collect();
+
+    for pc in tail_calls {
+        instructions[pc] = TailApply;
+    }
+}
+
+fn emit_delay(
+    args: &[AST],
+    instruction: Instruction,
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::MalformedExpression);
+    }
+
+    let mut instructions =
+        emit_lambda(&[List(vec![]), args[0].clone()], identifiers)?;
+    instructions.push(instruction);
+    Ok(instructions)
+}
+
+/// `(stream-cons a b)` is a forced promise of a pair of `(delay a)` and
+/// `(delay-force b)`, made with `%make-promise` and `%cons`.
+fn emit_stream_cons(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some("stream-cons".into())));
+    }
+
+    let pair = call(
+        "%cons",
+        vec![
+            List(vec![Atom("delay".into()), args[0].clone()]),
+            List(vec![Atom("delay-force".into()), args[1].clone()]),
+        ],
+    );
+
+    compile_single(&call("%make-promise", vec![pair]), identifiers)
+}
+
+fn emit_let(
+    args_: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args_.is_empty() {
         return Err(RuntimeError::BadArity(Some("let".into())));
     }
 
-    let mut instructions = vec![];
+    if let Atom(ref name) = args_[0] {
+        return emit_named_let(name, &args_[1..], identifiers);
+    }
 
-    if let List(ref bindings) = *args_.first().unwrap() {
-        let mut params = Vec::with_capacity(bindings.len());
+    let (params, inits) = let_bindings(&args_[0])?;
+    let mut instructions = vec![Frame];
 
-        instructions.push(Frame);
+    for init in &inits {
+        instructions.append(&mut compile_single(init, identifiers)?);
+        instructions.push(Argument);
+    }
 
-        for binding in bindings.iter() {
-            match *binding {
-                List(ref binding) if binding.len() == 2 => {
-                    params.push(unpack_atom(&binding[0])?);
+    instructions.push(Close {
+        args: params,
+        args_type: ArgumentsType::Fixed,
+        body: compile_body(&args_[1..], identifiers)?,
+    });
 
-                    instructions.append(&mut compile_single(&binding[1])?);
-                    instructions.push(Argument);
-                }
-                _ => return Err(RuntimeError::MalformedExpression),
+    instructions.push(Apply);
+
+    Ok(instructions)
+}
+
+/// `(let name ((var init) ...) body ...)` binds `name` to a procedure of the
+/// variables, only visible from its body, and calls it with the inits.
+fn emit_named_let(
+    name: &str,
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.is_empty() {
+        return Err(RuntimeError::MalformedExpression);
+    }
+
+    let (params, inits) = let_bindings(&args[0])?;
+
+    let mut lambda = vec![
+        Atom("lambda".into()),
+        List(params.into_iter().map(Atom).collect()),
+    ];
+    lambda.extend_from_slice(&args[1..]);
+
+    let procedure = List(vec![
+        Atom("letrec".into()),
+        List(vec![List(vec![Atom(name.into()), List(lambda)])]),
+        Atom(name.into()),
+    ]);
+
+    emit_apply(&procedure, &inits, identifiers)
+}
+
+/// `(do ((var init step) ...) (test expr ...) command ...)` is a named `let`
+/// looping until `test` holds. Variables without a step keep their value.
+fn emit_do(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("do".into())));
+    }
+
+    let specs = match args[0] {
+        List(ref specs) => specs,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let mut bindings = Vec::with_capacity(specs.len());
+    let mut steps = Vec::with_capacity(specs.len());
+
+    for spec in specs {
+        match *spec {
+            List(ref spec) if spec.len() == 2 || spec.len() == 3 => {
+                bindings.push(List(spec[..2].to_vec()));
+                steps.push(spec.get(2).unwrap_or(&spec[0]).clone());
             }
+            _ => return Err(RuntimeError::MalformedExpression),
         }
+    }
 
-        let mut inner_instr = vec![];
-        for body in &args_[1..] {
-            inner_instr.append(&mut compile_single(body)?);
+    let (test, results) = match args[1] {
+        List(ref clause) if !clause.is_empty() => (&clause[0], &clause[1..]),
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let name = identifiers.fresh("%do-loop");
+
+    let mut done = vec![Atom("begin".into())];
+    done.extend_from_slice(results);
+
+    let mut next = vec![Atom("begin".into())];
+    next.extend_from_slice(&args[2..]);
+    next.push(call(&name, steps));
+
+    emit_named_let(
+        &name,
+        &[
+            List(bindings),
+            List(vec![
+                Atom("if".into()),
+                test.clone(),
+                List(done),
+                List(next),
+            ]),
+        ],
+        identifiers,
+    )
+}
+
+/// `(let* (binding ...) body ...)` nests a `let` per binding.
+fn emit_let_star(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.is_empty() {
+        return Err(RuntimeError::BadArity(Some("let*".into())));
+    }
+
+    let bindings = match args[0] {
+        List(ref bindings) => bindings,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    if bindings.len() <= 1 {
+        return emit_let(args, identifiers);
+    }
+
+    let mut inner = vec![Atom("let*".into()), List(bindings[1..].to_vec())];
+    inner.extend_from_slice(&args[1..]);
+
+    emit_let(&[List(vec![bindings[0].clone()]), List(inner)], identifiers)
+}
+
+/// `letrec` and `letrec*` run in a frame of their own, where the variables are
+/// declared before any init is evaluated, so that referencing one before it is
+/// initialized is an error. `letrec*` initializes each variable in turn, while
+/// `letrec` evaluates every init before assigning any of them.
+fn emit_letrec(
+    args: &[AST],
+    sequential: bool,
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.is_empty() {
+        let name = if sequential { "letrec*" } else { "letrec" };
+        return Err(RuntimeError::BadArity(Some(name.into())));
+    }
+
+    let (params, inits) = let_bindings(&args[0])?;
+    let mut body: Bytecode = params.iter().cloned().map(Declare).collect();
+
+    if sequential {
+        for (param, init) in params.iter().zip(&inits) {
+            body.append(&mut compile_single(init, identifiers)?);
+            body.push(Assignment(param.clone()));
+        }
+    } else {
+        let temporaries: Vec<String> = params
+            .iter()
+            .map(|_| identifiers.fresh("%letrec"))
+            .collect();
+
+        body.push(Frame);
+        for init in &inits {
+            body.append(&mut compile_single(init, identifiers)?);
+            body.push(Argument);
         }
 
-        instructions.push(Close {
-            args: params,
+        let mut assignments = vec![];
+        for (param, temporary) in params.iter().zip(&temporaries) {
+            assignments.push(LoadReference(temporary.clone()));
+            assignments.push(Replace(param.clone()));
+        }
+        assignments.push(LoadUnspecified);
+
+        body.push(Close {
+            args: temporaries,
             args_type: ArgumentsType::Fixed,
-            body: inner_instr,
+            body: assignments,
         });
+        body.push(Apply);
+    }
 
-        instructions.push(Apply);
-    } else {
-        return Err(RuntimeError::MalformedExpression);
+    body.append(&mut compile_body(&args[1..], identifiers)?);
+    mark_tail_calls(&mut body);
+
+    Ok(vec![
+        Frame,
+        Close {
+            args: vec![],
+            args_type: ArgumentsType::Fixed,
+            body,
+        },
+        Apply,
+    ])
+}
+
+/// The variables and the inits of `((var init) ...)`.
+fn let_bindings(ast: &AST) -> Result<(Vec<String>, Vec<AST>), RuntimeError> {
+    let bindings = match *ast {
+        List(ref bindings) => bindings,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let mut params = Vec::with_capacity(bindings.len());
+    let mut inits = Vec::with_capacity(bindings.len());
+
+    for binding in bindings {
+        match *binding {
+            List(ref binding) if binding.len() == 2 => {
+                params.push(unpack_atom(&binding[0])?);
+                inits.push(binding[1].clone());
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    Ok((params, inits))
+}
+
+/// `(parameterize ((parameter value) ...) body ...)` hands the parameters, the
+/// values, both made into lists with `%list`, and a thunk of the body to
+/// `%parameterize`.
+fn emit_parameterize(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("parameterize".into())));
+    }
+
+    let (parameters, values): (Vec<_>, Vec<_>) =
+        values_bindings(&args[0])?.into_iter().unzip();
+
+    let mut thunk = vec![Atom("lambda".into()), List(vec![])];
+    thunk.extend_from_slice(&args[1..]);
+
+    compile_single(
+        &call(
+            "%parameterize",
+            vec![
+                call("%list", parameters),
+                call("%list", values),
+                List(thunk),
+            ],
+        ),
+        identifiers,
+    )
+}
+
+/// `(guard (variable clause ...) body ...)` hands a thunk of the body and a
+/// procedure of the clauses to `%guard`. The procedure returns its second
+/// argument when no clause accepts the object raised.
+fn emit_guard(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("guard".into())));
+    }
+
+    let (variable, clauses) = match args[0] {
+        List(ref spec) if !spec.is_empty() => (&spec[0], &spec[1..]),
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let declined = Atom(identifiers.fresh("%guard-declined"));
+    let mut cond = vec![Atom("cond".into())];
+    cond.extend_from_slice(clauses);
+    match clauses.last() {
+        Some(List(ref clause))
+            if clause.first() == Some(&Atom("else".into())) => {}
+        _ => cond.push(List(vec![Atom("else".into()), declined.clone()])),
+    }
+
+    let mut thunk = vec![Atom("lambda".into()), List(vec![])];
+    thunk.extend_from_slice(&args[1..]);
+    let handler = List(vec![
+        Atom("lambda".into()),
+        List(vec![variable.clone(), declined]),
+        List(cond),
+    ]);
+
+    compile_single(&call("%guard", vec![List(thunk), handler]), identifiers)
+}
+
+/// `(assert expr)` returns the value of `expr`, or raises an `&assertion`
+/// condition with `%assertion-violation` if it is false.
+fn emit_assert(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::BadArity(Some("assert".into())));
+    }
+
+    let violation = call(
+        "%assertion-violation",
+        vec![
+            List(vec![Atom("quote".into()), Atom("assert".into())]),
+            Str("assertion failed".into()),
+            List(vec![Atom("quote".into()), args[0].clone()]),
+        ],
+    );
+
+    compile_single(
+        &List(vec![Atom("or".into()), args[0].clone(), violation]),
+        identifiers,
+    )
+}
+
+/// `(let-values (((formals) init) ...) body ...)` calls each init with a
+/// consumer binding its values. With several bindings, the values are bound to
+/// temporaries first, so that no init sees the variables of another.
+fn emit_let_values(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("let-values".into())));
+    }
+
+    let bindings = values_bindings(&args[0])?;
+    let body = &args[1..];
+
+    if bindings.len() == 1 {
+        let (ref formals, ref init) = bindings[0];
+        return compile_single(
+            &call_with_values(init, formals, body.to_vec()),
+            identifiers,
+        );
+    }
+
+    let mut temporaries = vec![];
+    let mut consumers = vec![];
+
+    for (formals, init) in bindings {
+        consumers.push((
+            rename_formals(&formals, &mut temporaries, identifiers)?,
+            init,
+        ));
+    }
+
+    let let_bindings = temporaries
+        .into_iter()
+        .map(|(name, temporary)| List(vec![Atom(name), Atom(temporary)]))
+        .collect();
+    let mut let_ = vec![Atom("let".into()), List(let_bindings)];
+    let_.extend_from_slice(body);
+
+    let nested = consumers
+        .iter()
+        .rev()
+        .fold(List(let_), |inner, (formals, init)| {
+            call_with_values(init, formals, vec![inner])
+        });
+
+    compile_single(&nested, identifiers)
+}
+
+fn emit_let_star_values(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("let*-values".into())));
+    }
+
+    let bindings = match args[0] {
+        List(ref bindings) => bindings,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    if bindings.len() <= 1 {
+        return emit_let_values(args, identifiers);
     }
 
+    let mut inner =
+        vec![Atom("let*-values".into()), List(bindings[1..].to_vec())];
+    inner.extend_from_slice(&args[1..]);
+
+    emit_let_values(&[List(vec![bindings[0].clone()]), List(inner)], identifiers)
+}
+
+/// `(define-values formals expr)` defines every variable of `formals`, then
+/// assigns them the values of `expr`.
+fn emit_define_values(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some("define-values".into())));
+    }
+
+    let mut temporaries = vec![];
+    let formals = rename_formals(&args[0], &mut temporaries, identifiers)?;
+
+    let mut instructions = vec![];
+    let mut assignments = vec![];
+
+    for (name, temporary) in temporaries {
+        instructions.push(LoadUnspecified);
+        instructions.push(Assignment(name.clone()));
+        assignments.push(List(vec![
+            Atom("set!".into()),
+            Atom(name),
+            Atom(temporary),
+        ]));
+    }
+
+    if assignments.is_empty() {
+        assignments.push(Bool(false));
+    }
+
+    instructions.append(&mut compile_single(
+        &call_with_values(&args[1], &formals, assignments),
+        identifiers,
+    )?);
+    instructions.push(LoadUnspecified);
+
     Ok(instructions)
 }
 
+/// `(receive formals expr body ...)` from SRFI 8.
+fn emit_receive(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.len() < 3 {
+        return Err(RuntimeError::BadArity(Some("receive".into())));
+    }
+
+    compile_single(
+        &call_with_values(&args[1], &args[0], args[2..].to_vec()),
+        identifiers,
+    )
+}
+
+fn values_bindings(ast: &AST) -> Result<Vec<(AST, AST)>, RuntimeError> {
+    match *ast {
+        List(ref bindings) => bindings
+            .iter()
+            .map(|binding| match *binding {
+                List(ref binding) if binding.len() == 2 => {
+                    Ok((binding[0].clone(), binding[1].clone()))
+                }
+                _ => Err(RuntimeError::MalformedExpression),
+            })
+            .collect(),
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+/// Replaces every variable of `formals` with a temporary, recording the
+/// variable and its temporary in order.
+fn rename_formals(
+    formals: &AST,
+    temporaries: &mut Vec<(String, String)>,
+    identifiers: &mut Identifiers,
+) -> Result<AST, RuntimeError> {
+    let mut rename = |ast: &AST| -> Result<AST, RuntimeError> {
+        let name = unpack_atom(ast)?;
+        let temporary = identifiers.fresh("%values");
+        temporaries.push((name, temporary.clone()));
+        Ok(Atom(temporary))
+    };
+
+    match *formals {
+        Atom(..) => rename(formals),
+        List(ref list) => {
+            list.iter().map(rename).collect::<Result<_, _>>().map(List)
+        }
+        DottedList(ref list, ref rest) => {
+            let list = list.iter().map(&mut rename).collect::<Result<_, _>>()?;
+            Ok(DottedList(list, Box::new(rename(rest)?)))
+        }
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+/// `(%call-with-values (lambda () producer) (lambda formals body ...))`
+fn call_with_values(producer: &AST, formals: &AST, body: Vec<AST>) -> AST {
+    let mut consumer = vec![Atom("lambda".into()), formals.clone()];
+    consumer.extend(body);
+
+    call(
+        "%call-with-values",
+        vec![
+            List(vec![Atom("lambda".into()), List(vec![]), producer.clone()]),
+            List(consumer),
+        ],
+    )
+}
+
+/// Expands `define-record-type` into definitions built on the procedural
+/// layer, called through their `%` aliases. The record name is bound to the
+/// constructor descriptor, which also stands for the record type wherever one
+/// is expected. The expander adds a `(%type-name name)` clause when it renames
+/// the record name.
+fn emit_define_record_type(
+    args: &[AST],
+    identifiers: &mut Identifiers,
+) -> Result<Bytecode, RuntimeError> {
+    if args.is_empty() {
+        return Err(RuntimeError::BadArity(Some("define-record-type".into())));
+    }
+
+    let (name, constructor, predicate) = match args[0] {
+        Atom(ref name) => {
+            (name.clone(), format!("make-{}", name), format!("{}?", name))
+        }
+        List(ref names) if names.len() == 3 => (
+            unpack_atom(&names[0])?,
+            unpack_atom(&names[1])?,
+            unpack_atom(&names[2])?,
+        ),
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let mut fields = vec![];
+    let mut parent_rtd = Bool(false);
+    let mut parent_descriptor = Bool(false);
+    let mut protocol = Bool(false);
+    let mut sealed = Bool(false);
+    let mut opaque = Bool(false);
+    let mut uid = Bool(false);
+    let mut type_name = name.clone();
+
+    for clause in &args[1..] {
+        let clause = match *clause {
+            List(ref clause) if !clause.is_empty() => clause,
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        match (unpack_atom(&clause[0])?.as_ref(), &clause[1..]) {
+            ("fields", specs) => {
+                for spec in specs {
+                    fields.push(record_field(&name, spec)?);
+                }
+            }
+            ("parent", [parent]) => {
+                parent_rtd =
+                    call("%record-type-descriptor", vec![parent.clone()]);
+                parent_descriptor = parent.clone();
+            }
+            ("parent-rtd", [rtd, descriptor]) => {
+                parent_rtd = rtd.clone();
+                parent_descriptor = descriptor.clone();
+            }
+            ("protocol", [expr]) => protocol = expr.clone(),
+            ("sealed", [expr]) => sealed = expr.clone(),
+            ("opaque", [expr]) => opaque = expr.clone(),
+            ("%type-name", [type_]) => type_name = unpack_atom(type_)?,
+            ("nongenerative", []) => uid = quote(Atom(type_name.clone())),
+            ("nongenerative", [id]) => uid = quote(Atom(unpack_atom(id)?)),
+            _ => return Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    let specs = fields
+        .iter()
+        .map(|field| {
+            let kind = if field.mutator.is_some() {
+                "mutable"
+            } else {
+                "immutable"
+            };
+            List(vec![Atom(kind.into()), Atom(field.name.clone())])
+        })
+        .collect();
+
+    let rtd = call(
+        "%make-record-type-descriptor",
+        vec![
+            quote(Atom(type_name)),
+            parent_rtd,
+            uid,
+            sealed,
+            opaque,
+            quote(Vector(specs)),
+        ],
+    );
+    let descriptor = call(
+        "%make-record-constructor-descriptor",
+        vec![rtd, parent_descriptor, protocol],
+    );
+
+    let record_name = Atom(name.clone());
+    let mut definitions = vec![
+        (name.clone(), descriptor),
+        (
+            constructor,
+            call("%record-constructor", vec![record_name.clone()]),
+        ),
+        (
+            predicate,
+            call("%record-predicate", vec![record_name.clone()]),
+        ),
+    ];
+
+    for (index, field) in fields.into_iter().enumerate() {
+        let index = Integer(index as i64);
+
+        definitions.push((
+            field.accessor,
+            call("%record-accessor", vec![record_name.clone(), index.clone()]),
+        ));
+
+        if let Some(mutator) = field.mutator {
+            definitions.push((
+                mutator,
+                call("%record-mutator", vec![record_name.clone(), index]),
+            ));
+        }
+    }
+
+    let mut instructions = vec![];
+    for (name, value) in definitions {
+        instructions.append(&mut emit_define(&[Atom(name), value], identifiers)?);
+    }
+
+    Ok(instructions)
+}
+
+struct RecordField {
+    name: String,
+    accessor: String,
+    mutator: Option<String>,
+}
+
+fn record_field(record: &str, spec: &AST) -> Result<RecordField, RuntimeError> {
+    let accessor = |field: &str| format!("{}-{}", record, field);
+    let mutator = |field: &str| format!("{}-{}-set!", record, field);
+
+    let spec = match *spec {
+        Atom(ref name) => {
+            return Ok(RecordField {
+                name: name.clone(),
+                accessor: accessor(name),
+                mutator: None,
+            })
+        }
+        List(ref spec) if spec.len() >= 2 => spec,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let name = unpack_atom(&spec[1])?;
+
+    match (unpack_atom(&spec[0])?.as_ref(), &spec[2..]) {
+        ("immutable", []) => Ok(RecordField {
+            accessor: accessor(&name),
+            mutator: None,
+            name,
+        }),
+        ("immutable", [get]) => Ok(RecordField {
+            accessor: unpack_atom(get)?,
+            mutator: None,
+            name,
+        }),
+        ("mutable", []) => Ok(RecordField {
+            accessor: accessor(&name),
+            mutator: Some(mutator(&name)),
+            name,
+        }),
+        ("mutable", [get, set]) => Ok(RecordField {
+            accessor: unpack_atom(get)?,
+            mutator: Some(unpack_atom(set)?),
+            name,
+        }),
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+fn quote(ast: AST) -> AST {
+    List(vec![Atom("quote".into()), ast])
+}
+
+fn call(procedure: &str, mut args: Vec<AST>) -> AST {
+    args.insert(0, Atom(procedure.into()));
+    List(args)
+}
+
 fn function_arguments(
     ast: &AST,
 ) -> Result<(Vec<String>, ArgumentsType), RuntimeError> {
@@ -347,11 +1346,14 @@ mod test {
             Instruction::{self, *},
         },
         parser::parse,
+        vm::VM,
     };
 
     fn parse_and_compile(input: &str) -> Vec<Instruction> {
-        let ast = parse(input).expect(&format!("failed to parse {:?}", input));
-        compile(&ast).expect(&format!("failed to compile {:?}", input))
+        let ast = parse(input)
+            .unwrap_or_else(|e| panic!("failed to parse {:?}: {:?}", input, e));
+        compile(&ast, &mut VM::new().identifiers)
+            .unwrap_or_else(|e| panic!("failed to compile {:?}: {:?}", input, e))
     }
 
     #[test]
@@ -596,6 +1598,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn delay() {
+        assert_eq!(
+            vec![
+                Close {
+                    args: vec![],
+                    args_type: ArgumentsType::Fixed,
+                    body: vec![LoadConstant(Integer(1))],
+                },
+                Delay,
+            ],
+            parse_and_compile("(delay 1)")
+        );
+    }
+
     #[test]
     fn let_() {
         assert_eq!(
@@ -615,7 +1632,7 @@ mod test {
                         LoadReference("b".into()),
                         Argument,
                         LoadReference("+".into()),
-                        Apply,
+                        TailApply,
                     ],
                 },
                 Apply,
@@ -628,6 +1645,99 @@ mod test {
         );
     }
 
+    #[test]
+    fn tail_calls() {
+        let body = |input: &str| match parse_and_compile(input).remove(0) {
+            Close { body, .. } => body,
+            other => panic!("expected a closure, got {:?}", other),
+        };
+
+        assert_eq!(
+            vec![
+                Frame,
+                LoadReference("f".into()),
+                Apply,
+                Frame,
+                LoadReference("g".into()),
+                TailApply,
+            ],
+            body("(lambda () (f) (g))")
+        );
+        assert_eq!(
+            vec![
+                Frame,
+                LoadReference("p".into()),
+                Apply,
+                JumpOnFalse(4),
+                Frame,
+                LoadReference("f".into()),
+                TailApply,
+                Jump(3),
+                Frame,
+                LoadReference("g".into()),
+                TailApply,
+            ],
+            body("(lambda () (if (p) (f) (g)))")
+        );
+        assert_eq!(
+            vec![
+                Frame,
+                LoadReference("f".into()),
+                Apply,
+                JumpOnFalse(3),
+                Frame,
+                LoadReference("g".into()),
+                TailApply,
+            ],
+            body("(lambda () (and (f) (g)))")
+        );
+        assert_eq!(
+            vec![
+                Frame,
+                LoadReference("f".into()),
+                Apply,
+                JumpOnTrue(3),
+                Frame,
+                LoadReference("g".into()),
+                TailApply,
+            ],
+            body("(lambda () (or (f) (g)))")
+        );
+    }
+
+    #[test]
+    fn cond() {
+        assert_eq!(
+            vec![
+                LoadReference("a".into()),
+                JumpOnFalse(2),
+                LoadConstant(Integer(1)),
+                Jump(8),
+                LoadReference("b".into()),
+                JumpOnFalse(5),
+                Frame,
+                Argument,
+                LoadReference("f".into()),
+                Apply,
+                Jump(1),
+                LoadConstant(Integer(2)),
+            ],
+            parse_and_compile("(cond (a 1) (b => f) (else 2))")
+        );
+        assert_eq!(
+            vec![LoadReference("a".into()), JumpOnTrue(1), LoadUnspecified,],
+            parse_and_compile("(cond (a))")
+        );
+    }
+
+    #[test]
+    fn calls_outside_procedures_are_not_tail_calls() {
+        assert_eq!(
+            vec![Frame, LoadReference("f".into()), Apply],
+            parse_and_compile("(f)")
+        );
+    }
+
     #[test]
     fn function_application() {
         assert_eq!(
@@ -0,0 +1,580 @@
+use crate::{
+    errors::RuntimeError,
+    memory::Memory,
+    records::{ConstructorDescriptor, Field, RecordProcedure, RecordType},
+    values::RcValue,
+};
+use std::rc::Rc;
+
+/// The standard condition types of `(rnrs conditions)`, which errors raised
+/// by the VM are made of.
+pub struct ConditionTypes {
+    pub condition: Rc<RecordType>,
+    pub warning: Rc<RecordType>,
+    pub serious: Rc<RecordType>,
+    pub error: Rc<RecordType>,
+    pub violation: Rc<RecordType>,
+    pub assertion: Rc<RecordType>,
+    pub irritants: Rc<RecordType>,
+    pub who: Rc<RecordType>,
+    pub message: Rc<RecordType>,
+    pub non_continuable: Rc<RecordType>,
+    pub implementation_restriction: Rc<RecordType>,
+    pub lexical: Rc<RecordType>,
+    pub syntax: Rc<RecordType>,
+    pub undefined: Rc<RecordType>,
+    pub io: Rc<RecordType>,
+    pub io_filename: Rc<RecordType>,
+}
+
+fn condition_type(
+    name: &str,
+    parent: &Rc<RecordType>,
+    fields: &[&str],
+) -> Rc<RecordType> {
+    Rc::new(RecordType {
+        name: name.into(),
+        parent: Some(parent.clone()),
+        uid: None,
+        sealed: false,
+        opaque: false,
+        fields: fields
+            .iter()
+            .map(|&name| Field {
+                name: name.into(),
+                mutable: false,
+            })
+            .collect(),
+    })
+}
+
+impl ConditionTypes {
+    pub fn new() -> ConditionTypes {
+        let condition = Rc::new(RecordType {
+            name: "&condition".into(),
+            parent: None,
+            uid: None,
+            sealed: false,
+            opaque: false,
+            fields: vec![],
+        });
+        let serious = condition_type("&serious", &condition, &[]);
+        let error = condition_type("&error", &serious, &[]);
+        let violation = condition_type("&violation", &serious, &[]);
+        let io = condition_type("&i/o", &error, &[]);
+
+        ConditionTypes {
+            warning: condition_type("&warning", &condition, &[]),
+            assertion: condition_type("&assertion", &violation, &[]),
+            irritants: condition_type("&irritants", &condition, &["irritants"]),
+            who: condition_type("&who", &condition, &["who"]),
+            message: condition_type("&message", &condition, &["message"]),
+            non_continuable: condition_type("&non-continuable", &violation, &[]),
+            implementation_restriction: condition_type(
+                "&implementation-restriction",
+                &violation,
+                &[],
+            ),
+            lexical: condition_type("&lexical", &violation, &[]),
+            syntax: condition_type("&syntax", &violation, &["form", "subform"]),
+            undefined: condition_type("&undefined", &violation, &[]),
+            io_filename: condition_type("&i/o-filename", &io, &["filename"]),
+            io,
+            condition,
+            serious,
+            error,
+            violation,
+        }
+    }
+
+    /// Global bindings for the condition types, along with their
+    /// constructors, predicates and field accessors.
+    pub fn bindings(&self, memory: &mut Memory) -> Vec<(String, RcValue)> {
+        let types: [(&Rc<RecordType>, &str, &str, &[&str]); 15] = [
+            (&self.warning, "make-warning", "warning?", &[]),
+            (
+                &self.serious,
+                "make-serious-condition",
+                "serious-condition?",
+                &[],
+            ),
+            (&self.error, "make-error", "error?", &[]),
+            (&self.violation, "make-violation", "violation?", &[]),
+            (
+                &self.assertion,
+                "make-assertion-violation",
+                "assertion-violation?",
+                &[],
+            ),
+            (
+                &self.irritants,
+                "make-irritants-condition",
+                "irritants-condition?",
+                &["condition-irritants"],
+            ),
+            (
+                &self.who,
+                "make-who-condition",
+                "who-condition?",
+                &["condition-who"],
+            ),
+            (
+                &self.message,
+                "make-message-condition",
+                "message-condition?",
+                &["condition-message"],
+            ),
+            (
+                &self.non_continuable,
+                "make-non-continuable-violation",
+                "non-continuable-violation?",
+                &[],
+            ),
+            (
+                &self.implementation_restriction,
+                "make-implementation-restriction-violation",
+                "implementation-restriction-violation?",
+                &[],
+            ),
+            (
+                &self.lexical,
+                "make-lexical-violation",
+                "lexical-violation?",
+                &[],
+            ),
+            (
+                &self.syntax,
+                "make-syntax-violation",
+                "syntax-violation?",
+                &["syntax-violation-form", "syntax-violation-subform"],
+            ),
+            (
+                &self.undefined,
+                "make-undefined-violation",
+                "undefined-violation?",
+                &[],
+            ),
+            (&self.io, "make-i/o-error", "i/o-error?", &[]),
+            (
+                &self.io_filename,
+                "make-i/o-filename-error",
+                "i/o-filename-error?",
+                &["i/o-error-filename"],
+            ),
+        ];
+
+        let mut bindings =
+            vec![("&condition".to_owned(), descriptor(&self.condition, memory))];
+
+        for &(rtd, constructor, predicate, accessors) in types.iter() {
+            bindings.push((rtd.name.clone(), descriptor(rtd, memory)));
+
+            let procedure = RecordProcedure::Constructor {
+                rtd: rtd.clone(),
+                arity: rtd.field_count(),
+                collected: vec![],
+            };
+            bindings
+                .push((constructor.into(), memory.record_procedure(procedure)));
+
+            let procedure = RecordProcedure::ConditionPredicate(rtd.clone());
+            bindings.push((predicate.into(), memory.record_procedure(procedure)));
+
+            for (index, &accessor) in accessors.iter().enumerate() {
+                let field = memory.record_procedure(RecordProcedure::Accessor(
+                    rtd.clone(),
+                    rtd.parent_field_count() + index,
+                ));
+                let procedure =
+                    RecordProcedure::ConditionAccessor(rtd.clone(), field);
+                bindings
+                    .push((accessor.into(), memory.record_procedure(procedure)));
+            }
+        }
+
+        bindings
+    }
+
+    /// The condition describing `error`, as handlers get to see it.
+    pub fn describe(&self, error: &RuntimeError, memory: &mut Memory) -> RcValue {
+        use crate::errors::RuntimeError::*;
+
+        let mut builder = Builder {
+            memory,
+            components: vec![],
+        };
+
+        match *error {
+            BadArity(ref who) => {
+                builder.simple(&self.assertion, vec![]);
+                if let Some(ref who) = *who {
+                    let who = builder.memory.intern(who.clone());
+                    builder.simple(&self.who, vec![who]);
+                }
+                builder.message(&self.message, "wrong number of arguments");
+            }
+            BadValueCount(count) => {
+                builder.simple(&self.assertion, vec![]);
+                builder.message(&self.message, "wrong number of values");
+                let count = builder.memory.integer(count as i64);
+                builder.irritants(&self.irritants, vec![count]);
+            }
+            CannotPopLastFrame => {
+                builder.simple(&self.error, vec![]);
+                builder.message(&self.message, "cannot pop the last frame");
+            }
+            ImmutableValue(ref value) => {
+                builder.simple(&self.assertion, vec![]);
+                builder.message(&self.message, "attempt to mutate a constant");
+                builder.irritants(&self.irritants, vec![value.clone()]);
+            }
+            IndexOutOfRange(ref index) => {
+                builder.simple(&self.assertion, vec![]);
+                builder.message(&self.message, "index out of range");
+                builder.irritants(&self.irritants, vec![index.clone()]);
+            }
+            IntegerOverflow(ref who) => {
+                builder.simple(&self.implementation_restriction, vec![]);
+                let who = builder.memory.intern(who.clone());
+                builder.simple(&self.who, vec![who]);
+                builder.message(&self.message, "result out of range");
+            }
+            LexicalViolation(ref path, ref reason) => {
+                builder.simple(&self.lexical, vec![]);
+                builder.message(&self.message, reason);
+                let path = builder.memory.string(path.chars().collect());
+                builder.irritants(&self.irritants, vec![path]);
+            }
+            LoadFailed(ref path, ref reason) => {
+                let path = builder.memory.string(path.chars().collect());
+                builder.simple(&self.io_filename, vec![path]);
+                builder.message(&self.message, reason);
+            }
+            Located(_, ref error) => return self.describe(error, builder.memory),
+            MalformedExpression => {
+                let no_form = builder.memory.b_false();
+                builder.simple(&self.syntax, vec![no_form.clone(), no_form]);
+                builder.message(&self.message, "malformed expression");
+            }
+            NoMatchingArity(ref arities, count) => {
+                builder.simple(&self.assertion, vec![]);
+                let message = format!(
+                    "wrong number of arguments, expected {}",
+                    describe_arities(arities)
+                );
+                builder.message(&self.message, &message);
+                let count = builder.memory.integer(count as i64);
+                builder.irritants(&self.irritants, vec![count]);
+            }
+            GuardEscape(..) => {
+                builder.simple(&self.error, vec![]);
+                builder.message(&self.message, "escape to an exited guard");
+            }
+            PrimitiveFailed(ref who) => {
+                builder.simple(&self.error, vec![]);
+                let who = builder.memory.intern(who.clone());
+                builder.simple(&self.who, vec![who]);
+                builder.message(&self.message, "primitive failed");
+            }
+            Raised(ref raised) => return raised.clone(),
+            SyntaxViolation(ref who, ref message, ref form, ref subform) => {
+                builder.simple(&self.syntax, vec![form.clone(), subform.clone()]);
+                if let Some(ref who) = *who {
+                    let who = builder.memory.intern(who.clone());
+                    builder.simple(&self.who, vec![who]);
+                }
+                builder.message(&self.message, message);
+            }
+            UnappliableValue(ref value) => {
+                builder.simple(&self.assertion, vec![]);
+                builder
+                    .message(&self.message, "attempt to apply a non-procedure");
+                builder.irritants(&self.irritants, vec![value.clone()]);
+            }
+            UnboundVariable(ref name) => {
+                builder.simple(&self.undefined, vec![]);
+                builder.message(&self.message, "unbound variable");
+                let name = builder.memory.intern(name.clone());
+                builder.irritants(&self.irritants, vec![name]);
+            }
+            UnknownLibrary(ref reference) => {
+                builder.simple(&self.error, vec![]);
+                builder.message(&self.message, "unknown library");
+                let reference =
+                    builder.memory.string(reference.chars().collect());
+                builder.irritants(&self.irritants, vec![reference]);
+            }
+            UninitializedVariable(ref name) => {
+                builder.simple(&self.assertion, vec![]);
+                builder.message(
+                    &self.message,
+                    "variable referenced before its initialization",
+                );
+                let name = builder.memory.intern(name.clone());
+                builder.irritants(&self.irritants, vec![name]);
+            }
+            WrongArgumentType(ref value) => {
+                builder.simple(&self.assertion, vec![]);
+                builder.message(&self.message, "wrong argument type");
+                builder.irritants(&self.irritants, vec![value.clone()]);
+            }
+        }
+
+        builder.build()
+    }
+
+    /// The condition `error` and `assertion-violation` raise: a simple
+    /// condition of type `rtd`, who raised it unless `who` is `#f`, a message
+    /// and a list of irritants.
+    pub fn error(
+        &self,
+        rtd: &Rc<RecordType>,
+        who: RcValue,
+        message: RcValue,
+        irritants: RcValue,
+        memory: &mut Memory,
+    ) -> RcValue {
+        let mut builder = Builder {
+            memory,
+            components: vec![],
+        };
+
+        builder.simple(rtd, vec![]);
+        if !who.is_false() {
+            builder.simple(&self.who, vec![who]);
+        }
+        builder.simple(&self.message, vec![message]);
+        builder.simple(&self.irritants, vec![irritants]);
+
+        builder.build()
+    }
+
+    /// The condition raised when the handler of a non-continuable raise of
+    /// `raised` returns.
+    pub fn non_continuable(
+        &self,
+        raised: RcValue,
+        memory: &mut Memory,
+    ) -> RcValue {
+        let mut builder = Builder {
+            memory,
+            components: vec![],
+        };
+
+        builder.simple(&self.non_continuable, vec![]);
+        builder.message(
+            &self.message,
+            "handler returned from a non-continuable raise",
+        );
+        builder.irritants(&self.irritants, vec![raised]);
+
+        builder.build()
+    }
+}
+
+impl Default for ConditionTypes {
+    fn default() -> ConditionTypes {
+        ConditionTypes::new()
+    }
+}
+
+fn descriptor(rtd: &Rc<RecordType>, memory: &mut Memory) -> RcValue {
+    let descriptor = ConstructorDescriptor::default_for(rtd.clone());
+    memory.record_constructor_descriptor(Rc::new(descriptor))
+}
+
+/// The arities of `case-lambda` clauses, as in "1, 2 or at least 3".
+fn describe_arities(arities: &[(usize, bool)]) -> String {
+    let arities: Vec<String> = arities
+        .iter()
+        .map(|&(required, more)| match more {
+            true => format!("at least {}", required),
+            false => required.to_string(),
+        })
+        .collect();
+
+    match arities.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, init)) => format!("{} or {}", init.join(", "), last),
+        None => "no arguments".into(),
+    }
+}
+
+struct Builder<'a> {
+    memory: &'a mut Memory,
+    components: Vec<RcValue>,
+}
+
+impl<'a> Builder<'a> {
+    fn simple(&mut self, rtd: &Rc<RecordType>, fields: Vec<RcValue>) {
+        let condition = self.memory.record(rtd.clone(), fields);
+        self.components.push(condition);
+    }
+
+    fn message(&mut self, rtd: &Rc<RecordType>, message: &str) {
+        let message = self.memory.string(message.chars().collect());
+        self.simple(rtd, vec![message]);
+    }
+
+    fn irritants(&mut self, rtd: &Rc<RecordType>, irritants: Vec<RcValue>) {
+        let irritants = self.memory.list(irritants);
+        self.simple(rtd, vec![irritants]);
+    }
+
+    fn build(self) -> RcValue {
+        self.memory.condition(self.components)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        compiler::compile, errors::RuntimeError::*, parser::parse,
+        values::RcValue, vm::VM,
+    };
+
+    fn inspect(error: crate::errors::RuntimeError, input: &str) -> String {
+        let mut vm = VM::new();
+        let condition = vm.condition(&error);
+        vm.env.set("c".into(), condition);
+
+        let bytecode =
+            compile(&parse(input).unwrap(), &mut vm.identifiers).unwrap();
+        let result: RcValue = vm.execute(bytecode).unwrap();
+        format!("{}", result)
+    }
+
+    #[test]
+    fn wrong_argument_type() {
+        let mut vm = VM::new();
+        let value = vm.memory.intern("a".into());
+
+        assert_eq!(
+            "(#t #t \"wrong argument type\" (a))",
+            inspect(
+                WrongArgumentType(value),
+                "(list (assertion-violation? c) (violation? c)
+                       (condition-message c) (condition-irritants c))"
+            )
+        );
+    }
+
+    #[test]
+    fn bad_arity() {
+        assert_eq!(
+            "(#t car \"wrong number of arguments\")",
+            inspect(
+                BadArity(Some("car".into())),
+                "(list (assertion-violation? c) (condition-who c)
+                       (condition-message c))"
+            )
+        );
+        assert_eq!(
+            "(#t #f)",
+            inspect(
+                BadArity(None),
+                "(list (assertion-violation? c) (who-condition? c))"
+            )
+        );
+    }
+
+    #[test]
+    fn unbound_variable() {
+        assert_eq!(
+            "(#t \"unbound variable\" (foo))",
+            inspect(
+                UnboundVariable("foo".into()),
+                "(list (undefined-violation? c) (condition-message c)
+                       (condition-irritants c))"
+            )
+        );
+    }
+
+    #[test]
+    fn malformed_expression() {
+        assert_eq!(
+            "(#t #f \"malformed expression\")",
+            inspect(
+                MalformedExpression,
+                "(list (syntax-violation? c) (syntax-violation-form c)
+                       (condition-message c))"
+            )
+        );
+    }
+
+    #[test]
+    fn syntax_violation() {
+        let mut vm = VM::new();
+        let form = vm.memory.intern("form".into());
+        let subform = vm.memory.integer(1);
+
+        assert_eq!(
+            "(#t form 1 who \"bad\")",
+            inspect(
+                SyntaxViolation(Some("who".into()), "bad".into(), form, subform),
+                "(list (syntax-violation? c) (syntax-violation-form c)
+                       (syntax-violation-subform c) (condition-who c)
+                       (condition-message c))"
+            )
+        );
+    }
+
+    #[test]
+    fn no_matching_arity() {
+        assert_eq!(
+            "(#t \"wrong number of arguments, expected 1, 2 or at least 4\" (3))",
+            inspect(
+                NoMatchingArity(vec![(1, false), (2, false), (4, true)], 3),
+                "(list (assertion-violation? c) (condition-message c)
+                       (condition-irritants c))"
+            )
+        );
+    }
+
+    #[test]
+    fn other_errors() {
+        let mut vm = VM::new();
+        let value = vm.memory.integer(1);
+
+        assert_eq!(
+            "(#t (1))",
+            inspect(
+                IndexOutOfRange(value.clone()),
+                "(list (assertion-violation? c) (condition-irritants c))"
+            )
+        );
+        assert_eq!(
+            "(#t (1))",
+            inspect(
+                ImmutableValue(value.clone()),
+                "(list (assertion-violation? c) (condition-irritants c))"
+            )
+        );
+        assert_eq!(
+            "(#t (1))",
+            inspect(
+                UnappliableValue(value),
+                "(list (assertion-violation? c) (condition-irritants c))"
+            )
+        );
+        assert_eq!(
+            "(#t vector-ref)",
+            inspect(
+                PrimitiveFailed("vector-ref".into()),
+                "(list (error? c) (condition-who c))"
+            )
+        );
+        assert_eq!(
+            "(#t #f)",
+            inspect(CannotPopLastFrame, "(list (error? c) (violation? c))")
+        );
+    }
+
+    #[test]
+    fn raised_objects() {
+        let mut vm = VM::new();
+        let value = vm.memory.intern("boom".into());
+
+        assert_eq!("boom", inspect(Raised(value), "c"));
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, rc::Rc};
+
+/// The ordered universe of symbols enum sets are drawn from.
+pub struct Enumeration {
+    pub symbols: Vec<String>,
+    indexes: HashMap<String, usize>,
+}
+
+impl Enumeration {
+    pub fn new(symbols: Vec<String>) -> Enumeration {
+        let mut unique = Vec::with_capacity(symbols.len());
+        let mut indexes = HashMap::new();
+
+        for symbol in symbols {
+            if !indexes.contains_key(&symbol) {
+                indexes.insert(symbol.clone(), unique.len());
+                unique.push(symbol);
+            }
+        }
+
+        Enumeration {
+            symbols: unique,
+            indexes,
+        }
+    }
+
+    pub fn index(&self, symbol: &str) -> Option<usize> {
+        self.indexes.get(symbol).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+/// A subset of an enumeration, with one flag per symbol of the universe.
+#[derive(Clone)]
+pub struct EnumSet {
+    pub universe: Rc<Enumeration>,
+    pub members: Vec<bool>,
+}
+
+impl EnumSet {
+    pub fn empty(universe: Rc<Enumeration>) -> EnumSet {
+        EnumSet {
+            members: vec![false; universe.len()],
+            universe,
+        }
+    }
+
+    pub fn full(universe: Rc<Enumeration>) -> EnumSet {
+        EnumSet {
+            members: vec![true; universe.len()],
+            universe,
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.universe
+            .index(symbol)
+            .is_some_and(|index| self.members[index])
+    }
+
+    /// Members, in the order of the universe.
+    pub fn symbols(&self) -> Vec<&str> {
+        self.universe
+            .symbols
+            .iter()
+            .zip(&self.members)
+            .filter(|&(_, &member)| member)
+            .map(|(symbol, _)| symbol.as_str())
+            .collect()
+    }
+
+    pub fn same_universe(&self, other: &EnumSet) -> bool {
+        Rc::ptr_eq(&self.universe, &other.universe)
+    }
+
+    /// Combines the members of two sets over the same universe.
+    pub fn combine<F>(&self, other: &EnumSet, f: F) -> EnumSet
+    where
+        F: Fn(bool, bool) -> bool,
+    {
+        EnumSet {
+            universe: self.universe.clone(),
+            members: self
+                .members
+                .iter()
+                .zip(&other.members)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        }
+    }
+
+    /// Whether every member of this set belongs to `other`, and this set's
+    /// universe is included in `other`'s.
+    pub fn is_subset_of(&self, other: &EnumSet) -> bool {
+        self.universe
+            .symbols
+            .iter()
+            .all(|symbol| other.universe.index(symbol).is_some())
+            && self.symbols().iter().all(|symbol| other.contains(symbol))
+    }
+}
+
+/// Procedures returned by `enum-set-indexer` and `enum-set-constructor`.
+#[derive(Clone)]
+pub enum EnumSetProcedure {
+    Indexer(Rc<Enumeration>),
+    Constructor(Rc<Enumeration>),
+}
+
+impl EnumSetProcedure {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EnumSetProcedure::Indexer(..) => "enum-set-indexer",
+            EnumSetProcedure::Constructor(..) => "enum-set-constructor",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EnumSet, Enumeration};
+    use std::rc::Rc;
+
+    fn universe(symbols: &[&str]) -> Rc<Enumeration> {
+        Rc::new(Enumeration::new(
+            symbols.iter().map(|&s| s.to_owned()).collect(),
+        ))
+    }
+
+    #[test]
+    fn duplicates_are_dropped() {
+        let colors = universe(&["red", "green", "red", "blue"]);
+
+        assert_eq!(vec!["red", "green", "blue"], colors.symbols);
+        assert_eq!(Some(2), colors.index("blue"));
+        assert_eq!(None, colors.index("purple"));
+    }
+
+    #[test]
+    fn set_operations() {
+        let colors = universe(&["red", "green", "blue"]);
+        let mut warm = EnumSet::empty(colors.clone());
+        warm.members[0] = true;
+        let full = EnumSet::full(colors);
+
+        assert_eq!(vec!["red"], warm.symbols());
+        assert!(warm.contains("red"));
+        assert!(!warm.contains("blue"));
+        assert!(warm.is_subset_of(&full));
+        assert!(!full.is_subset_of(&warm));
+        assert_eq!(
+            vec!["green", "blue"],
+            full.combine(&warm, |a, b| a && !b).symbols()
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use crate::values::RcValue;
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
+/// Where the value of a variable is stored. Environments importing a variable
+/// share its location, and so see assignments to it.
+pub type Location = Rc<RefCell<RcValue>>;
+
 #[derive(Clone)]
 pub struct CellEnv(Rc<RefCell<Env>>);
 
@@ -25,13 +29,23 @@ impl CellEnv {
         self.0.borrow_mut().replace(name, expr)
     }
 
+    /// The location `name` is bound to, here or in an outer environment.
+    pub fn location(&self, name: &str) -> Option<Location> {
+        self.0.borrow().location(name)
+    }
+
+    /// Binds `name` to a location another environment binds.
+    pub fn link(&self, name: String, location: Location) {
+        self.0.borrow_mut().defs.insert(name, location);
+    }
+
     fn build(env: Env) -> CellEnv {
         CellEnv(Rc::new(RefCell::new(env)))
     }
 }
 
 struct Env {
-    defs: HashMap<String, RcValue>,
+    defs: HashMap<String, Location>,
     outer: Option<CellEnv>,
 }
 
@@ -51,26 +65,33 @@ impl Env {
     }
 
     pub fn set(&mut self, name: String, expr: RcValue) {
-        self.defs.insert(name, expr);
+        self.defs.insert(name, Rc::new(RefCell::new(expr)));
     }
 
     pub fn get(&self, name: &str) -> Option<RcValue> {
         match self.defs.get(name) {
-            Some(value) => Some(value.clone()),
+            Some(location) => Some(location.borrow().clone()),
             None => self.get_from_outer(name),
         }
     }
 
     pub fn replace(&mut self, name: String, expr: RcValue) -> Option<RcValue> {
         match self.defs.get(&name) {
-            Some(_) => {
-                self.set(name, expr.clone());
+            Some(location) => {
+                *location.borrow_mut() = expr.clone();
                 Some(expr)
             }
             None => self.replace_on_outer(name, expr),
         }
     }
 
+    fn location(&self, name: &str) -> Option<Location> {
+        match self.defs.get(name) {
+            Some(location) => Some(location.clone()),
+            None => self.outer.as_ref().and_then(|outer| outer.location(name)),
+        }
+    }
+
     fn get_from_outer(&self, name: &str) -> Option<RcValue> {
         self.outer.clone().and_then(|env| env.get(name))
     }
@@ -124,4 +145,23 @@ mod test {
         assert_eq!(Some(inner_foo), inner.get("foo"));
         assert_eq!(Some(bar), inner.get("bar"));
     }
+
+    #[test]
+    fn linked_locations_are_shared() {
+        let mut mem = Memory::new();
+        let library = CellEnv::new();
+        let program = CellEnv::new();
+
+        library.set("foo".into(), mem.integer(1));
+        program.link("bar".into(), library.location("foo").unwrap());
+        library.replace("foo".into(), mem.integer(2));
+
+        assert_eq!(Some(mem.integer(2)), program.get("bar"));
+
+        program.replace("bar".into(), mem.integer(3));
+        assert_eq!(Some(mem.integer(3)), library.get("foo"));
+
+        program.set("bar".into(), mem.integer(4));
+        assert_eq!(Some(mem.integer(3)), library.get("foo"));
+    }
 }
@@ -21,10 +21,52 @@ impl<'a> From<ParseError<'a>> for Error<'a> {
 #[derive(PartialEq, Debug)]
 pub enum RuntimeError {
     BadArity(Option<String>),
+    BadValueCount(usize),
     CannotPopLastFrame,
+    /// Unwinds to the `guard` installed at a depth of the handler stack, with
+    /// the value of the clause that accepted the object raised.
+    GuardEscape(usize, RcValue),
+    ImmutableValue(RcValue),
+    IndexOutOfRange(RcValue),
+    /// Raised when the result of an arithmetic procedure, named here, is too
+    /// large to be represented.
+    IntegerOverflow(String),
+    /// Raised when a file can't be parsed: its path and where parsing
+    /// failed.
+    LexicalViolation(String, String),
+    /// Raised when a file can't be read: its path and why.
+    LoadFailed(String, String),
+    /// Raised by a form read from a file: where the form starts, as
+    /// `path:line:column`, and the error it raised.
+    Located(String, Box<RuntimeError>),
     MalformedExpression,
+    /// Raised when no clause of a `case-lambda` accepts the arguments: the
+    /// arity of each clause, a number of arguments and whether more are
+    /// accepted, and the number of arguments given.
+    NoMatchingArity(Vec<(usize, bool)>, usize),
     PrimitiveFailed(String),
+    /// An object raised that no exception handler was left to handle, or
+    /// that the handlers declined by raising again.
+    Raised(RcValue),
+    /// Raised by `syntax-violation`: who raised it, a message, the offending
+    /// form and the subform it was about, if any.
+    SyntaxViolation(Option<String>, String, RcValue, RcValue),
     UnappliableValue(RcValue),
     UnboundVariable(String),
+    /// Raised when an imported library is neither defined nor found on the
+    /// library path: the library reference, as written.
+    UnknownLibrary(String),
+    UninitializedVariable(String),
     WrongArgumentType(RcValue),
 }
+
+impl RuntimeError {
+    /// This error, as raised by a form starting at `location`. Escapes to a
+    /// `guard` are left as they are, for the `guard` to catch them.
+    pub fn located(self, location: String) -> RuntimeError {
+        match self {
+            RuntimeError::GuardEscape(..) => self,
+            error => RuntimeError::Located(location, Box::new(error)),
+        }
+    }
+}
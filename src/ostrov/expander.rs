@@ -0,0 +1,2706 @@
+use crate::{
+    ast::AST::{self, *},
+    compiler::compile_single,
+    env::CellEnv,
+    errors::RuntimeError,
+    files::{self, Forms, Location},
+    libraries::{self, ImportSet, Library, LibraryReference},
+    syntax_rules::{pattern_variables, CaseContext, SyntaxRules},
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    slice,
+};
+
+/// Forms that define at the top level, which the environments of `environment`
+/// don't allow.
+static DEFINITIONS: [&str; 7] = [
+    "define",
+    "define-enumeration",
+    "define-record-type",
+    "define-syntax",
+    "define-values",
+    "import",
+    "library",
+];
+
+/// Forms the expander or the compiler handle themselves. They are bound in the
+/// built-in top level, whence the standard libraries export them, and in the
+/// REPL's. Local variables named after them are renamed, so that they don't
+/// turn calls into special forms.
+static SPECIAL_FORMS: [&str; 42] = [
+    "and",
+    "assert",
+    "begin",
+    "case",
+    "case-lambda",
+    "cond",
+    "define",
+    "define-enumeration",
+    "define-record-type",
+    "define-syntax",
+    "define-values",
+    "delay",
+    "delay-force",
+    "do",
+    "guard",
+    "identifier-syntax",
+    "if",
+    "import",
+    "include",
+    "include-ci",
+    "lambda",
+    "let",
+    "let*",
+    "let*-values",
+    "let-syntax",
+    "let-values",
+    "letrec",
+    "letrec*",
+    "letrec-syntax",
+    "library",
+    "or",
+    "parameterize",
+    "quote",
+    "receive",
+    "set!",
+    "stream-cons",
+    "syntax",
+    "syntax-case",
+    "syntax-rules",
+    "unless",
+    "when",
+    "with-syntax",
+];
+
+/// Syntactic keywords introduced by definitions seen so far.
+enum Keyword {
+    /// `(type-name symbol)` checks `symbol` against the enumeration.
+    EnumerationType(Vec<String>),
+    /// `(constructor symbol ...)` builds an enum set of the enumeration bound
+    /// to the type name.
+    EnumerationConstructor(String, Vec<String>),
+}
+
+/// How a macro rewrites its uses.
+enum Transformer {
+    Rules(SyntaxRules),
+    /// A procedure from syntax objects to syntax objects, defined in `scope`.
+    /// Variable transformers also rewrite `set!`s of their keyword.
+    Procedure {
+        procedure: RcValue,
+        scope: Rc<Scope>,
+        variable: bool,
+    },
+}
+
+/// What an identifier denotes.
+#[derive(Clone)]
+enum Binding {
+    /// A local variable, and the name the compiler knows it by.
+    Variable(Rc<String>),
+    /// A pattern variable of `syntax-case`, the name of the variable holding
+    /// what it matched, and the number of ellipses following it.
+    PatternVariable(Rc<String>, usize),
+    Macro(Rc<Transformer>),
+    Keyword(Rc<Keyword>),
+    /// A special form imported under another name.
+    Special(Rc<String>),
+}
+
+/// An identifier is either bound, a special form, or free: a global variable.
+enum Resolution {
+    Bound(Binding),
+    SpecialForm(String),
+    Free(String),
+}
+
+/// The bindings of a region of code, nested in the region around it. The
+/// top-level scopes of programs and libraries have the environment their
+/// variables live in.
+pub struct Scope {
+    bindings: RefCell<HashMap<String, Binding>>,
+    outer: Option<Rc<Scope>>,
+    env: CellEnv,
+    /// Whether the top level allows definitions and assignments of its
+    /// variables, which the environments of `environment` don't.
+    mutable: bool,
+    /// The identifiers of the top level imported from libraries, which can't
+    /// be defined, nor assigned if they are variables.
+    imported: RefCell<HashSet<String>>,
+}
+
+impl Scope {
+    fn new(outer: &Rc<Scope>) -> Rc<Scope> {
+        Rc::new(Scope {
+            bindings: RefCell::new(HashMap::new()),
+            outer: Some(outer.clone()),
+            env: outer.env.clone(),
+            mutable: outer.mutable,
+            imported: RefCell::new(HashSet::new()),
+        })
+    }
+
+    /// A top-level scope, whose variables live in `env`.
+    pub fn top_level(env: CellEnv) -> Rc<Scope> {
+        Rc::new(Scope {
+            bindings: RefCell::new(HashMap::new()),
+            outer: None,
+            env,
+            mutable: true,
+            imported: RefCell::new(HashSet::new()),
+        })
+    }
+
+    /// A top-level scope that can't be defined in, nor its variables
+    /// assigned.
+    fn immutable(env: CellEnv) -> Rc<Scope> {
+        Rc::new(Scope {
+            bindings: RefCell::new(HashMap::new()),
+            outer: None,
+            env,
+            mutable: false,
+            imported: RefCell::new(HashSet::new()),
+        })
+    }
+
+    /// The top-level scope `scope` is nested in.
+    fn top(scope: &Rc<Scope>) -> Rc<Scope> {
+        match scope.outer {
+            Some(ref outer) => Scope::top(outer),
+            None => scope.clone(),
+        }
+    }
+
+    fn lookup(&self, identifier: &str) -> Option<Binding> {
+        match self.bindings.borrow().get(identifier) {
+            Some(binding) => Some(binding.clone()),
+            None => self
+                .outer
+                .as_ref()
+                .and_then(|outer| outer.lookup(identifier)),
+        }
+    }
+
+    fn bind(&self, identifier: String, binding: Binding) {
+        self.bindings.borrow_mut().insert(identifier, binding);
+    }
+}
+
+/// An identifier introduced by a macro expansion, standing for `name` as seen
+/// from the scope of the macro definition. The identifiers of the forms handed
+/// to procedural transformers are aliases too. Aliases made by an expansion
+/// carry its mark.
+struct Alias {
+    name: String,
+    scope: Rc<Scope>,
+    mark: Option<usize>,
+}
+
+/// What the expander knows about identifiers: the top-level scopes and the
+/// aliases macro expansions created. It lives in the VM, where the syntax
+/// object procedures that transformers call get to it.
+pub struct Identifiers {
+    global: Rc<Scope>,
+    /// The top level whose forms are being run.
+    current: Rc<Scope>,
+    /// The top level of the built-in bindings, which the standard libraries
+    /// export.
+    builtins: Rc<Scope>,
+    aliases: HashMap<String, Alias>,
+    /// Identifiers introduced by some macro. Local variables with these names
+    /// are renamed, so that they can't capture the macro's references.
+    introduced: HashSet<String>,
+    /// Marked aliases, by the identifier they stand for and their mark.
+    marked: HashMap<(String, usize), String>,
+    /// The scopes of the procedural transformers being run, innermost last.
+    transformers: Vec<Rc<Scope>>,
+    renamed: usize,
+    marks: usize,
+}
+
+impl Identifiers {
+    /// Identifiers of a program whose top-level variables live in `env`, with
+    /// the built-in bindings in `builtins`.
+    pub fn new(builtins: CellEnv, env: CellEnv) -> Identifiers {
+        let global = Scope::top_level(env);
+        let builtins = Scope::top_level(builtins);
+        for scope in &[&global, &builtins] {
+            for name in SPECIAL_FORMS.iter() {
+                let special = Binding::Special(Rc::new((*name).to_owned()));
+                scope.bind((*name).to_owned(), special);
+            }
+        }
+
+        Identifiers {
+            current: global.clone(),
+            global,
+            builtins,
+            aliases: HashMap::new(),
+            introduced: HashSet::new(),
+            marked: HashMap::new(),
+            transformers: vec![],
+            renamed: 0,
+            marks: 0,
+        }
+    }
+
+    /// The top level of the REPL.
+    pub fn global(&self) -> &Rc<Scope> {
+        &self.global
+    }
+
+    pub fn builtins(&self) -> &Rc<Scope> {
+        &self.builtins
+    }
+
+    /// The identifier an alias was created for, following aliases of aliases.
+    pub fn base(&self, identifier: &str) -> String {
+        match self.aliases.get(identifier) {
+            Some(alias) => self.base(&alias.name),
+            None => identifier.to_owned(),
+        }
+    }
+
+    /// Creates an alias of `identifier` as seen from `scope`.
+    pub fn alias(&mut self, identifier: &str, scope: &Rc<Scope>) -> String {
+        let alias = self.fresh(identifier);
+        self.aliases.insert(
+            alias.clone(),
+            Alias {
+                name: identifier.to_owned(),
+                scope: scope.clone(),
+                mark: None,
+            },
+        );
+        alias
+    }
+
+    /// A name no identifier has yet, based on `identifier`.
+    pub fn fresh(&mut self, identifier: &str) -> String {
+        self.renamed += 1;
+        format!("{}%{}", self.base(identifier), self.renamed)
+    }
+
+    /// Whether two identifiers, each seen from its own scope, denote the same
+    /// binding.
+    pub fn same_binding(
+        &self,
+        identifier: &str,
+        scope: &Rc<Scope>,
+        other: &str,
+        other_scope: &Rc<Scope>,
+    ) -> bool {
+        use self::{Binding::*, Resolution::*};
+
+        match (
+            self.resolve(identifier, scope),
+            self.resolve(other, other_scope),
+        ) {
+            (SpecialForm(name), SpecialForm(other))
+            | (Free(name), Free(other)) => name == other,
+            (Bound(Variable(name)), Bound(Variable(other)))
+            | (
+                Bound(PatternVariable(name, _)),
+                Bound(PatternVariable(other, _)),
+            ) => Rc::ptr_eq(&name, &other),
+            (Bound(Macro(name)), Bound(Macro(other))) => {
+                Rc::ptr_eq(&name, &other)
+            }
+            (Bound(Keyword(name)), Bound(Keyword(other))) => {
+                Rc::ptr_eq(&name, &other)
+            }
+            _ => false,
+        }
+    }
+
+    /// `free-identifier=?`. Identifiers introduced by the transformer being
+    /// run are seen from the scope it was defined in.
+    pub fn free_identifier_eq(&self, identifier: &str, other: &str) -> bool {
+        let scope = self.transformers.last().unwrap_or(&self.current);
+        self.same_binding(identifier, scope, other, scope)
+    }
+
+    /// A mark no expansion has yet.
+    pub fn new_mark(&mut self) -> usize {
+        self.marks += 1;
+        self.marks
+    }
+
+    /// The alias of `identifier` as seen from `scope` that the expansion
+    /// marked with `mark` introduces, the same for all its occurrences.
+    pub fn marked_alias(
+        &mut self,
+        identifier: &str,
+        scope: &Rc<Scope>,
+        mark: usize,
+    ) -> String {
+        let key = (identifier.to_owned(), mark);
+        if let Some(alias) = self.marked.get(&key) {
+            return alias.clone();
+        }
+
+        let alias = self.fresh(identifier);
+        self.aliases.insert(
+            alias.clone(),
+            Alias {
+                name: identifier.to_owned(),
+                scope: scope.clone(),
+                mark: Some(mark),
+            },
+        );
+        self.marked.insert(key, alias.clone());
+        alias
+    }
+
+    /// The identifier `datum->syntax` makes of `symbol`: the one the
+    /// expansions that introduced `template` would have made of it.
+    pub fn with_context(&mut self, symbol: &str, template: &str) -> String {
+        let (name, scope, mark) = match self.aliases.get(template) {
+            Some(alias) => (alias.name.clone(), alias.scope.clone(), alias.mark),
+            None => return symbol.to_owned(),
+        };
+
+        let name = self.with_context(symbol, &name);
+        match mark {
+            Some(mark) => self.marked_alias(&name, &scope, mark),
+            None => self.alias(&name, &scope),
+        }
+    }
+
+    /// Replaces aliases with the identifiers they were created for, as quoted
+    /// data and top-level names are plain symbols.
+    pub fn strip(&self, ast: &AST) -> AST {
+        map_atoms(ast, &mut |atom| self.base(atom))
+    }
+
+    fn strip_all(&self, items: &[AST]) -> Vec<AST> {
+        items.iter().map(|item| self.strip(item)).collect()
+    }
+
+    /// Replaces the identifiers of a macro use with aliases carrying a new
+    /// mark, which tells them apart from the identifiers a transformer
+    /// introduces.
+    fn mark(&mut self, form: &AST, scope: &Rc<Scope>) -> (AST, usize) {
+        let mark = self.new_mark();
+        let form =
+            map_atoms(form, &mut |atom| self.marked_alias(atom, scope, mark));
+
+        (form, mark)
+    }
+
+    /// Undoes `mark` on what a transformer returned. The identifiers that
+    /// don't carry the mark were introduced by the transformer, and are renamed
+    /// to refer to what they mean in `scope`, where it was defined.
+    fn unmark(&mut self, form: &AST, mark: usize, scope: &Rc<Scope>) -> AST {
+        let introduced = self.new_mark();
+
+        map_atoms(form, &mut |atom| match self.aliases.get(atom) {
+            Some(alias) if alias.mark == Some(mark) => alias.name.clone(),
+            _ => self.marked_alias(atom, scope, introduced),
+        })
+    }
+
+    /// The top-level scope a free identifier is seen from: the one of the
+    /// macro definition that introduced it, if any.
+    fn free_scope(&self, identifier: &str, scope: &Rc<Scope>) -> Rc<Scope> {
+        match self.aliases.get(identifier) {
+            Some(alias) if scope.lookup(identifier).is_none() => {
+                self.free_scope(&alias.name, &alias.scope)
+            }
+            _ => Scope::top(scope),
+        }
+    }
+
+    fn resolve(&self, identifier: &str, scope: &Rc<Scope>) -> Resolution {
+        match scope.lookup(identifier) {
+            Some(Binding::Special(name)) => {
+                return Resolution::SpecialForm((*name).clone())
+            }
+            Some(binding) => return Resolution::Bound(binding),
+            None => (),
+        }
+
+        match self.aliases.get(identifier) {
+            Some(alias) => self.resolve(&alias.name, &alias.scope),
+            None => Resolution::Free(identifier.to_owned()),
+        }
+    }
+}
+
+/// A form of a body, before or after its expansion.
+enum BodyForm {
+    Pending(AST),
+    Expanded(AST),
+    /// A form included from a file, and where it starts.
+    Located(Box<BodyForm>, Location),
+}
+
+/// Rewrites forms that need compile-time knowledge before they reach the
+/// compiler: macro uses and enumeration keywords. Macros are hygienic: the
+/// identifiers they introduce are aliases, which get fresh names when bound and
+/// otherwise refer to what they meant where the macro was defined. Procedural
+/// transformers run in the VM, and top-level keywords are remembered there
+/// across top-level forms.
+pub struct Expander<'a> {
+    vm: &'a mut VM,
+    /// The top-level scope of the program or library being expanded.
+    top: Rc<Scope>,
+}
+
+impl<'a> Expander<'a> {
+    /// An expander for the top level whose forms are being run, the one of the
+    /// REPL when none is.
+    pub fn new(vm: &'a mut VM) -> Expander<'a> {
+        let top = vm.identifiers.current.clone();
+        Expander { vm, top }
+    }
+
+    pub fn expand(&mut self, ast: &AST) -> Result<AST, RuntimeError> {
+        let top = self.top.clone();
+        self.expand_in(ast, &top)
+    }
+
+    /// Rewrites `ast` with the transformer of the macro it is a use of, if
+    /// any, leaving the identifiers the macro introduced renamed.
+    pub fn expand_once(
+        &mut self,
+        ast: &AST,
+    ) -> Result<Option<AST>, RuntimeError> {
+        let global = self.top.clone();
+
+        let transformer = match *ast {
+            Atom(ref identifier) => {
+                match self.vm.identifiers.resolve(identifier, &global) {
+                    Resolution::Bound(Binding::Macro(transformer)) => {
+                        Some(transformer)
+                    }
+                    _ => None,
+                }
+            }
+            _ => self.macro_use(ast, &global),
+        };
+
+        match transformer {
+            Some(transformer) => {
+                self.transform(&transformer, ast, &global).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// `ast` with its first macro use rewritten once: `ast` itself if it is
+    /// one, otherwise the first of its subforms, from left to right, that
+    /// has one. Quoted data and syntax definitions aren't looked into, and
+    /// the variables bound by `lambda`, `define` and the `let` forms shadow
+    /// the macros of the same name in their scope.
+    pub fn expand_step(
+        &mut self,
+        ast: &AST,
+    ) -> Result<Option<AST>, RuntimeError> {
+        let global = self.top.clone();
+        self.step_in(ast, &global, &[])
+    }
+
+    fn step_in(
+        &mut self,
+        ast: &AST,
+        scope: &Rc<Scope>,
+        shadowed: &[String],
+    ) -> Result<Option<AST>, RuntimeError> {
+        let list = match *ast {
+            Atom(ref identifier) if !shadowed.contains(identifier) => {
+                return match self.vm.identifiers.resolve(identifier, scope) {
+                    Resolution::Bound(Binding::Macro(transformer)) => {
+                        self.transform(&transformer, ast, scope).map(Some)
+                    }
+                    _ => Ok(None),
+                };
+            }
+            List(ref list) if !list.is_empty() => list,
+            _ => return Ok(None),
+        };
+
+        match list[0] {
+            Atom(ref head) if shadowed.contains(head) => {}
+            _ => {
+                if let Some(transformer) = self.macro_use(ast, scope) {
+                    return self.transform(&transformer, ast, scope).map(Some);
+                }
+            }
+        }
+
+        let name = match self.special_form(ast, scope) {
+            Some(name) => name,
+            None => return self.step_subforms(list, 0, scope, shadowed),
+        };
+
+        match (name.as_ref(), list.get(1)) {
+            ("quote", _)
+            | ("syntax", _)
+            | ("syntax-case", _)
+            | ("define-syntax", _)
+            | ("let-syntax", _)
+            | ("letrec-syntax", _)
+            | ("define-record-type", _)
+            | ("define-enumeration", _)
+            | ("library", _)
+            | ("import", _) => Ok(None),
+            ("lambda", Some(formals)) | ("define", Some(formals)) => {
+                let mut inner = shadowed.to_vec();
+                if name == "lambda" || !matches!(*formals, Atom(..)) {
+                    inner.extend(formals_names(formals));
+                }
+                self.step_subforms(list, 2, scope, &inner)
+            }
+            ("let", _) | ("let*", _) | ("letrec", _) | ("letrec*", _) => {
+                self.step_let(list, scope, shadowed)
+            }
+            ("case", Some(key)) => {
+                if let Some(stepped) = self.step_in(key, scope, shadowed)? {
+                    return Ok(Some(replaced(list, 1, stepped)));
+                }
+
+                for (index, clause) in list.iter().enumerate().skip(2) {
+                    if let List(ref clause) = *clause {
+                        if let Some(stepped) =
+                            self.step_subforms(clause, 1, scope, shadowed)?
+                        {
+                            return Ok(Some(replaced(list, index, stepped)));
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            _ => self.step_subforms(list, 1, scope, shadowed),
+        }
+    }
+
+    /// `list` with its first macro use from `from` on rewritten once.
+    fn step_subforms(
+        &mut self,
+        list: &[AST],
+        from: usize,
+        scope: &Rc<Scope>,
+        shadowed: &[String],
+    ) -> Result<Option<AST>, RuntimeError> {
+        for (index, form) in list.iter().enumerate().skip(from) {
+            if let Some(stepped) = self.step_in(form, scope, shadowed)? {
+                return Ok(Some(replaced(list, index, stepped)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Steps the inits of a `let` form, then its body, where the variables
+    /// it binds shadow macros.
+    fn step_let(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+        shadowed: &[String],
+    ) -> Result<Option<AST>, RuntimeError> {
+        let (at, mut inner) = match list.get(1) {
+            Some(Atom(ref name)) => (2, vec![name.clone()]),
+            _ => (1, vec![]),
+        };
+        let bindings = match list.get(at) {
+            Some(List(ref bindings)) => bindings,
+            _ => return Ok(None),
+        };
+
+        for (index, binding) in bindings.iter().enumerate() {
+            let binding = match *binding {
+                List(ref binding) if binding.len() == 2 => binding,
+                _ => continue,
+            };
+            inner.extend(formals_names(&binding[0]));
+
+            if let Some(stepped) = self.step_in(&binding[1], scope, shadowed)? {
+                let binding = replaced(binding, 1, stepped);
+                let bindings = replaced(bindings, index, binding);
+                return Ok(Some(replaced(list, at, bindings)));
+            }
+        }
+
+        inner.extend_from_slice(shadowed);
+        self.step_subforms(list, at + 1, scope, &inner)
+    }
+
+    /// The special form `form` is, if any.
+    fn special_form(&self, form: &AST, scope: &Rc<Scope>) -> Option<String> {
+        match *form {
+            List(ref list) if !list.is_empty() => match list[0] {
+                Atom(ref head) => {
+                    match self.vm.identifiers.resolve(head, scope) {
+                        Resolution::SpecialForm(name) => Some(name),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Binds `identifier` to a variable in `scope`, returning the name the
+    /// compiler will know it by. Top-level variables are global, so they keep
+    /// their name and shadow any keyword, but imported identifiers can't be
+    /// defined. Local `%` names are renamed, so that they don't capture the
+    /// internals the compiler refers to.
+    fn bind_variable(
+        &mut self,
+        identifier: &str,
+        scope: &Rc<Scope>,
+    ) -> Result<String, RuntimeError> {
+        if Rc::ptr_eq(scope, &self.top) {
+            let name = self.vm.identifiers.base(identifier);
+            self.check_not_imported(&name, scope)?;
+            scope.bindings.borrow_mut().remove(&name);
+            return Ok(name);
+        }
+
+        if let Some(Binding::Variable(name)) =
+            scope.bindings.borrow().get(identifier)
+        {
+            return Ok((**name).clone());
+        }
+
+        let identifiers = &mut self.vm.identifiers;
+
+        let name = if identifiers.aliases.contains_key(identifier)
+            || identifiers.introduced.contains(identifier)
+            || SPECIAL_FORMS.contains(&identifier)
+            || identifier.starts_with('%')
+        {
+            identifiers.fresh(identifier)
+        } else {
+            identifier.to_owned()
+        };
+
+        scope.bind(
+            identifier.to_owned(),
+            Binding::Variable(Rc::new(name.clone())),
+        );
+        Ok(name)
+    }
+
+    /// Fails with a syntax violation if `name` is imported at the top level
+    /// `top`, where it can't be defined.
+    fn check_not_imported(
+        &mut self,
+        name: &str,
+        top: &Rc<Scope>,
+    ) -> Result<(), RuntimeError> {
+        if top.imported.borrow().contains(name) {
+            return Err(self.syntax_violation(
+                "define",
+                "definition of an imported identifier",
+                &Atom(name.to_owned()),
+                name,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn bind_formals(
+        &mut self,
+        formals: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        match *formals {
+            Atom(ref identifier) => {
+                Ok(Atom(self.bind_variable(identifier, scope)?))
+            }
+            List(ref identifiers) => identifiers
+                .iter()
+                .map(|identifier| self.bind_identifier(identifier, scope))
+                .collect::<Result<_, _>>()
+                .map(List),
+            DottedList(ref identifiers, ref rest) => {
+                let identifiers = identifiers
+                    .iter()
+                    .map(|identifier| self.bind_identifier(identifier, scope))
+                    .collect::<Result<_, _>>()?;
+                let rest = self.bind_identifier(rest, scope)?;
+                Ok(DottedList(identifiers, Box::new(rest)))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    fn bind_identifier(
+        &mut self,
+        identifier: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        Ok(Atom(self.bind_variable(&atom(identifier)?, scope)?))
+    }
+
+    fn expand_in(
+        &mut self,
+        ast: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if let Some(transformer) = self.macro_use(ast, scope) {
+            let expanded = self.transform(&transformer, ast, scope)?;
+            return self.expand_in(&expanded, scope);
+        }
+
+        match *ast {
+            Atom(ref identifier) => self.expand_reference(identifier, scope),
+            List(ref list) if !list.is_empty() => self.expand_list(list, scope),
+            _ => Ok(self.vm.identifiers.strip(ast)),
+        }
+    }
+
+    /// A reference to `identifier`. Procedural transformers may rewrite
+    /// references to their keyword, pattern variables may only be referenced
+    /// in templates.
+    fn expand_reference(
+        &mut self,
+        identifier: &str,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        match self.vm.identifiers.resolve(identifier, scope) {
+            Resolution::Bound(Binding::Variable(name)) => {
+                Ok(Atom((*name).clone()))
+            }
+            Resolution::Bound(Binding::PatternVariable(..)) => {
+                Err(RuntimeError::MalformedExpression)
+            }
+            Resolution::Bound(Binding::Macro(transformer)) => {
+                let form = Atom(identifier.to_owned());
+                let expanded = self.transform(&transformer, &form, scope)?;
+                self.expand_in(&expanded, scope)
+            }
+            Resolution::Bound(Binding::Keyword(..)) => {
+                Ok(Atom(self.vm.identifiers.base(identifier)))
+            }
+            Resolution::Bound(Binding::Special(name)) => {
+                Ok(Atom((*name).clone()))
+            }
+            Resolution::SpecialForm(name) | Resolution::Free(name) => {
+                Ok(Atom(self.free_reference(identifier, name, scope)))
+            }
+        }
+    }
+
+    /// A reference to the free identifier `name`. When a macro of another
+    /// program or library introduced it, it refers to the variable of that top
+    /// level, whose location is linked into this one under a fresh name.
+    fn free_reference(
+        &mut self,
+        identifier: &str,
+        name: String,
+        scope: &Rc<Scope>,
+    ) -> String {
+        let top = self.vm.identifiers.free_scope(identifier, scope);
+        if Rc::ptr_eq(&top, &self.top) {
+            return name;
+        }
+
+        match (top.env.location(&name), self.top.env.location(&name)) {
+            (Some(location), Some(ref local)) if Rc::ptr_eq(&location, local) => {
+                name
+            }
+            (Some(location), _) => {
+                let linked = self.vm.identifiers.fresh(&name);
+                self.top.env.link(linked.clone(), location);
+                linked
+            }
+            (None, _) => name,
+        }
+    }
+
+    fn expand_list(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if let Atom(ref head) = list[0] {
+            match self.vm.identifiers.resolve(head, scope) {
+                Resolution::Bound(Binding::Keyword(keyword)) => {
+                    return keyword
+                        .expand(&self.vm.identifiers.strip_all(&list[1..]));
+                }
+                Resolution::Free(ref name)
+                    if SPECIAL_FORMS.contains(&name.as_str()) =>
+                {
+                    // A special form that isn't imported, unless a global
+                    // variable of its name is defined. That one is called
+                    // through a `begin`, so that the compiler doesn't take the
+                    // call for the special form.
+                    let top = self.vm.identifiers.free_scope(head, scope);
+                    if top.env.location(name).is_none() {
+                        return Err(RuntimeError::UnboundVariable(name.clone()));
+                    }
+
+                    let mut expanded = vec![List(vec![
+                        Atom("begin".into()),
+                        Atom(self.free_reference(head, name.clone(), scope)),
+                    ])];
+                    expanded.append(&mut self.expand_all(&list[1..], scope)?);
+                    return Ok(List(expanded));
+                }
+                Resolution::SpecialForm(ref name) if list.len() > 1 => match name
+                    .as_ref()
+                {
+                    "quote" => {
+                        return Ok(self
+                            .vm
+                            .identifiers
+                            .strip(&List(list.to_vec())))
+                    }
+                    "define-record-type" => {
+                        return self.expand_define_record_type(list, scope)
+                    }
+                    "lambda" => return self.expand_lambda(list, scope),
+                    "case-lambda" => return self.expand_case_lambda(list, scope),
+                    "define" => return self.expand_define(list, scope),
+                    "define-values" | "receive" => {
+                        return self.expand_values_binding(name, list, scope)
+                    }
+                    "let" | "let*" | "letrec" | "letrec*" | "let-values"
+                    | "let*-values" => return self.expand_let(name, list, scope),
+                    "do" => return self.expand_do(list, scope),
+                    "case" => return self.expand_case(list, scope),
+                    "cond" => return self.expand_cond(list, scope),
+                    "guard" => return self.expand_guard(list, scope),
+                    "set!" => return self.expand_set(list, scope),
+                    "syntax-case" => return self.expand_syntax_case(list, scope),
+                    "syntax" => return self.expand_syntax(list, scope),
+                    "with-syntax" => return self.expand_with_syntax(list, scope),
+                    "define-syntax" => return self.define_syntax(list, scope),
+                    "library" => return self.define_library(list, scope),
+                    "include" | "include-ci" => {
+                        let fold_case = name == "include-ci";
+                        let mut expanded = vec![Atom("begin".into())];
+                        for (form, location) in
+                            self.included_forms(list, fold_case)?
+                        {
+                            let form = self
+                                .expand_in(&form, scope)
+                                .map_err(|error| located(&location, error))?;
+                            expanded.push(locate_form(form, &location));
+                        }
+                        return Ok(List(expanded));
+                    }
+                    "import" => return self.expand_import(list, scope),
+                    "let-syntax" => {
+                        return self.expand_let_syntax(list, scope, false)
+                    }
+                    "letrec-syntax" => {
+                        return self.expand_let_syntax(list, scope, true)
+                    }
+                    "define-enumeration" => {
+                        return self.define_enumeration(&list[1..], scope)
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+
+        self.expand_all(list, scope).map(List)
+    }
+
+    fn expand_all(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<Vec<AST>, RuntimeError> {
+        list.iter().map(|ast| self.expand_in(ast, scope)).collect()
+    }
+
+    /// The transformer of the macro `form` is a use of, if any.
+    fn macro_use(
+        &self,
+        form: &AST,
+        scope: &Rc<Scope>,
+    ) -> Option<Rc<Transformer>> {
+        let head = match *form {
+            List(ref list) | DottedList(ref list, _) => list.first(),
+            _ => None,
+        };
+
+        match head {
+            Some(Atom(ref head)) => {
+                match self.vm.identifiers.resolve(head, scope) {
+                    Resolution::Bound(Binding::Macro(transformer)) => {
+                        Some(transformer)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Rewrites `form` as long as it is a macro use.
+    fn expand_head(
+        &mut self,
+        form: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let mut form = form.clone();
+
+        while let Some(transformer) = self.macro_use(&form, scope) {
+            form = self.transform(&transformer, &form, scope)?;
+        }
+
+        Ok(form)
+    }
+
+    /// Expands a body in `scope`. Its definitions, including the ones macros
+    /// expand to or `begin`s splice in, are bound before any of their values
+    /// or of the expressions are expanded.
+    fn expand_body(
+        &mut self,
+        body: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<Vec<AST>, RuntimeError> {
+        let mut forms = vec![];
+        self.scan_body(body, scope, &mut forms)?;
+
+        forms
+            .into_iter()
+            .map(|form| self.expand_body_form(form, scope))
+            .collect()
+    }
+
+    fn expand_body_form(
+        &mut self,
+        form: BodyForm,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        match form {
+            BodyForm::Pending(form) => self.expand_in(&form, scope),
+            BodyForm::Expanded(form) => Ok(form),
+            BodyForm::Located(form, location) => self
+                .expand_body_form(*form, scope)
+                .map(|form| locate_form(form, &location))
+                .map_err(|error| located(&location, error)),
+        }
+    }
+
+    fn scan_body(
+        &mut self,
+        body: &[AST],
+        scope: &Rc<Scope>,
+        forms: &mut Vec<BodyForm>,
+    ) -> Result<(), RuntimeError> {
+        for form in body {
+            let form = self.expand_head(form, scope)?;
+
+            let list = match form {
+                List(ref list) => list,
+                _ => {
+                    forms.push(BodyForm::Pending(form));
+                    continue;
+                }
+            };
+
+            match self.special_form(&form, scope).as_deref() {
+                Some("begin") => self.scan_body(&list[1..], scope, forms)?,
+                Some(keyword @ ("include" | "include-ci")) => {
+                    let fold_case = keyword == "include-ci";
+                    for (form, location) in
+                        self.included_forms(list, fold_case)?
+                    {
+                        let start = forms.len();
+                        self.scan_body(slice::from_ref(&form), scope, forms)
+                            .map_err(|error| located(&location, error))?;
+
+                        let scanned: Vec<_> = forms.drain(start..).collect();
+                        forms.extend(scanned.into_iter().map(|form| {
+                            BodyForm::Located(Box::new(form), location.clone())
+                        }));
+                    }
+                }
+                Some("define-syntax") => {
+                    self.define_syntax(list, scope)?;
+                }
+                Some("define") if list.len() > 1 => {
+                    match list[1] {
+                        List(ref target) | DottedList(ref target, _)
+                            if !target.is_empty() =>
+                        {
+                            self.bind_formals(&target[0], scope)?;
+                        }
+                        ref target => {
+                            self.bind_formals(target, scope)?;
+                        }
+                    }
+                    forms.push(BodyForm::Pending(form.clone()));
+                }
+                Some("define-values") if list.len() > 1 => {
+                    self.bind_formals(&list[1], scope)?;
+                    forms.push(BodyForm::Pending(form.clone()));
+                }
+                Some("define-record-type") if list.len() > 1 => {
+                    // Binds its names, leaving the clauses' expressions to be
+                    // expanded with the rest of the body.
+                    let names: Vec<_> = list
+                        .iter()
+                        .take(2)
+                        .chain(list[2..].iter().filter(|clause| match **clause {
+                            List(ref clause) => match clause.first() {
+                                Some(Atom(ref keyword)) => {
+                                    self.vm.identifiers.base(keyword) == "fields"
+                                }
+                                _ => false,
+                            },
+                            _ => false,
+                        }))
+                        .cloned()
+                        .collect();
+                    self.expand_define_record_type(&names, scope)?;
+                    forms.push(BodyForm::Pending(form.clone()));
+                }
+                Some("define-enumeration") => {
+                    let definition =
+                        self.define_enumeration(&list[1..], scope)?;
+                    forms.push(BodyForm::Expanded(definition));
+                }
+                _ => forms.push(BodyForm::Pending(form.clone())),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expand_lambda(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let inner = Scope::new(scope);
+
+        let mut expanded =
+            vec![Atom("lambda".into()), self.bind_formals(&list[1], &inner)?];
+        expanded.append(&mut self.expand_body(&list[2..], &inner)?);
+        Ok(List(expanded))
+    }
+
+    /// `(case-lambda (formals body ...) ...)`, each clause expanded as a
+    /// `lambda`.
+    fn expand_case_lambda(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let mut expanded = vec![Atom("case-lambda".into())];
+
+        for clause in &list[1..] {
+            let clause = match *clause {
+                List(ref clause) if clause.len() > 1 => clause,
+                _ => return Err(RuntimeError::MalformedExpression),
+            };
+
+            let inner = Scope::new(scope);
+            let mut lambda = vec![self.bind_formals(&clause[0], &inner)?];
+            lambda.append(&mut self.expand_body(&clause[1..], &inner)?);
+            expanded.push(List(lambda));
+        }
+
+        Ok(List(expanded))
+    }
+
+    /// `(define (name . formals) body ...)` is expanded as `(define name
+    /// (lambda formals body ...))`.
+    fn expand_define(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (target, formals) = match list[1] {
+            Atom(..) => {
+                let mut expanded = vec![
+                    Atom("define".into()),
+                    self.bind_formals(&list[1], scope)?,
+                ];
+                expanded.append(&mut self.expand_all(&list[2..], scope)?);
+                return Ok(List(expanded));
+            }
+            List(ref target) if !target.is_empty() => {
+                (&target[0], List(target[1..].to_vec()))
+            }
+            DottedList(ref target, ref rest) if target.len() == 1 => {
+                (&target[0], (**rest).clone())
+            }
+            DottedList(ref target, ref rest) if !target.is_empty() => {
+                (&target[0], DottedList(target[1..].to_vec(), rest.clone()))
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let builtins = self.vm.identifiers.builtins.clone();
+        let lambda = self.vm.identifiers.alias("lambda", &builtins);
+        let mut lambda = vec![Atom(lambda), formals];
+        lambda.extend_from_slice(&list[2..]);
+
+        self.expand_define(
+            &[list[0].clone(), target.clone(), List(lambda)],
+            scope,
+        )
+    }
+
+    /// `(define-values formals expr)` and `(receive formals expr body ...)`.
+    fn expand_values_binding(
+        &mut self,
+        form: &str,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if list.len() < 3 {
+            return Ok(self.vm.identifiers.strip(&List(list.to_vec())));
+        }
+
+        let expr = self.expand_in(&list[2], scope)?;
+
+        if form == "define-values" {
+            let formals = self.bind_formals(&list[1], scope)?;
+            let mut expanded = vec![Atom(form.into()), formals, expr];
+            expanded.append(&mut self.expand_all(&list[3..], scope)?);
+            return Ok(List(expanded));
+        }
+
+        let inner = Scope::new(scope);
+        let mut expanded = vec![
+            Atom(form.into()),
+            self.bind_formals(&list[1], &inner)?,
+            expr,
+        ];
+        expanded.append(&mut self.expand_body(&list[3..], &inner)?);
+        Ok(List(expanded))
+    }
+
+    /// `(define-record-type name-spec clause ...)`. The names it defines are
+    /// bound in `scope` and the expressions of its clauses expanded there.
+    /// Fields are written out with their accessor and mutator, whose default
+    /// names have the context of the record name, and the type keeps the
+    /// record name it was given when its variable is renamed.
+    fn expand_define_record_type(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (record, constructor, predicate) = self.record_names(&list[1])?;
+        let type_name = self.vm.identifiers.base(&record);
+        let name = self.bind_variable(&record, scope)?;
+
+        let mut expanded = vec![
+            Atom("define-record-type".into()),
+            List(vec![
+                Atom(name.clone()),
+                Atom(self.bind_variable(&constructor, scope)?),
+                Atom(self.bind_variable(&predicate, scope)?),
+            ]),
+        ];
+        if name != type_name {
+            expanded.push(List(vec![
+                Atom("%type-name".into()),
+                Atom(type_name.clone()),
+            ]));
+        }
+
+        for clause in &list[2..] {
+            let clause = match *clause {
+                List(ref clause) if !clause.is_empty() => clause,
+                _ => return Err(RuntimeError::MalformedExpression),
+            };
+
+            let keyword = self.vm.identifiers.base(&atom(&clause[0])?);
+            let mut args = match keyword.as_ref() {
+                "fields" => {
+                    let mut fields = vec![];
+                    for spec in &clause[1..] {
+                        fields.push(self.bind_field(&record, spec, scope)?);
+                    }
+                    fields
+                }
+                "nongenerative" => self.vm.identifiers.strip_all(&clause[1..]),
+                _ => self.expand_all(&clause[1..], scope)?,
+            };
+
+            args.insert(0, Atom(keyword));
+            expanded.push(List(args));
+        }
+
+        Ok(List(expanded))
+    }
+
+    /// The record name, constructor and predicate of a record name spec.
+    fn record_names(
+        &mut self,
+        spec: &AST,
+    ) -> Result<(String, String, String), RuntimeError> {
+        match *spec {
+            Atom(ref record) => {
+                let name = self.vm.identifiers.base(record);
+                let identifiers = &mut self.vm.identifiers;
+                Ok((
+                    record.clone(),
+                    identifiers.with_context(&format!("make-{}", name), record),
+                    identifiers.with_context(&format!("{}?", name), record),
+                ))
+            }
+            List(ref names) if names.len() == 3 => {
+                Ok((atom(&names[0])?, atom(&names[1])?, atom(&names[2])?))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    /// Binds the accessor and the mutator of the field spec `spec` of the
+    /// record named `record`, returning the spec with both of them named.
+    fn bind_field(
+        &mut self,
+        record: &str,
+        spec: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (kind, field, names) = match *spec {
+            Atom(ref field) => ("immutable".into(), field, &[][..]),
+            List(ref spec) if spec.len() >= 2 => match spec[1] {
+                Atom(ref field) => (
+                    self.vm.identifiers.base(&atom(&spec[0])?),
+                    field,
+                    &spec[2..],
+                ),
+                _ => return Err(RuntimeError::MalformedExpression),
+            },
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+        let field = self.vm.identifiers.base(field);
+        let name = self.vm.identifiers.base(record);
+        let accessor = format!("{}-{}", name, field);
+        let mutator = format!("{}-{}-set!", name, field);
+
+        let names = match (kind.as_ref(), names) {
+            ("immutable", []) => {
+                vec![self.vm.identifiers.with_context(&accessor, record)]
+            }
+            ("mutable", []) => vec![
+                self.vm.identifiers.with_context(&accessor, record),
+                self.vm.identifiers.with_context(&mutator, record),
+            ],
+            ("immutable", [_]) | ("mutable", [_, _]) => {
+                names.iter().map(atom).collect::<Result<_, _>>()?
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let mut expanded = vec![Atom(kind), Atom(field)];
+        for name in names {
+            expanded.push(Atom(self.bind_variable(&name, scope)?));
+        }
+        Ok(List(expanded))
+    }
+
+    /// `let` and its variants. The bindings' values are expanded in the scope
+    /// that `form` gives them, and the body in a new scope binding the
+    /// variables.
+    fn expand_let(
+        &mut self,
+        form: &str,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let mut expanded = vec![Atom(form.into())];
+        let mut scope = scope.clone();
+        let mut rest = &list[1..];
+
+        // Named `let`.
+        if let (Atom(ref name), "let", true) = (&rest[0], form, rest.len() > 1) {
+            let outer = Scope::new(&scope);
+            expanded.push(Atom(self.bind_variable(name, &outer)?));
+            rest = &rest[1..];
+
+            let bindings = match binding_pairs(&rest[0]) {
+                Some(bindings) => bindings,
+                None => {
+                    return Ok(self.vm.identifiers.strip(&List(list.to_vec())))
+                }
+            };
+
+            let inner = Scope::new(&outer);
+            let mut expanded_bindings = vec![];
+            for (variable, init) in bindings {
+                let init = self.expand_in(init, &scope)?;
+                let variable = self.bind_formals(variable, &inner)?;
+                expanded_bindings.push(List(vec![variable, init]));
+            }
+
+            expanded.push(List(expanded_bindings));
+            expanded.append(&mut self.expand_body(&rest[1..], &inner)?);
+            return Ok(List(expanded));
+        }
+
+        let bindings = match binding_pairs(&rest[0]) {
+            Some(bindings) => bindings,
+            None => return Ok(self.vm.identifiers.strip(&List(list.to_vec()))),
+        };
+
+        let mut expanded_bindings = vec![];
+
+        match form {
+            "let*" | "let*-values" => {
+                for (formals, init) in bindings {
+                    let init = self.expand_in(init, &scope)?;
+                    scope = Scope::new(&scope);
+                    let formals = self.bind_formals(formals, &scope)?;
+                    expanded_bindings.push(List(vec![formals, init]));
+                }
+            }
+            "letrec" | "letrec*" => {
+                scope = Scope::new(&scope);
+
+                let formals = bindings
+                    .iter()
+                    .map(|(formals, _)| self.bind_formals(formals, &scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for (formals, (_, init)) in formals.into_iter().zip(bindings) {
+                    let init = self.expand_in(init, &scope)?;
+                    expanded_bindings.push(List(vec![formals, init]));
+                }
+            }
+            _ => {
+                let inner = Scope::new(&scope);
+
+                for (formals, init) in bindings {
+                    let init = self.expand_in(init, &scope)?;
+                    let formals = self.bind_formals(formals, &inner)?;
+                    expanded_bindings.push(List(vec![formals, init]));
+                }
+
+                scope = inner;
+            }
+        }
+
+        expanded.push(List(expanded_bindings));
+        expanded.append(&mut self.expand_body(&rest[1..], &scope)?);
+        Ok(List(expanded))
+    }
+
+    /// `(do ((variable init step) ...) (test expr ...) command ...)`.
+    fn expand_do(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let specs = match (&list[1], list.get(2)) {
+            (List(ref specs), Some(List(..))) => specs,
+            _ => return Ok(self.vm.identifiers.strip(&List(list.to_vec()))),
+        };
+
+        let inner = Scope::new(scope);
+        let mut expanded_specs = vec![];
+
+        for spec in specs {
+            match *spec {
+                List(ref spec) if spec.len() == 2 || spec.len() == 3 => {
+                    let init = self.expand_in(&spec[1], scope)?;
+                    let variable = self.bind_identifier(&spec[0], &inner)?;
+                    expanded_specs.push((variable, init, spec.get(2)));
+                }
+                _ => return Ok(self.vm.identifiers.strip(&List(list.to_vec()))),
+            }
+        }
+
+        let mut specs = vec![];
+        for (variable, init, step) in expanded_specs {
+            let mut spec = vec![variable, init];
+            if let Some(step) = step {
+                spec.push(self.expand_in(step, &inner)?);
+            }
+            specs.push(List(spec));
+        }
+
+        let mut expanded = vec![Atom("do".into()), List(specs)];
+        expanded.append(&mut self.expand_all(&list[2..], &inner)?);
+        Ok(List(expanded))
+    }
+
+    /// `(case key ((datum ...) expr ...) ...)`, whose datums are quoted.
+    fn expand_case(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let mut expanded =
+            vec![Atom("case".into()), self.expand_in(&list[1], scope)?];
+
+        for clause in &list[2..] {
+            match *clause {
+                List(ref clause) if !clause.is_empty() => {
+                    let mut expanded_clause =
+                        vec![self.vm.identifiers.strip(&clause[0])];
+                    expanded_clause
+                        .append(&mut self.expand_all(&clause[1..], scope)?);
+                    expanded.push(List(expanded_clause));
+                }
+                _ => expanded.push(self.vm.identifiers.strip(clause)),
+            }
+        }
+
+        Ok(List(expanded))
+    }
+
+    /// `(cond clause ...)`, whose clauses are not calls.
+    fn expand_cond(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let mut expanded = vec![Atom("cond".into())];
+        expanded.append(&mut self.expand_clauses(&list[1..], scope)?);
+        Ok(List(expanded))
+    }
+
+    /// The clauses of a `cond` or a `guard`.
+    fn expand_clauses(
+        &mut self,
+        clauses: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<Vec<AST>, RuntimeError> {
+        clauses
+            .iter()
+            .map(|clause| match *clause {
+                List(ref clause) => self.expand_all(clause, scope).map(List),
+                _ => Ok(self.vm.identifiers.strip(clause)),
+            })
+            .collect()
+    }
+
+    /// `(guard (variable clause ...) body ...)`, whose clauses are in the
+    /// scope of the variable.
+    fn expand_guard(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (variable, clauses) = match list[1] {
+            List(ref spec) if !spec.is_empty() => (&spec[0], &spec[1..]),
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let inner = Scope::new(scope);
+        let mut spec = vec![self.bind_identifier(variable, &inner)?];
+        spec.append(&mut self.expand_clauses(clauses, &inner)?);
+
+        let mut expanded = vec![Atom("guard".into()), List(spec)];
+        expanded.append(&mut self.expand_body(&list[2..], &Scope::new(scope))?);
+        Ok(List(expanded))
+    }
+
+    /// `(define-syntax keyword transformer)` binds the keyword in `scope`.
+    fn define_syntax(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if list.len() != 3 {
+            return Err(RuntimeError::BadArity(Some("define-syntax".into())));
+        }
+
+        let keyword = atom(&list[1])?;
+        let transformer = self.transformer(&list[2], scope)?;
+
+        let keyword = if Rc::ptr_eq(scope, &self.top) {
+            let keyword = self.vm.identifiers.base(&keyword);
+            self.check_not_imported(&keyword, scope)?;
+            keyword
+        } else {
+            keyword
+        };
+        scope.bind(keyword, Binding::Macro(transformer));
+
+        Ok(List(vec![Atom("begin".into())]))
+    }
+
+    /// `(library name (export spec ...) (import spec ...) body ...)` defines
+    /// a library. Its body is expanded and run form by form in a top level of
+    /// its own, where only the imported bindings are visible.
+    fn define_library(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if !Rc::ptr_eq(scope, &self.top) || list.len() < 4 {
+            return Err(RuntimeError::MalformedExpression);
+        }
+
+        let (name, version) =
+            libraries::library_name(&self.vm.identifiers.strip(&list[1]))?;
+        let exports = libraries::exports(&self.clause("export", &list[2])?)?;
+        let imports = self.clause("import", &list[3])?;
+
+        let env = self.vm.isolated_env();
+        let mut expander = Expander {
+            vm: &mut *self.vm,
+            top: Scope::top_level(env),
+        };
+        for import in &imports {
+            expander.import(import)?;
+        }
+        expander.run(&list[4..])?;
+
+        let top = expander.top;
+        for (_, internal) in &exports {
+            let defined = top.bindings.borrow().contains_key(internal)
+                || top.env.location(internal).is_some();
+
+            if !defined {
+                return Err(self.syntax_violation(
+                    "library",
+                    "exported identifier not defined",
+                    &list[2],
+                    internal,
+                ));
+            }
+        }
+
+        self.vm.libraries.define(Library {
+            name,
+            version,
+            scope: top,
+            exports,
+        });
+
+        Ok(List(vec![Atom("begin".into())]))
+    }
+
+    /// The items of the clause `(keyword item ...)`, plain data.
+    fn clause(
+        &self,
+        keyword: &str,
+        form: &AST,
+    ) -> Result<Vec<AST>, RuntimeError> {
+        match self.vm.identifiers.strip(form) {
+            List(mut items) if items.first() == Some(&Atom(keyword.into())) => {
+                Ok(items.split_off(1))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    /// Expands, compiles and runs `forms` one after the other at the top level,
+    /// in its environment, returning their values.
+    pub fn run(&mut self, forms: &[AST]) -> Result<Vec<RcValue>, RuntimeError> {
+        self.at_top(|expander| {
+            forms.iter().map(|form| expander.run_form(form)).collect()
+        })
+    }
+
+    /// Runs `f` with the environment of the top level as the current one, and
+    /// the top level as the one `load` loads into.
+    fn at_top<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        let env = mem::replace(&mut self.vm.env, self.top.env.clone());
+        let current =
+            mem::replace(&mut self.vm.identifiers.current, self.top.clone());
+
+        let result = f(self);
+
+        self.vm.env = env;
+        self.vm.identifiers.current = current;
+        result
+    }
+
+    /// Expands, compiles and runs the top-level form `form`. The forms of the
+    /// files it includes, if it is an `include` form, are run one by one
+    /// instead, so that their errors are located.
+    fn run_form(&mut self, form: &AST) -> Result<RcValue, RuntimeError> {
+        let top = self.top.clone();
+        let form = self.expand_head(form, &top)?;
+
+        if let List(ref list) = form {
+            if let Some(fold_case) = self.include_form(&form, &top) {
+                let mut value = self.vm.memory.unspecified();
+                for (path, forms) in self.include(list, fold_case)? {
+                    value = self.run_located(&path, forms)?;
+                }
+                return Ok(value);
+            }
+        }
+
+        let expanded = self.expand_in(&form, &top)?;
+        let bytecode = compile_single(&expanded, &mut self.vm.identifiers)?;
+        self.vm.execute(bytecode)
+    }
+
+    /// Runs the forms read from the file at `path`, against which relative
+    /// paths are resolved meanwhile. Errors are wrapped with the location of
+    /// the form that raised them. Returns the value of the last form.
+    fn run_located(
+        &mut self,
+        path: &Path,
+        forms: Forms,
+    ) -> Result<RcValue, RuntimeError> {
+        self.vm.loading.push(path.to_owned());
+
+        let mut result = Ok(self.vm.memory.unspecified());
+        for (form, location) in forms {
+            result = self
+                .run_form(&form)
+                .map_err(|error| located(&location, error));
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.vm.loading.pop();
+        result
+    }
+
+    /// Runs the top-level program `forms`: `(import spec ...)` followed by a
+    /// body, run form by form in a top level of its own where only the
+    /// imported bindings are visible. Returns the values of the body forms.
+    pub fn run_program(
+        &mut self,
+        forms: &[AST],
+    ) -> Result<Vec<RcValue>, RuntimeError> {
+        match forms.split_first() {
+            Some((import, body)) => self.program(import)?.run(body),
+            None => Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    /// Runs the file at `path`: a top-level program if it starts with an
+    /// `import` form, otherwise forms loaded at this top level.
+    pub fn run_file(&mut self, path: &Path) -> Result<RcValue, RuntimeError> {
+        let mut forms = files::read(path)?;
+
+        match forms.first() {
+            Some((List(ref list), _)) if is_import(list) => {
+                let (import, location) = forms.remove(0);
+                let mut program = self
+                    .program(&import)
+                    .map_err(|error| located(&location, error))?;
+
+                program.at_top(|program| program.run_located(path, forms))
+            }
+            _ => self.at_top(|expander| expander.run_located(path, forms)),
+        }
+    }
+
+    /// Loads the file at `path`, running its forms at this top level.
+    pub fn load(&mut self, path: &Path) -> Result<RcValue, RuntimeError> {
+        let forms = files::read(path)?;
+        self.at_top(|expander| expander.run_located(path, forms))
+    }
+
+    /// An expander for the top level of a program starting with `import`,
+    /// which must be an `(import spec ...)` form. Only the bindings it imports
+    /// are visible there.
+    fn program(&mut self, import: &AST) -> Result<Expander<'_>, RuntimeError> {
+        let specs = match *import {
+            List(ref list) if is_import(list) => &list[1..],
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let env = self.vm.isolated_env();
+        let mut program = Expander {
+            vm: &mut *self.vm,
+            top: Scope::top_level(env),
+        };
+        for spec in specs {
+            program.import(spec)?;
+        }
+
+        Ok(program)
+    }
+
+    /// Whether `form` is an `include` form, and if so whether it is an
+    /// `include-ci` one.
+    fn include_form(&self, form: &AST, scope: &Rc<Scope>) -> Option<bool> {
+        match self.special_form(form, scope).as_deref() {
+            Some("include") => Some(false),
+            Some("include-ci") => Some(true),
+            _ => None,
+        }
+    }
+
+    /// The files `(include file-name ...)` includes: their paths, and their
+    /// forms with their identifiers folded to lower case if `fold_case`.
+    /// Relative file names are resolved against the file being loaded.
+    fn include(
+        &mut self,
+        list: &[AST],
+        fold_case: bool,
+    ) -> Result<Vec<(PathBuf, Forms)>, RuntimeError> {
+        list[1..]
+            .iter()
+            .map(|name| {
+                let name = match *name {
+                    Str(ref name) => name,
+                    _ => return Err(RuntimeError::MalformedExpression),
+                };
+                let path = files::resolve(name, &self.vm.loading);
+
+                let mut forms = files::read(&path)?;
+                if fold_case {
+                    for (form, _) in &mut forms {
+                        *form = map_atoms(form, &mut |atom| atom.to_lowercase());
+                    }
+                }
+
+                Ok((path, forms))
+            })
+            .collect()
+    }
+
+    /// The forms of the files `(include file-name ...)` includes, spliced
+    /// together, each with its location.
+    fn included_forms(
+        &mut self,
+        list: &[AST],
+        fold_case: bool,
+    ) -> Result<Forms, RuntimeError> {
+        Ok(self
+            .include(list, fold_case)?
+            .into_iter()
+            .flat_map(|(_, forms)| forms)
+            .collect())
+    }
+
+    /// The top level of `(environment import-spec ...)`, where only the
+    /// imported bindings are visible.
+    pub fn environment(
+        &mut self,
+        specs: &[AST],
+    ) -> Result<Rc<Scope>, RuntimeError> {
+        let mut expander = Expander {
+            top: Scope::immutable(self.vm.isolated_env()),
+            vm: &mut *self.vm,
+        };
+        for spec in specs {
+            expander.import(spec)?;
+        }
+
+        Ok(expander.top)
+    }
+
+    /// Expands, compiles and runs `form` at the top level `top`, which must
+    /// not be a definition if `top` is immutable.
+    pub fn eval(
+        &mut self,
+        form: &AST,
+        top: &Rc<Scope>,
+    ) -> Result<RcValue, RuntimeError> {
+        let mut expander = Expander {
+            vm: &mut *self.vm,
+            top: top.clone(),
+        };
+
+        if !top.mutable {
+            if let Some(keyword) = expander.definition(form, top)? {
+                return Err(expander.syntax_violation(
+                    "eval",
+                    "definition in an immutable environment",
+                    form,
+                    &keyword,
+                ));
+            }
+        }
+
+        let mut values = expander.run(slice::from_ref(form))?;
+        Ok(values.remove(0))
+    }
+
+    /// The keyword of the definition `form` is, or of one that a `begin` form
+    /// splices in, if any.
+    fn definition(
+        &mut self,
+        form: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<Option<String>, RuntimeError> {
+        let form = self.expand_head(form, scope)?;
+
+        match (self.special_form(&form, scope), form) {
+            (Some(ref keyword), List(ref list)) if keyword == "begin" => {
+                for form in &list[1..] {
+                    if let Some(keyword) = self.definition(form, scope)? {
+                        return Ok(Some(keyword));
+                    }
+                }
+                Ok(None)
+            }
+            (Some(keyword), _) if DEFINITIONS.contains(&keyword.as_ref()) => {
+                Ok(Some(keyword))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `(import spec ...)`, which imports into the top level.
+    fn expand_import(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if !Rc::ptr_eq(scope, &self.top) {
+            return Err(RuntimeError::MalformedExpression);
+        }
+
+        for spec in self.vm.identifiers.strip_all(&list[1..]) {
+            self.import(&spec)?;
+        }
+
+        Ok(List(vec![Atom("begin".into())]))
+    }
+
+    /// Binds the identifiers of the import spec `spec` at the top level.
+    fn import(&mut self, spec: &AST) -> Result<(), RuntimeError> {
+        let set = ImportSet::parse(spec)?;
+        let library = self.library(set.library())?;
+
+        let exported: Vec<String> = library
+            .exports
+            .iter()
+            .map(|(external, _)| external.clone())
+            .collect();
+        let names = match set.names(&exported) {
+            Ok(names) => names,
+            Err(identifier) => {
+                return Err(self.syntax_violation(
+                    "import",
+                    "identifier not exported",
+                    spec,
+                    &identifier,
+                ))
+            }
+        };
+
+        for (name, external) in names {
+            let internal = library.internal(&external).unwrap_or(&external);
+            self.import_binding(&library.scope, internal, name);
+        }
+
+        Ok(())
+    }
+
+    /// Binds `name` at the top level to what `internal` is bound to at the
+    /// top level `from` of a library: a keyword, a special form, or a variable
+    /// whose location both then share.
+    fn import_binding(&mut self, from: &Rc<Scope>, internal: &str, name: String) {
+        let binding = from.bindings.borrow().get(internal).cloned();
+        let location = from.env.location(internal);
+
+        match binding {
+            Some(binding) => self.top.bind(name.clone(), binding),
+            None => {
+                self.top.bindings.borrow_mut().remove(&name);
+            }
+        }
+
+        self.top.imported.borrow_mut().insert(name.clone());
+        if let Some(location) = location {
+            self.top.env.link(name, location);
+        }
+    }
+
+    /// The library `reference` refers to, loaded from the library path if it
+    /// isn't defined yet.
+    fn library(
+        &mut self,
+        reference: &LibraryReference,
+    ) -> Result<Rc<Library>, RuntimeError> {
+        if self.vm.libraries.find(&reference.name).is_none() {
+            if let Some(path) = self.vm.libraries.file(&reference.name) {
+                self.load_libraries(&path)?;
+            }
+        }
+
+        match self.vm.libraries.find(&reference.name) {
+            Some(library) if reference.version.matches(&library.version) => {
+                Ok(library)
+            }
+            _ => Err(RuntimeError::UnknownLibrary(reference.written.clone())),
+        }
+    }
+
+    /// Defines the libraries of the file at `path`, unless it is already
+    /// being loaded.
+    fn load_libraries(&mut self, path: &Path) -> Result<(), RuntimeError> {
+        if !self.vm.libraries.start_loading(path) {
+            return Ok(());
+        }
+
+        let result = self.define_libraries(path);
+        self.vm.libraries.finish_loading(path);
+        result
+    }
+
+    fn define_libraries(&mut self, path: &Path) -> Result<(), RuntimeError> {
+        let forms = files::read(path)?;
+
+        let mut expander = Expander::new(&mut *self.vm);
+        let top = expander.top.clone();
+        for (form, location) in &forms {
+            match *form {
+                List(ref list)
+                    if list.first() == Some(&Atom("library".into())) =>
+                {
+                    expander
+                        .define_library(list, &top)
+                        .map_err(|error| located(location, error))?;
+                }
+                _ => {
+                    let form = Value::from_ast(form, &mut self.vm.memory);
+                    let no_subform = self.vm.memory.b_false();
+                    let error = RuntimeError::SyntaxViolation(
+                        None,
+                        "expected a library form".into(),
+                        form,
+                        no_subform,
+                    );
+                    return Err(located(location, error));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn syntax_violation(
+        &mut self,
+        who: &str,
+        message: &str,
+        form: &AST,
+        subform: &str,
+    ) -> RuntimeError {
+        let form = self.vm.identifiers.strip(form);
+        let form = Value::from_ast(&form, &mut self.vm.memory);
+        let subform = self.vm.memory.intern(subform.to_owned());
+
+        RuntimeError::SyntaxViolation(
+            Some(who.into()),
+            message.into(),
+            form,
+            subform,
+        )
+    }
+
+    /// `(let-syntax ((keyword transformer) ...) body ...)` and `letrec-syntax`,
+    /// whose transformers are defined in the scope of their own keywords.
+    fn expand_let_syntax(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+        recursive: bool,
+    ) -> Result<AST, RuntimeError> {
+        let bindings = match binding_pairs(&list[1]) {
+            Some(bindings) => bindings,
+            None => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let inner = Scope::new(scope);
+
+        for (keyword, transformer) in bindings {
+            let keyword = atom(keyword)?;
+            let transformer = if recursive {
+                self.transformer(transformer, &inner)?
+            } else {
+                self.transformer(transformer, scope)?
+            };
+            inner.bind(keyword, Binding::Macro(transformer));
+        }
+
+        let mut expanded = vec![Atom("let".into()), List(vec![])];
+        expanded.append(&mut self.expand_body(&list[2..], &inner)?);
+        Ok(List(expanded))
+    }
+
+    /// Builds the transformer `spec` defined in `scope`: `(syntax-rules ...)`,
+    /// `(identifier-syntax ...)`, or an expression evaluating to a procedure.
+    fn transformer(
+        &mut self,
+        spec: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<Rc<Transformer>, RuntimeError> {
+        let args = match *spec {
+            List(ref spec) if !spec.is_empty() => &spec[1..],
+            _ => &[],
+        };
+
+        match self.special_form(spec, scope).as_deref() {
+            Some("syntax-rules") => {
+                let rules = SyntaxRules::new(args, scope.clone())?;
+                let introduced = rules.introduced(&mut self.vm.identifiers);
+                self.vm.identifiers.introduced.extend(introduced);
+
+                Ok(Rc::new(Transformer::Rules(rules)))
+            }
+            Some("identifier-syntax") => {
+                let (procedure, variable) = self.identifier_syntax(args)?;
+                self.procedure_transformer(&procedure, scope, variable)
+            }
+            _ => self.procedure_transformer(spec, scope, false),
+        }
+    }
+
+    /// Evaluates `spec`, which must yield the procedure of a transformer.
+    fn procedure_transformer(
+        &mut self,
+        spec: &AST,
+        scope: &Rc<Scope>,
+        variable: bool,
+    ) -> Result<Rc<Transformer>, RuntimeError> {
+        let expanded = self.expand_in(spec, scope)?;
+        let bytecode = compile_single(&expanded, &mut self.vm.identifiers)?;
+        let procedure = self.vm.execute(bytecode)?;
+
+        if !procedure.is_procedure() {
+            return Err(RuntimeError::WrongArgumentType(procedure));
+        }
+
+        Ok(Rc::new(Transformer::Procedure {
+            procedure,
+            scope: scope.clone(),
+            variable,
+        }))
+    }
+
+    /// Rewrites `(identifier-syntax template)` and `(identifier-syntax (id1
+    /// template1) ((set! id2 pattern) template2))` as the `syntax-case`
+    /// transformers they stand for, telling whether the latter, a variable
+    /// transformer, was given.
+    fn identifier_syntax(
+        &mut self,
+        args: &[AST],
+    ) -> Result<(AST, bool), RuntimeError> {
+        let builtins = self.vm.identifiers.builtins.clone();
+        let mut alias =
+            |name: &str| Atom(self.vm.identifiers.alias(name, &builtins));
+
+        let (lambda, syntax_case, syntax, is_identifier) = (
+            alias("lambda"),
+            alias("syntax-case"),
+            alias("syntax"),
+            alias("identifier?"),
+        );
+        let (x, id, arg) = (alias("x"), alias("id"), alias("arg"));
+        let ellipsis = Atom("...".into());
+
+        let (literals, mut clauses, reference, template) = match args {
+            [template] => (vec![], vec![], id, template.clone()),
+            [List(ref reference), List(ref assignment)]
+                if reference.len() == 2 && assignment.len() == 2 =>
+            {
+                let literal = match assignment[0] {
+                    List(ref pattern) if pattern.len() == 3 => pattern[0].clone(),
+                    _ => return Err(RuntimeError::MalformedExpression),
+                };
+                let clause = List(vec![
+                    assignment[0].clone(),
+                    List(vec![syntax.clone(), assignment[1].clone()]),
+                ]);
+
+                (
+                    vec![literal],
+                    vec![clause],
+                    reference[0].clone(),
+                    reference[1].clone(),
+                )
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+        let variable = !literals.is_empty();
+
+        clauses.push(List(vec![
+            reference.clone(),
+            List(vec![is_identifier, List(vec![syntax.clone(), reference])]),
+            List(vec![syntax.clone(), template.clone()]),
+        ]));
+        clauses.push(List(vec![
+            List(vec![Atom("_".into()), arg.clone(), ellipsis.clone()]),
+            List(vec![syntax, List(vec![template, arg, ellipsis])]),
+        ]));
+
+        let mut syntax_case = vec![syntax_case, x.clone(), List(literals)];
+        syntax_case.append(&mut clauses);
+
+        Ok((
+            List(vec![lambda, List(vec![x]), List(syntax_case)]),
+            variable,
+        ))
+    }
+
+    /// Rewrites the macro use `form` with `transformer`. Procedural
+    /// transformers get `form` as a syntax object, with its identifiers
+    /// marked, and the identifiers they introduce are renamed.
+    fn transform(
+        &mut self,
+        transformer: &Transformer,
+        form: &AST,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (procedure, definition) = match *transformer {
+            Transformer::Rules(ref rules) => {
+                return rules.expand(form, &mut self.vm.identifiers, scope)
+            }
+            Transformer::Procedure {
+                ref procedure,
+                ref scope,
+                ..
+            } => (procedure.clone(), scope),
+        };
+
+        let (input, mark) = self.vm.identifiers.mark(form, scope);
+        let input = self.vm.memory.syntax(input);
+
+        self.vm.identifiers.transformers.push(definition.clone());
+        let output = self.vm.call(procedure, vec![input]);
+        self.vm.identifiers.transformers.pop();
+
+        let output = output?;
+        match output.to_ast() {
+            Some(output) => {
+                Ok(self.vm.identifiers.unmark(&output, mark, definition))
+            }
+            None => Err(RuntimeError::WrongArgumentType(output)),
+        }
+    }
+
+    /// `(set! variable expr)`, which variable transformers rewrite when
+    /// `variable` is their keyword.
+    fn expand_set(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if let Atom(ref target) = list[1] {
+            if let Resolution::Bound(Binding::Macro(transformer)) =
+                self.vm.identifiers.resolve(target, scope)
+            {
+                return match *transformer {
+                    Transformer::Procedure { variable: true, .. } => {
+                        let form = List(list.to_vec());
+                        let expanded =
+                            self.transform(&transformer, &form, scope)?;
+                        self.expand_in(&expanded, scope)
+                    }
+                    _ => Err(RuntimeError::MalformedExpression),
+                };
+            }
+
+            let top = self.vm.identifiers.free_scope(target, scope);
+            if let Resolution::Free(name) =
+                self.vm.identifiers.resolve(target, scope)
+            {
+                let message = if !top.mutable {
+                    Some("assignment of an immutable variable")
+                } else if top.imported.borrow().contains(&name) {
+                    Some("assignment of an imported variable")
+                } else {
+                    None
+                };
+
+                if let Some(message) = message {
+                    return Err(self.syntax_violation(
+                        "set!",
+                        message,
+                        &List(list.to_vec()),
+                        &name,
+                    ));
+                }
+            }
+        }
+
+        self.expand_all(list, scope).map(List)
+    }
+
+    /// `(syntax-case expr (literal ...) (pattern [fender] output) ...)` is
+    /// expanded as a call to `%syntax-case`, with each fender and output made
+    /// a procedure of the pattern variables of its clause.
+    fn expand_syntax_case(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let literals = match list.get(2) {
+            Some(List(ref literals)) => {
+                literals.iter().map(atom).collect::<Result<Vec<_>, _>>()?
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let mut expanded = vec![
+            Atom("%syntax-case".into()),
+            self.expand_in(&list[1], scope)?,
+            quote(List(args_of(&literals))),
+        ];
+
+        for clause in &list[3..] {
+            let clause = match *clause {
+                List(ref clause) if clause.len() == 2 || clause.len() == 3 => {
+                    clause
+                }
+                _ => return Err(RuntimeError::MalformedExpression),
+            };
+
+            let pattern = self.syntax_pattern(&clause[0], &literals);
+            let context = CaseContext {
+                literals: &literals,
+                identifiers: &self.vm.identifiers,
+            };
+            let variables = pattern_variables(&pattern, &context);
+
+            let inner = Scope::new(scope);
+            let mut formals = vec![];
+            for (variable, depth) in variables {
+                let name = self.vm.identifiers.fresh(&variable);
+                formals.push(Atom(name.clone()));
+                inner.bind(
+                    variable,
+                    Binding::PatternVariable(Rc::new(name), depth),
+                );
+            }
+
+            let fender = match clause.len() {
+                3 => List(vec![
+                    Atom("lambda".into()),
+                    List(formals.clone()),
+                    self.expand_in(&clause[1], &inner)?,
+                ]),
+                _ => Bool(false),
+            };
+            let output = List(vec![
+                Atom("lambda".into()),
+                List(formals),
+                self.expand_in(&clause[clause.len() - 1], &inner)?,
+            ]);
+
+            expanded.extend(vec![quote(pattern), fender, output]);
+        }
+
+        Ok(List(expanded))
+    }
+
+    /// A `syntax-case` pattern, with its ellipses and underscores, which may be
+    /// aliases, replaced by plain ones.
+    fn syntax_pattern(&self, pattern: &AST, literals: &[String]) -> AST {
+        map_atoms(pattern, &mut |atom| {
+            let base = self.vm.identifiers.base(atom);
+
+            if literals.iter().all(|literal| literal != atom)
+                && (base == "..." || base == "_")
+            {
+                base
+            } else {
+                atom.to_owned()
+            }
+        })
+    }
+
+    /// `(syntax template)` is expanded as a call to `%syntax` with the
+    /// template, the pattern variables it refers to and their values.
+    fn expand_syntax(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if list.len() != 2 {
+            return Err(RuntimeError::MalformedExpression);
+        }
+
+        let mut variables = vec![];
+        let template = self.syntax_template(&list[1], scope, &mut variables);
+
+        let depths = variables
+            .iter()
+            .map(|(name, depth)| {
+                DottedList(
+                    vec![Atom(name.clone())],
+                    Box::new(Integer(*depth as i64)),
+                )
+            })
+            .collect();
+
+        let mut expanded =
+            vec![Atom("%syntax".into()), quote(template), quote(List(depths))];
+        expanded.extend(variables.into_iter().map(|(name, _)| Atom(name)));
+        Ok(List(expanded))
+    }
+
+    /// Replaces the pattern variables of a template with the variables holding
+    /// what they matched, collecting these along with their depth.
+    fn syntax_template(
+        &mut self,
+        template: &AST,
+        scope: &Rc<Scope>,
+        variables: &mut Vec<(String, usize)>,
+    ) -> AST {
+        match *template {
+            Atom(ref atom) => match self.vm.identifiers.resolve(atom, scope) {
+                Resolution::Bound(Binding::PatternVariable(name, depth)) => {
+                    if !variables.iter().any(|(variable, _)| **variable == *name)
+                    {
+                        variables.push(((*name).clone(), depth));
+                    }
+                    Atom((*name).clone())
+                }
+                _ => {
+                    let base = self.vm.identifiers.base(atom);
+                    if base == "..." {
+                        return Atom(base);
+                    }
+
+                    self.vm.identifiers.introduced.insert(base);
+                    template.clone()
+                }
+            },
+            List(ref items) => {
+                List(self.syntax_templates(items, scope, variables))
+            }
+            DottedList(ref items, ref tail) => {
+                let items = self.syntax_templates(items, scope, variables);
+                let tail = self.syntax_template(tail, scope, variables);
+                DottedList(items, Box::new(tail))
+            }
+            Vector(ref items) => {
+                Vector(self.syntax_templates(items, scope, variables))
+            }
+            _ => template.clone(),
+        }
+    }
+
+    fn syntax_templates(
+        &mut self,
+        templates: &[AST],
+        scope: &Rc<Scope>,
+        variables: &mut Vec<(String, usize)>,
+    ) -> Vec<AST> {
+        templates
+            .iter()
+            .map(|template| self.syntax_template(template, scope, variables))
+            .collect()
+    }
+
+    /// `(with-syntax ((pattern expr) ...) body ...)` is `(syntax-case (list
+    /// expr ...) () ((pattern ...) (let () body ...)))`.
+    fn expand_with_syntax(
+        &mut self,
+        list: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let bindings = match binding_pairs(&list[1]) {
+            Some(bindings) => bindings,
+            None => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let builtins = self.vm.identifiers.builtins.clone();
+        let mut alias =
+            |name: &str| Atom(self.vm.identifiers.alias(name, &builtins));
+        let (syntax_case, list_procedure, let_form) =
+            (alias("syntax-case"), alias("list"), alias("let"));
+
+        let mut patterns = vec![];
+        let mut exprs = vec![list_procedure];
+        for (pattern, expr) in bindings {
+            patterns.push(pattern.clone());
+            exprs.push(expr.clone());
+        }
+
+        let mut body = vec![let_form, List(vec![])];
+        body.extend_from_slice(&list[2..]);
+
+        let form = List(vec![
+            syntax_case,
+            List(exprs),
+            List(vec![]),
+            List(vec![List(patterns), List(body)]),
+        ]);
+        self.expand_in(&form, scope)
+    }
+
+    /// `(define-enumeration type-name (symbol ...) constructor-syntax)` binds
+    /// `type-name` to the enumeration's universe and introduces the two
+    /// keywords.
+    fn define_enumeration(
+        &mut self,
+        args: &[AST],
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        if args.len() != 3 {
+            return Err(RuntimeError::BadArity(Some(
+                "define-enumeration".into(),
+            )));
+        }
+
+        let type_name = self.vm.identifiers.base(&atom(&args[0])?);
+        let constructor = self.vm.identifiers.base(&atom(&args[2])?);
+        let symbols = match self.vm.identifiers.strip(&args[1]) {
+            List(ref symbols) => {
+                symbols.iter().map(atom).collect::<Result<Vec<_>, _>>()?
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        if Rc::ptr_eq(scope, &self.top) {
+            self.check_not_imported(&type_name, scope)?;
+            self.check_not_imported(&constructor, scope)?;
+        }
+
+        let universe = quote(List(args_of(&symbols)));
+
+        scope.bind(
+            type_name.clone(),
+            Binding::Keyword(Rc::new(Keyword::EnumerationType(symbols.clone()))),
+        );
+        scope.bind(
+            constructor,
+            Binding::Keyword(Rc::new(Keyword::EnumerationConstructor(
+                type_name.clone(),
+                symbols,
+            ))),
+        );
+
+        Ok(List(vec![
+            Atom("define".into()),
+            Atom(type_name),
+            List(vec![Atom("%make-enumeration".into()), universe]),
+        ]))
+    }
+}
+
+impl Keyword {
+    fn expand(&self, args: &[AST]) -> Result<AST, RuntimeError> {
+        match *self {
+            Keyword::EnumerationType(ref symbols) => match args {
+                [Atom(ref symbol)] if symbols.contains(symbol) => {
+                    Ok(quote(Atom(symbol.clone())))
+                }
+                _ => Err(RuntimeError::MalformedExpression),
+            },
+            Keyword::EnumerationConstructor(ref type_name, ref symbols) => {
+                let members = args
+                    .iter()
+                    .map(|arg| match *arg {
+                        Atom(ref symbol) if symbols.contains(symbol) => {
+                            Ok(arg.clone())
+                        }
+                        _ => Err(RuntimeError::MalformedExpression),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let constructor = List(vec![
+                    Atom("%enum-set-constructor".into()),
+                    Atom(type_name.clone()),
+                ]);
+
+                Ok(List(vec![constructor, quote(List(members))]))
+            }
+        }
+    }
+}
+
+/// Whether `list` is an `(import spec ...)` form.
+fn is_import(list: &[AST]) -> bool {
+    list.first() == Some(&Atom("import".into()))
+}
+
+/// `error`, raised by the form at `location`. Escapes to a `guard` are left
+/// alone, for the `guard` to catch them.
+fn located(location: &Location, error: RuntimeError) -> RuntimeError {
+    error.located(location.to_string())
+}
+
+/// The expanded form `form`, read at `location`, made to locate the errors it
+/// raises when run: expressions and the values of variable definitions are
+/// run through `%located`. Other definitions are left as they are.
+fn locate_form(form: AST, location: &Location) -> AST {
+    let located = |expression: AST| {
+        let thunk = List(vec![Atom("lambda".into()), List(vec![]), expression]);
+        List(vec![
+            Atom("%located".into()),
+            Str(location.to_string()),
+            thunk,
+        ])
+    };
+
+    match form {
+        List(mut list) => match list.first() {
+            Some(Atom(ref head)) if head == "define" && list.len() == 3 => {
+                match list[2] {
+                    List(ref value)
+                        if value.first() == Some(&Atom("lambda".into())) => {}
+                    _ => {
+                        let value = list.pop().unwrap();
+                        list.push(located(value));
+                    }
+                }
+                List(list)
+            }
+            Some(Atom(ref head))
+                if head == "begin" || DEFINITIONS.contains(&head.as_str()) =>
+            {
+                List(list)
+            }
+            _ => located(List(list)),
+        },
+        form => located(form),
+    }
+}
+
+fn atom(ast: &AST) -> Result<String, RuntimeError> {
+    match *ast {
+        Atom(ref atom) => Ok(atom.clone()),
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+fn args_of(symbols: &[String]) -> Vec<AST> {
+    symbols.iter().map(|symbol| Atom(symbol.clone())).collect()
+}
+
+/// The `(name value)` pairs of a binding list.
+fn binding_pairs(bindings: &AST) -> Option<Vec<(&AST, &AST)>> {
+    match *bindings {
+        List(ref bindings) => bindings
+            .iter()
+            .map(|binding| match *binding {
+                List(ref binding) if binding.len() == 2 => {
+                    Some((&binding[0], &binding[1]))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// The identifiers of the formals `formals`.
+fn formals_names(formals: &AST) -> Vec<String> {
+    match *formals {
+        Atom(ref identifier) => vec![identifier.clone()],
+        List(ref items) => items.iter().flat_map(formals_names).collect(),
+        DottedList(ref items, ref rest) => items
+            .iter()
+            .chain(Some(&**rest))
+            .flat_map(formals_names)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// `list` with the item at `index` replaced by `item`.
+fn replaced(list: &[AST], index: usize, item: AST) -> AST {
+    let mut list = list.to_vec();
+    list[index] = item;
+    List(list)
+}
+
+fn quote(ast: AST) -> AST {
+    List(vec![Atom("quote".into()), ast])
+}
+
+/// Rebuilds `ast` with each of its atoms replaced by `rename` of it.
+fn map_atoms(ast: &AST, rename: &mut dyn FnMut(&str) -> String) -> AST {
+    fn map_all(
+        items: &[AST],
+        rename: &mut dyn FnMut(&str) -> String,
+    ) -> Vec<AST> {
+        items.iter().map(|item| map_atoms(item, rename)).collect()
+    }
+
+    match *ast {
+        Atom(ref atom) => Atom(rename(atom)),
+        List(ref items) => List(map_all(items, rename)),
+        DottedList(ref items, ref tail) => {
+            let items = map_all(items, rename);
+            DottedList(items, Box::new(map_atoms(tail, rename)))
+        }
+        Vector(ref items) => Vector(map_all(items, rename)),
+        _ => ast.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Expander;
+    use crate::{ast::AST, errors::RuntimeError, parser::parse, vm::VM};
+
+    fn expand(vm: &mut VM, input: &str) -> Result<AST, RuntimeError> {
+        Expander::new(vm).expand(&parse(input).unwrap()[0])
+    }
+
+    fn expand_ok(vm: &mut VM, input: &str) -> AST {
+        expand(vm, input).unwrap()
+    }
+
+    #[test]
+    fn define_enumeration() {
+        let mut vm = VM::new();
+
+        assert_eq!(
+            parse("(define color (%make-enumeration '(red blue)))").unwrap()[0],
+            expand_ok(&mut vm, "(define-enumeration color (red blue) color-set)")
+        );
+        assert_eq!(
+            parse("(list 'red)").unwrap()[0],
+            expand_ok(&mut vm, "(list (color red))")
+        );
+        assert_eq!(
+            parse("((%enum-set-constructor color) '(blue red))").unwrap()[0],
+            expand_ok(&mut vm, "(color-set blue red)")
+        );
+        assert_eq!(
+            Err(RuntimeError::MalformedExpression),
+            expand(&mut vm, "(color green)")
+        );
+    }
+
+    #[test]
+    fn binding_positions_are_left_alone() {
+        let mut vm = VM::new();
+        expand_ok(&mut vm, "(define-enumeration color (red) color-set)");
+
+        assert_eq!(
+            parse("(lambda (color x) x)").unwrap()[0],
+            expand_ok(&mut vm, "(lambda (color x) x)")
+        );
+        assert_eq!(
+            parse("(let ((color 1)) (color red))").unwrap()[0],
+            expand_ok(&mut vm, "(let ((color 1)) (color red))")
+        );
+        assert_eq!(
+            parse("'(color blue)").unwrap()[0],
+            expand_ok(&mut vm, "'(color blue)")
+        );
+    }
+
+    #[test]
+    fn introduced_bindings_are_renamed() {
+        let mut vm = VM::new();
+        expand_ok(
+            &mut vm,
+            "(define-syntax my-or
+               (syntax-rules () ((_ a b) (let ((t a)) (if t t b)))))",
+        );
+
+        assert_eq!(
+            parse("(let ((t%4 #f)) (if t%4 t%4 t))").unwrap()[0],
+            expand_ok(&mut vm, "(my-or #f t)")
+        );
+        assert_eq!(
+            parse("(let ((t%5 1)) (let ((t%9 #f)) (if t%9 t%9 t%5)))").unwrap()
+                [0],
+            expand_ok(&mut vm, "(let ((t 1)) (my-or #f t))")
+        );
+    }
+}
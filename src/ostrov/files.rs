@@ -0,0 +1,73 @@
+use crate::{
+    ast::AST,
+    errors::RuntimeError,
+    parser::{parse_located, Position},
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Where a form read from a file starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.path.display(),
+            self.position.line,
+            self.position.column
+        )
+    }
+}
+
+/// Forms read from a file, each with its location.
+pub type Forms = Vec<(AST, Location)>;
+
+/// The forms of the file at `path`.
+pub fn read(path: &Path) -> Result<Forms, RuntimeError> {
+    let content = fs::read_to_string(path).map_err(|error| {
+        RuntimeError::LoadFailed(path.display().to_string(), error.to_string())
+    })?;
+    let forms = parse_located(&content).map_err(|error| {
+        RuntimeError::LexicalViolation(
+            path.display().to_string(),
+            match error.position() {
+                Some(position) => format!(
+                    "invalid syntax at line {}, column {}",
+                    position.line, position.column
+                ),
+                None => "invalid syntax".into(),
+            },
+        )
+    })?;
+
+    Ok(forms
+        .into_iter()
+        .map(|(form, position)| {
+            let location = Location {
+                path: path.to_owned(),
+                position,
+            };
+            (form, location)
+        })
+        .collect())
+}
+
+/// `path`, resolved against the directory of the innermost of the files
+/// being `loading` if it is relative.
+pub fn resolve(path: &str, loading: &[PathBuf]) -> PathBuf {
+    let path = Path::new(path);
+
+    match loading.last().and_then(|file| file.parent()) {
+        Some(directory) if path.is_relative() => directory.join(path),
+        _ => path.to_owned(),
+    }
+}
@@ -0,0 +1,152 @@
+use crate::values::RcValue;
+use std::collections::HashMap;
+
+/// How a hashtable hashes and compares its keys.
+#[derive(Clone)]
+pub enum Equivalence {
+    Eq,
+    Eqv,
+    Custom { hash: RcValue, equiv: RcValue },
+}
+
+/// Storage of a Scheme hashtable. Hashes are computed, and keys compared, by
+/// the caller, since both may involve calling Scheme procedures; the table
+/// only keeps entries in insertion order and indexes them by hash.
+#[derive(Clone)]
+pub struct Hashtable {
+    pub equivalence: Equivalence,
+    pub mutable: bool,
+    entries: Vec<(u64, RcValue, RcValue)>,
+    buckets: HashMap<u64, Vec<usize>>,
+    /// Bumped whenever entries are added or removed, which invalidates the
+    /// indexes handed out before.
+    generation: usize,
+}
+
+impl Hashtable {
+    pub fn new(equivalence: Equivalence) -> Hashtable {
+        Hashtable {
+            equivalence,
+            mutable: true,
+            entries: Vec::new(),
+            buckets: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Indexes and keys of the entries whose key hashes to `hash`.
+    pub fn candidates(&self, hash: u64) -> Vec<(usize, RcValue)> {
+        self.buckets
+            .get(&hash)
+            .map(|indexes| {
+                indexes
+                    .iter()
+                    .map(|&i| (i, self.entries[i].1.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn value(&self, index: usize) -> RcValue {
+        self.entries[index].2.clone()
+    }
+
+    pub fn set_value(&mut self, index: usize, value: RcValue) {
+        self.entries[index].2 = value;
+    }
+
+    pub fn insert(&mut self, hash: u64, key: RcValue, value: RcValue) {
+        self.buckets
+            .entry(hash)
+            .or_default()
+            .push(self.entries.len());
+        self.entries.push((hash, key, value));
+        self.generation += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        let (hash, _, _) = self.entries.swap_remove(index);
+        self.unindex(hash, index);
+        self.generation += 1;
+
+        if index < self.entries.len() {
+            let moved_hash = self.entries[index].0;
+            let moved_from = self.entries.len();
+
+            for i in self.buckets.get_mut(&moved_hash).unwrap().iter_mut() {
+                if *i == moved_from {
+                    *i = index;
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.buckets.clear();
+        self.generation += 1;
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&RcValue, &RcValue)> {
+        self.entries.iter().map(|(_, key, value)| (key, value))
+    }
+
+    fn unindex(&mut self, hash: u64, index: usize) {
+        let bucket = self.buckets.get_mut(&hash).unwrap();
+        bucket.retain(|&i| i != index);
+
+        if bucket.is_empty() {
+            self.buckets.remove(&hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Equivalence, Hashtable};
+    use crate::memory::Memory;
+
+    #[test]
+    fn insert_and_lookup() {
+        let mut mem = Memory::new();
+        let mut table = Hashtable::new(Equivalence::Eqv);
+
+        table.insert(1, mem.integer(1), mem.integer(10));
+        table.insert(1, mem.integer(2), mem.integer(20));
+        table.insert(2, mem.integer(3), mem.integer(30));
+
+        assert_eq!(3, table.len());
+        assert_eq!(2, table.candidates(1).len());
+        assert_eq!(mem.integer(30), table.value(table.candidates(2)[0].0));
+        assert!(table.candidates(3).is_empty());
+    }
+
+    #[test]
+    fn remove_keeps_indexes_consistent() {
+        let mut mem = Memory::new();
+        let mut table = Hashtable::new(Equivalence::Eqv);
+
+        table.insert(1, mem.integer(1), mem.integer(10));
+        table.insert(2, mem.integer(2), mem.integer(20));
+        table.insert(3, mem.integer(3), mem.integer(30));
+
+        table.remove(0);
+
+        assert_eq!(2, table.len());
+        assert!(table.candidates(1).is_empty());
+        assert_eq!(mem.integer(30), table.value(table.candidates(3)[0].0));
+        assert_eq!(mem.integer(20), table.value(table.candidates(2)[0].0));
+    }
+}
@@ -6,6 +6,9 @@ pub type Bytecode = Vec<Instruction>;
 #[derive(Debug, PartialEq, Clone)]
 pub enum Instruction {
     Apply,
+    /// Applies the consumer of a `call-with-values` to the values of the
+    /// producer, once it returns. Only the VM emits it.
+    ApplyValues,
     Argument,
     Assignment(String),
     Close {
@@ -13,6 +16,9 @@ pub enum Instruction {
         args_type: ArgumentsType,
         body: Bytecode,
     },
+    Declare(String),
+    Delay,
+    DelayForce,
     Frame,
     LoadConstant(AST),
     LoadReference(String),
@@ -21,4 +27,14 @@ pub enum Instruction {
     JumpOnTrue(usize),
     Jump(usize),
     Replace(String),
+    /// Settles the promise in the rib with the value of its thunk, once the
+    /// thunk returns, then forces it again. Only the VM emits it.
+    Resolve,
+    TailApply,
+    /// Unbinds the parameters bound above the first ones of a depth, once
+    /// the body of a `parameterize` returns. Only the VM emits it.
+    Unbind(usize),
+    /// Uninstalls the exception handlers above the first ones of a depth,
+    /// once the thunk they were installed for returns. Only the VM emits it.
+    Uninstall(usize),
 }
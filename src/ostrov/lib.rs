@@ -1,12 +1,24 @@
-mod ast;
+pub mod ast;
 mod compiler;
+mod conditions;
+mod enumerations;
 mod env;
 pub mod errors;
+mod expander;
+mod files;
+mod hashtable;
 mod instructions;
+mod libraries;
 mod memory;
+mod parameters;
 mod parser;
+mod ports;
 mod primitives;
+mod promises;
+mod records;
 pub mod repl;
+mod rnrs;
 pub mod runtime;
+mod syntax_rules;
 pub mod values;
 mod vm;
@@ -0,0 +1,584 @@
+use crate::{
+    ast::AST::{self, *},
+    errors::RuntimeError,
+    expander::Scope,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Extensions of the files libraries are looked up in, by preference.
+static EXTENSIONS: [&str; 3] = ["sls", "ss", "scm"];
+
+/// A library defined by a `library` form.
+pub struct Library {
+    pub name: Vec<String>,
+    pub version: Vec<u64>,
+    /// The top-level scope of the library body, which binds its environment.
+    pub scope: Rc<Scope>,
+    /// The identifiers the library exports: the name importers know each by,
+    /// and the one it has in the library.
+    pub exports: Vec<(String, String)>,
+}
+
+impl Library {
+    /// The identifier of the library exported as `external`.
+    pub fn internal(&self, external: &str) -> Option<&str> {
+        self.exports
+            .iter()
+            .find(|(name, _)| name == external)
+            .map(|(_, internal)| internal.as_ref())
+    }
+}
+
+/// The libraries defined so far, and where to look for the others.
+pub struct Libraries {
+    defined: Vec<Rc<Library>>,
+    /// Directories searched in order for the file of a library imported before
+    /// being defined: `(foo bar)` is `foo/bar.sls`, `foo/bar.ss` or
+    /// `foo/bar.scm` in one of them.
+    pub path: Vec<PathBuf>,
+    /// Files being loaded, so that libraries importing each other don't load
+    /// them forever.
+    loading: HashSet<PathBuf>,
+}
+
+impl Libraries {
+    pub fn new() -> Libraries {
+        Libraries {
+            defined: vec![],
+            path: vec![PathBuf::from(".")],
+            loading: HashSet::new(),
+        }
+    }
+
+    pub fn find(&self, name: &[String]) -> Option<Rc<Library>> {
+        self.defined
+            .iter()
+            .find(|library| library.name == name)
+            .cloned()
+    }
+
+    /// Defines `library`, replacing any library of the same name.
+    pub fn define(&mut self, library: Library) {
+        self.defined.retain(|defined| defined.name != library.name);
+        self.defined.push(Rc::new(library));
+    }
+
+    /// The file the library `name` would be defined in, if any.
+    pub fn file(&self, name: &[String]) -> Option<PathBuf> {
+        let (last, init) = name.split_last()?;
+
+        self.path.iter().find_map(|directory| {
+            let directory = init
+                .iter()
+                .fold(directory.clone(), |path, part| path.join(part));
+
+            EXTENSIONS
+                .iter()
+                .map(|extension| {
+                    directory.join(format!("{}.{}", last, extension))
+                })
+                .find(|path| path.is_file())
+        })
+    }
+
+    /// Marks `path` as being loaded, telling whether it was not already.
+    pub fn start_loading(&mut self, path: &Path) -> bool {
+        self.loading.insert(path.to_owned())
+    }
+
+    pub fn finish_loading(&mut self, path: &Path) {
+        self.loading.remove(path);
+    }
+}
+
+impl Default for Libraries {
+    fn default() -> Libraries {
+        Libraries::new()
+    }
+}
+
+/// `(identifier ... [(version ...)])`, the name of a `library` form.
+pub fn library_name(form: &AST) -> Result<(Vec<String>, Vec<u64>), RuntimeError> {
+    let (parts, version) = name_and_version(form)?;
+
+    let version = match version {
+        Some(List(ref version)) => {
+            version.iter().map(sub_version).collect::<Result<_, _>>()?
+        }
+        Some(_) => return Err(RuntimeError::MalformedExpression),
+        None => vec![],
+    };
+
+    Ok((parts, version))
+}
+
+/// Splits `(identifier ... [version])` into the identifiers and the version.
+fn name_and_version(
+    form: &AST,
+) -> Result<(Vec<String>, Option<&AST>), RuntimeError> {
+    let items = match *form {
+        List(ref items) if !items.is_empty() => items,
+        _ => return Err(RuntimeError::MalformedExpression),
+    };
+
+    let (parts, version) = match items.split_last() {
+        Some((version @ List(..), parts)) => (parts, Some(version)),
+        _ => (&items[..], None),
+    };
+
+    if parts.is_empty() {
+        return Err(RuntimeError::MalformedExpression);
+    }
+
+    let parts = parts
+        .iter()
+        .map(|part| match *part {
+            Atom(ref part) => Ok(part.clone()),
+            _ => Err(RuntimeError::MalformedExpression),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((parts, version))
+}
+
+fn sub_version(form: &AST) -> Result<u64, RuntimeError> {
+    match *form {
+        Integer(n) if n >= 0 => Ok(n as u64),
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+/// Which versions of a library an import accepts.
+pub enum VersionReference {
+    /// `(sub-version-reference ...)`: versions with at least as many parts,
+    /// each of the first ones accepted by its reference.
+    Parts(Vec<SubVersionReference>),
+    And(Vec<VersionReference>),
+    Or(Vec<VersionReference>),
+    Not(Box<VersionReference>),
+}
+
+impl VersionReference {
+    fn parse(form: &AST) -> Result<VersionReference, RuntimeError> {
+        let items = match *form {
+            List(ref items) => items,
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let operands = || {
+            items[1..]
+                .iter()
+                .map(VersionReference::parse)
+                .collect::<Result<_, _>>()
+        };
+
+        match items.first() {
+            Some(Atom(ref operator)) if operator == "and" => {
+                Ok(VersionReference::And(operands()?))
+            }
+            Some(Atom(ref operator)) if operator == "or" => {
+                Ok(VersionReference::Or(operands()?))
+            }
+            Some(Atom(ref operator)) if operator == "not" && items.len() == 2 => {
+                let operand = VersionReference::parse(&items[1])?;
+                Ok(VersionReference::Not(Box::new(operand)))
+            }
+            _ => items
+                .iter()
+                .map(SubVersionReference::parse)
+                .collect::<Result<_, _>>()
+                .map(VersionReference::Parts),
+        }
+    }
+
+    pub fn matches(&self, version: &[u64]) -> bool {
+        match *self {
+            VersionReference::Parts(ref parts) => {
+                parts.len() <= version.len()
+                    && parts
+                        .iter()
+                        .zip(version)
+                        .all(|(part, &sub_version)| part.matches(sub_version))
+            }
+            VersionReference::And(ref references) => references
+                .iter()
+                .all(|reference| reference.matches(version)),
+            VersionReference::Or(ref references) => references
+                .iter()
+                .any(|reference| reference.matches(version)),
+            VersionReference::Not(ref reference) => !reference.matches(version),
+        }
+    }
+}
+
+/// Which values of a part of a version an import accepts.
+pub enum SubVersionReference {
+    Exactly(u64),
+    AtLeast(u64),
+    AtMost(u64),
+    And(Vec<SubVersionReference>),
+    Or(Vec<SubVersionReference>),
+    Not(Box<SubVersionReference>),
+}
+
+impl SubVersionReference {
+    fn parse(form: &AST) -> Result<SubVersionReference, RuntimeError> {
+        let items = match *form {
+            List(ref items) if items.len() > 1 => items,
+            _ => return sub_version(form).map(SubVersionReference::Exactly),
+        };
+
+        let operands = || {
+            items[1..]
+                .iter()
+                .map(SubVersionReference::parse)
+                .collect::<Result<_, _>>()
+        };
+
+        match (&items[0], items.len()) {
+            (Atom(ref operator), 2) if operator == ">=" => {
+                sub_version(&items[1]).map(SubVersionReference::AtLeast)
+            }
+            (Atom(ref operator), 2) if operator == "<=" => {
+                sub_version(&items[1]).map(SubVersionReference::AtMost)
+            }
+            (Atom(ref operator), 2) if operator == "not" => {
+                let operand = SubVersionReference::parse(&items[1])?;
+                Ok(SubVersionReference::Not(Box::new(operand)))
+            }
+            (Atom(ref operator), _) if operator == "and" => {
+                Ok(SubVersionReference::And(operands()?))
+            }
+            (Atom(ref operator), _) if operator == "or" => {
+                Ok(SubVersionReference::Or(operands()?))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    fn matches(&self, sub_version: u64) -> bool {
+        match *self {
+            SubVersionReference::Exactly(n) => sub_version == n,
+            SubVersionReference::AtLeast(n) => sub_version >= n,
+            SubVersionReference::AtMost(n) => sub_version <= n,
+            SubVersionReference::And(ref references) => references
+                .iter()
+                .all(|reference| reference.matches(sub_version)),
+            SubVersionReference::Or(ref references) => references
+                .iter()
+                .any(|reference| reference.matches(sub_version)),
+            SubVersionReference::Not(ref reference) => {
+                !reference.matches(sub_version)
+            }
+        }
+    }
+}
+
+/// `(identifier ... [version-reference])`, naming the library an import set
+/// is taken from.
+pub struct LibraryReference {
+    pub name: Vec<String>,
+    pub version: VersionReference,
+    /// The reference as written, for error messages.
+    pub written: String,
+}
+
+impl LibraryReference {
+    fn parse(form: &AST) -> Result<LibraryReference, RuntimeError> {
+        let (name, version) = name_and_version(form)?;
+
+        let version = match version {
+            Some(version) => VersionReference::parse(version)?,
+            None => VersionReference::Parts(vec![]),
+        };
+
+        Ok(LibraryReference {
+            name,
+            version,
+            written: form.to_string(),
+        })
+    }
+}
+
+/// The identifiers an `import` form imports from a library, and the names it
+/// imports them as.
+pub enum ImportSet {
+    Library(LibraryReference),
+    Only(Box<ImportSet>, Vec<String>),
+    Except(Box<ImportSet>, Vec<String>),
+    Prefix(Box<ImportSet>, String),
+    Rename(Box<ImportSet>, Vec<(String, String)>),
+}
+
+impl ImportSet {
+    /// Parses an import spec, whose import levels are ignored: there is a
+    /// single phase, where everything is available.
+    pub fn parse(spec: &AST) -> Result<ImportSet, RuntimeError> {
+        match *spec {
+            List(ref items)
+                if items.len() > 1 && items[0] == Atom("for".into()) =>
+            {
+                ImportSet::parse_set(&items[1])
+            }
+            _ => ImportSet::parse_set(spec),
+        }
+    }
+
+    fn parse_set(form: &AST) -> Result<ImportSet, RuntimeError> {
+        let items = match *form {
+            List(ref items) if !items.is_empty() => items,
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let operator = match items[0] {
+            Atom(ref operator) if items.len() > 1 => operator.as_ref(),
+            _ => "",
+        };
+        let set = || ImportSet::parse_set(&items[1]).map(Box::new);
+
+        match operator {
+            "library" if items.len() == 2 => {
+                LibraryReference::parse(&items[1]).map(ImportSet::Library)
+            }
+            "only" => Ok(ImportSet::Only(set()?, identifiers(&items[2..])?)),
+            "except" => Ok(ImportSet::Except(set()?, identifiers(&items[2..])?)),
+            "prefix" if items.len() == 3 => match items[2] {
+                Atom(ref prefix) => Ok(ImportSet::Prefix(set()?, prefix.clone())),
+                _ => Err(RuntimeError::MalformedExpression),
+            },
+            "rename" => {
+                let renames = items[2..]
+                    .iter()
+                    .map(identifier_pair)
+                    .collect::<Result<_, _>>()?;
+                Ok(ImportSet::Rename(set()?, renames))
+            }
+            _ => LibraryReference::parse(form).map(ImportSet::Library),
+        }
+    }
+
+    /// The library the identifiers are imported from.
+    pub fn library(&self) -> &LibraryReference {
+        match *self {
+            ImportSet::Library(ref reference) => reference,
+            ImportSet::Only(ref set, _)
+            | ImportSet::Except(ref set, _)
+            | ImportSet::Prefix(ref set, _)
+            | ImportSet::Rename(ref set, _) => set.library(),
+        }
+    }
+
+    /// The identifiers imported, out of the ones the library exports: the name
+    /// each is imported as, and the one it is exported as. Fails with the
+    /// identifier an `only`, `except` or `rename` names but doesn't import.
+    pub fn names(
+        &self,
+        exports: &[String],
+    ) -> Result<Vec<(String, String)>, String> {
+        match *self {
+            ImportSet::Library(..) => Ok(exports
+                .iter()
+                .map(|name| (name.clone(), name.clone()))
+                .collect()),
+            ImportSet::Only(ref set, ref identifiers) => {
+                let names = set.names(exports)?;
+                all_imported(&names, identifiers.iter())?;
+
+                Ok(names
+                    .into_iter()
+                    .filter(|(name, _)| identifiers.contains(name))
+                    .collect())
+            }
+            ImportSet::Except(ref set, ref identifiers) => {
+                let names = set.names(exports)?;
+                all_imported(&names, identifiers.iter())?;
+
+                Ok(names
+                    .into_iter()
+                    .filter(|(name, _)| !identifiers.contains(name))
+                    .collect())
+            }
+            ImportSet::Prefix(ref set, ref prefix) => Ok(set
+                .names(exports)?
+                .into_iter()
+                .map(|(name, external)| (format!("{}{}", prefix, name), external))
+                .collect()),
+            ImportSet::Rename(ref set, ref renames) => {
+                let names = set.names(exports)?;
+                all_imported(&names, renames.iter().map(|(from, _)| from))?;
+
+                Ok(names
+                    .into_iter()
+                    .map(|(name, external)| {
+                        match renames.iter().find(|(from, _)| *from == name) {
+                            Some((_, to)) => (to.clone(), external),
+                            None => (name, external),
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Fails with the first of `identifiers` not among the imported `names`.
+fn all_imported<'a>(
+    names: &[(String, String)],
+    mut identifiers: impl Iterator<Item = &'a String>,
+) -> Result<(), String> {
+    match identifiers
+        .find(|identifier| names.iter().all(|(name, _)| name != *identifier))
+    {
+        Some(identifier) => Err(identifier.clone()),
+        None => Ok(()),
+    }
+}
+
+/// The export specs of a `library` form, `identifier` or `(rename (internal
+/// external) ...)`, as pairs of the external and internal names.
+pub fn exports(specs: &[AST]) -> Result<Vec<(String, String)>, RuntimeError> {
+    let mut exports = vec![];
+
+    for spec in specs {
+        match *spec {
+            Atom(ref identifier) => {
+                exports.push((identifier.clone(), identifier.clone()))
+            }
+            List(ref items)
+                if !items.is_empty() && items[0] == Atom("rename".into()) =>
+            {
+                for rename in &items[1..] {
+                    let (internal, external) = identifier_pair(rename)?;
+                    exports.push((external, internal));
+                }
+            }
+            _ => return Err(RuntimeError::MalformedExpression),
+        }
+    }
+
+    Ok(exports)
+}
+
+fn identifiers(forms: &[AST]) -> Result<Vec<String>, RuntimeError> {
+    forms
+        .iter()
+        .map(|form| match *form {
+            Atom(ref identifier) => Ok(identifier.clone()),
+            _ => Err(RuntimeError::MalformedExpression),
+        })
+        .collect()
+}
+
+fn identifier_pair(form: &AST) -> Result<(String, String), RuntimeError> {
+    match *form {
+        List(ref pair) if pair.len() == 2 => match (&pair[0], &pair[1]) {
+            (Atom(ref first), Atom(ref second)) => {
+                Ok((first.clone(), second.clone()))
+            }
+            _ => Err(RuntimeError::MalformedExpression),
+        },
+        _ => Err(RuntimeError::MalformedExpression),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{exports, library_name, ImportSet, VersionReference};
+    use crate::parser::parse;
+
+    fn import_set(input: &str) -> ImportSet {
+        ImportSet::parse(&parse(input).unwrap()[0]).unwrap()
+    }
+
+    fn names(input: &str, exports: &[&str]) -> Result<Vec<String>, String> {
+        let exports: Vec<String> = exports.iter().map(|&e| e.into()).collect();
+
+        import_set(input).names(&exports).map(|names| {
+            names
+                .into_iter()
+                .map(|(name, external)| format!("{}={}", name, external))
+                .collect()
+        })
+    }
+
+    fn version_matches(reference: &str, version: &[u64]) -> bool {
+        VersionReference::parse(&parse(reference).unwrap()[0])
+            .unwrap()
+            .matches(version)
+    }
+
+    #[test]
+    fn library_names() {
+        assert_eq!(
+            (vec!["a".into(), "b".into()], vec![1, 2]),
+            library_name(&parse("(a b (1 2))").unwrap()[0]).unwrap()
+        );
+        assert_eq!(
+            (vec!["a".into()], vec![]),
+            library_name(&parse("(a)").unwrap()[0]).unwrap()
+        );
+        assert!(library_name(&parse("((1))").unwrap()[0]).is_err());
+    }
+
+    #[test]
+    fn import_sets() {
+        assert_eq!(
+            Ok(vec!["a=a".into(), "b=b".into()]),
+            names("(l)", &["a", "b"])
+        );
+        assert_eq!(Ok(vec!["b=b".into()]), names("(only (l) b)", &["a", "b"]));
+        assert_eq!(Ok(vec!["a=a".into()]), names("(except (l) b)", &["a", "b"]));
+        assert_eq!(
+            Ok(vec!["p:a=a".into(), "x=b".into()]),
+            names("(rename (prefix (l) p:) (p:b x))", &["a", "b"])
+        );
+        assert_eq!(
+            Ok(vec!["a=a".into()]),
+            names("(for (library (only)) run)", &["a"])
+        );
+        assert_eq!(Err("c".into()), names("(only (l) c)", &["a", "b"]));
+        assert_eq!(
+            Err("b".into()),
+            names("(rename (only (l) a) (b c))", &["a", "b"])
+        );
+    }
+
+    #[test]
+    fn import_set_libraries() {
+        assert_eq!(
+            vec!["only".to_string()],
+            import_set("(prefix (library (only)) o:)").library().name
+        );
+        assert_eq!("(a (1))", import_set("(only (a (1)) x)").library().written);
+    }
+
+    #[test]
+    fn version_references() {
+        assert!(version_matches("()", &[]));
+        assert!(version_matches("(1)", &[1, 2]));
+        assert!(!version_matches("(1 2)", &[1]));
+        assert!(version_matches("(1 (>= 2))", &[1, 3]));
+        assert!(!version_matches("(1 (<= 2))", &[1, 3]));
+        assert!(version_matches("((or 1 (and (>= 3) (not 4))))", &[5]));
+        assert!(!version_matches("((or 1 (and (>= 3) (not 4))))", &[4]));
+        assert!(version_matches("(or (2) (not (1)))", &[3]));
+        assert!(!version_matches("(and (1) (1 1))", &[1, 2]));
+    }
+
+    #[test]
+    fn export_specs() {
+        assert_eq!(
+            vec![
+                ("a".to_string(), "a".to_string()),
+                ("c".into(), "b".into()),
+                ("e".into(), "d".into())
+            ],
+            exports(&parse("a (rename (b c) (d e))").unwrap()).unwrap()
+        );
+        assert!(exports(&parse("(a b)").unwrap()).is_err());
+    }
+}
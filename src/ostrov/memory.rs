@@ -1,14 +1,32 @@
 use crate::{
+    ast::AST,
+    enumerations::{EnumSet, EnumSetProcedure},
     env::CellEnv,
+    expander::Scope,
+    hashtable::Hashtable,
     instructions::Bytecode,
+    parameters::Parameter,
+    ports::Port,
+    promises::{self, PromiseState},
+    records::{ConstructorDescriptor, RecordProcedure, RecordType},
     values::{ArgumentsType, RcValue, Value},
 };
-use std::rc::Rc;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+/// Number of stored values after which dead ones are dropped from the heap.
+const MIN_HEAP_LIMIT: usize = 1024;
 
 #[derive(Default)]
 pub struct Memory {
-    heap: Vec<RcValue>,
+    heap: Vec<Weak<Value>>,
+    heap_limit: usize,
     reserved: Vec<RcValue>,
+    symbols: HashMap<String, RcValue>,
+    record_types: HashMap<String, Rc<RecordType>>,
 }
 
 impl Memory {
@@ -24,6 +42,110 @@ impl Memory {
         self.store(value)
     }
 
+    pub fn character(&mut self, c: char) -> RcValue {
+        self.store(Value::Char(c))
+    }
+
+    pub fn string(&mut self, chars: Vec<char>) -> RcValue {
+        self.store(Value::string(chars, true))
+    }
+
+    pub fn literal_string(&mut self, chars: Vec<char>) -> RcValue {
+        self.store(Value::string(chars, false))
+    }
+
+    pub fn vector(&mut self, items: Vec<RcValue>) -> RcValue {
+        self.store(Value::vector(items, true))
+    }
+
+    pub fn literal_vector(&mut self, items: Vec<RcValue>) -> RcValue {
+        self.store(Value::vector(items, false))
+    }
+
+    pub fn hashtable(&mut self, table: Hashtable) -> RcValue {
+        self.store(Value::Hashtable(RefCell::new(table)))
+    }
+
+    pub fn record_type_descriptor(&mut self, rtd: Rc<RecordType>) -> RcValue {
+        self.store(Value::RecordTypeDescriptor(rtd))
+    }
+
+    pub fn record_constructor_descriptor(
+        &mut self,
+        descriptor: Rc<ConstructorDescriptor>,
+    ) -> RcValue {
+        self.store(Value::RecordConstructorDescriptor(descriptor))
+    }
+
+    pub fn record(
+        &mut self,
+        rtd: Rc<RecordType>,
+        fields: Vec<RcValue>,
+    ) -> RcValue {
+        self.store(Value::Record {
+            rtd,
+            fields: RefCell::new(fields),
+        })
+    }
+
+    pub fn condition(&mut self, components: Vec<RcValue>) -> RcValue {
+        self.store(Value::Condition(components))
+    }
+
+    pub fn enum_set(&mut self, set: EnumSet) -> RcValue {
+        self.store(Value::EnumSet(set))
+    }
+
+    pub fn enum_set_procedure(&mut self, procedure: EnumSetProcedure) -> RcValue {
+        self.store(Value::EnumSetProcedure(procedure))
+    }
+
+    /// Several values for a continuation. A single value stands for itself.
+    pub fn values(&mut self, mut values: Vec<RcValue>) -> RcValue {
+        if values.len() == 1 {
+            values.pop().unwrap()
+        } else {
+            self.store(Value::Values(values))
+        }
+    }
+
+    pub fn parameter(
+        &mut self,
+        value: RcValue,
+        converter: Option<RcValue>,
+    ) -> RcValue {
+        self.store(Value::Parameter(Parameter::new(value, converter)))
+    }
+
+    pub fn port(&mut self, port: Port) -> RcValue {
+        self.store(Value::Port(port))
+    }
+
+    pub fn promise(&mut self, state: PromiseState) -> RcValue {
+        self.store(Value::Promise(promises::new_box(state)))
+    }
+
+    pub fn record_procedure(&mut self, procedure: RecordProcedure) -> RcValue {
+        self.store(Value::RecordProcedure(procedure))
+    }
+
+    pub fn syntax(&mut self, form: AST) -> RcValue {
+        self.store(Value::Syntax(form))
+    }
+
+    pub fn environment(&mut self, scope: Rc<Scope>) -> RcValue {
+        self.store(Value::Environment(scope))
+    }
+
+    /// Record type previously created with `uid`, if any.
+    pub fn nongenerative_record_type(&self, uid: &str) -> Option<Rc<RecordType>> {
+        self.record_types.get(uid).cloned()
+    }
+
+    pub fn register_record_type(&mut self, uid: String, rtd: Rc<RecordType>) {
+        self.record_types.insert(uid, rtd);
+    }
+
     pub fn b_true(&self) -> RcValue {
         self.reserved[0].clone()
     }
@@ -44,12 +166,21 @@ impl Memory {
         }
     }
 
+    pub fn uninitialized(&self) -> RcValue {
+        self.reserved[4].clone()
+    }
+
     pub fn nil(&self) -> RcValue {
         self.reserved[2].clone()
     }
 
     pub fn pair(&mut self, left: RcValue, right: RcValue) -> RcValue {
-        let value = Value::Pair(left, right);
+        let value = Value::pair(left, right, true);
+        self.store(value)
+    }
+
+    pub fn literal_pair(&mut self, left: RcValue, right: RcValue) -> RcValue {
+        let value = Value::pair(left, right, false);
         self.store(value)
     }
 
@@ -61,9 +192,13 @@ impl Memory {
     }
 
     pub fn intern(&mut self, atom: String) -> RcValue {
-        let value = Value::Atom(atom);
+        if let Some(symbol) = self.symbols.get(&atom) {
+            return symbol.clone();
+        }
 
-        self.store(value)
+        let value = self.store(Value::Atom(atom.clone()));
+        self.symbols.insert(atom, value.clone());
+        value
     }
 
     pub fn closure(
@@ -84,19 +219,37 @@ impl Memory {
         self.store(value)
     }
 
+    pub fn case_lambda(&mut self, clauses: Vec<RcValue>) -> RcValue {
+        self.store(Value::CaseLambda(clauses))
+    }
+
     pub fn primitive(&self, name: String) -> RcValue {
         Rc::new(Value::PrimitiveFn(name))
     }
 
     pub fn dump(&self) {
-        for (i, value) in self.heap.iter().enumerate() {
+        for (i, value) in self.heap.iter().filter_map(Weak::upgrade).enumerate() {
             println!("{:04}: {:p} {}", i, value, value);
         }
     }
 
+    /// The heap only tracks values for `dump`: they are owned by whoever
+    /// references them, and dropped from the heap once they are gone.
     pub fn store(&mut self, value: Value) -> RcValue {
-        self.heap.push(Rc::new(value));
-        self.heap.last().unwrap().clone()
+        let value = Rc::new(value);
+
+        if self.heap.len() >= self.heap_limit {
+            self.heap.retain(|value| value.strong_count() > 0);
+            self.heap_limit = MIN_HEAP_LIMIT.max(self.heap.len() * 2);
+        }
+        self.heap.push(Rc::downgrade(&value));
+
+        value
+    }
+
+    /// Number of values on the heap, dead or alive.
+    pub fn heap_size(&self) -> usize {
+        self.heap.len()
     }
 
     fn init(&mut self) {
@@ -104,9 +257,29 @@ impl Memory {
         self.store_reserved(Value::Bool(false));
         self.store_reserved(Value::Nil);
         self.store_reserved(Value::Unspecified);
+        self.store_reserved(Value::Uninitialized);
     }
 
     fn store_reserved(&mut self, value: Value) {
         self.reserved.push(Rc::new(value));
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Memory, MIN_HEAP_LIMIT};
+    use crate::values::Value;
+
+    #[test]
+    fn dead_values_are_dropped_from_the_heap() {
+        let mut memory = Memory::new();
+        let kept = memory.integer(1);
+
+        for n in 0..10 * MIN_HEAP_LIMIT {
+            memory.integer(n as i64);
+        }
+
+        assert!(memory.heap_size() <= MIN_HEAP_LIMIT);
+        assert_eq!(Value::Integer(1), *kept);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::values::RcValue;
+use std::cell::RefCell;
+
+/// A procedure returning a dynamically bound value, rebound by
+/// `parameterize`.
+#[derive(Clone)]
+pub struct Parameter {
+    pub value: RefCell<RcValue>,
+    /// Applied to the values the parameter is bound to, initial one included.
+    pub converter: Option<RcValue>,
+}
+
+impl Parameter {
+    pub fn new(value: RcValue, converter: Option<RcValue>) -> Parameter {
+        Parameter {
+            value: RefCell::new(value),
+            converter,
+        }
+    }
+
+    pub fn get(&self) -> RcValue {
+        self.value.borrow().clone()
+    }
+
+    /// Binds the parameter to `value`, returning the previous value.
+    pub fn replace(&self, value: RcValue) -> RcValue {
+        self.value.replace(value)
+    }
+}
@@ -7,6 +7,35 @@ pub type Span<'a> = LocatedSpan<CompleteStr<'a>>;
 #[derive(PartialEq, Debug)]
 pub struct ParseError<'a>(Err<Span<'a>>);
 
+impl<'a> ParseError<'a> {
+    /// Where the input stopped making sense, if known.
+    pub fn position(&self) -> Option<Position> {
+        match self.0 {
+            Err::Error(Context::Code(ref span, _))
+            | Err::Failure(Context::Code(ref span, _)) => {
+                Some(Position::of(span))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A line and a column of the input, both starting from 1.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Position {
+    pub line: u32,
+    pub column: usize,
+}
+
+impl Position {
+    fn of(span: &Span) -> Position {
+        Position {
+            line: span.line,
+            column: span.get_utf8_column(),
+        }
+    }
+}
+
 named!(boolean(Span) -> AST,
     do_parse!(
         tag!("#") >>
@@ -25,7 +54,7 @@ named!(integer(Span) -> AST,
 
 named!(initial(Span) -> Span, alt!(constituent | special_initial));
 named!(constituent(Span) -> Span, recognize!(count!(alpha1, 1)));
-named!(special_initial(Span) -> Span, recognize!(one_of!("!$%&*/<=>?^_~")));
+named!(special_initial(Span) -> Span, recognize!(one_of!("!$%&*/:<=>?^_~")));
 named!(one_digit(Span) -> Span, recognize!(count!(digit1, 1)));
 named!(subsequent(Span) -> Span, alt!(initial | one_digit | special_subsequent));
 named!(special_subsequent(Span) -> Span, recognize!(one_of!("+-.@")));
@@ -47,6 +76,125 @@ named!(atom(Span) -> AST,
     )
 );
 
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "()[]\";".contains(c)
+}
+
+fn parse_error<O>(input: Span) -> IResult<Span, O> {
+    Err(Err::Error(error_position!(input, ErrorKind::Custom(0))))
+}
+
+fn character(input: Span) -> IResult<Span, AST> {
+    let fragment = input.fragment.0;
+
+    if !fragment.starts_with("#\\") {
+        return parse_error(input);
+    }
+
+    let rest = &fragment[2..];
+    let first = match rest.chars().next() {
+        Some(c) => c,
+        None => return parse_error(input),
+    };
+    let len = first.len_utf8()
+        + rest[first.len_utf8()..]
+            .chars()
+            .take_while(|&c| !is_delimiter(c))
+            .map(char::len_utf8)
+            .sum::<usize>();
+    let name = &rest[..len];
+
+    let c = if len == first.len_utf8() {
+        first
+    } else {
+        match name {
+            "alarm" => '\x07',
+            "backspace" => '\x08',
+            "delete" => '\x7f',
+            "escape" => '\x1b',
+            "linefeed" | "newline" => '\n',
+            "nul" => '\0',
+            "page" => '\x0c',
+            "return" => '\r',
+            "space" => ' ',
+            "tab" => '\t',
+            "vtab" => '\x0b',
+            _ => match name
+                .strip_prefix('x')
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(std::char::from_u32)
+            {
+                Some(c) => c,
+                None => return parse_error(input),
+            },
+        }
+    };
+
+    Ok((input.slice(2 + len..), Char(c)))
+}
+
+fn string(input: Span) -> IResult<Span, AST> {
+    let fragment = input.fragment.0;
+
+    if !fragment.starts_with('"') {
+        return parse_error(input);
+    }
+
+    let mut string = String::new();
+    let mut chars = fragment.char_indices().skip(1).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((input.slice(i + 1..), Str(string))),
+            '\\' => match chars.next() {
+                Some((_, 'a')) => string.push('\x07'),
+                Some((_, 'b')) => string.push('\x08'),
+                Some((_, 't')) => string.push('\t'),
+                Some((_, 'n')) => string.push('\n'),
+                Some((_, 'v')) => string.push('\x0b'),
+                Some((_, 'f')) => string.push('\x0c'),
+                Some((_, 'r')) => string.push('\r'),
+                Some((_, '"')) => string.push('"'),
+                Some((_, '\\')) => string.push('\\'),
+                Some((_, 'x')) => {
+                    let mut hex = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ';')) => break,
+                            Some((_, c)) if c.is_ascii_hexdigit() => hex.push(c),
+                            _ => return parse_error(input),
+                        }
+                    }
+
+                    match u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                    {
+                        Some(c) => string.push(c),
+                        None => return parse_error(input),
+                    }
+                }
+                Some((_, c)) if c.is_whitespace() => {
+                    // Line continuation: skip the rest of the line and the
+                    // leading whitespace of the next one.
+                    let mut newline = c == '\n';
+                    while let Some(&(_, c)) = chars.peek() {
+                        if !c.is_whitespace() || (newline && c == '\n') {
+                            break;
+                        }
+                        newline |= c == '\n';
+                        chars.next();
+                    }
+                }
+                _ => return parse_error(input),
+            },
+            c => string.push(c),
+        }
+    }
+
+    parse_error(input)
+}
+
 named!(quoted(Span) -> AST,
     map!(
         preceded!(char!('\''), value),
@@ -54,6 +202,13 @@ named!(quoted(Span) -> AST,
     )
 );
 
+named!(syntax(Span) -> AST,
+    map!(
+        preceded!(tag!("#'"), value),
+        |ast| List(vec![Atom("syntax".into()), ast])
+    )
+);
+
 named_args!(list(op: char, cl: char) <Span, AST>,
     delimited!(
         char!(op),
@@ -99,8 +254,21 @@ named!(list_or_dotted(Span) -> AST,
     )
 );
 
+named!(vector(Span) -> AST,
+    do_parse!(
+        tag!("#(") >>
+        items: separated_list_complete!(multispace1, value) >>
+        char!(')') >>
+        (Vector(items))
+    )
+);
+
 named!(value(Span) -> AST,
-    alt!(boolean | integer | atom | quoted | list_or_dotted)
+    alt!(
+        character | string | vector | boolean | integer | atom | quoted
+      | syntax
+      | list_or_dotted
+    )
 );
 
 named!(exprs(Span) -> Vec<AST>,
@@ -115,12 +283,45 @@ named!(exprs(Span) -> Vec<AST>,
     )
 );
 
+fn here(input: Span) -> IResult<Span, Span> {
+    Ok((input, input))
+}
+
+named!(located_value(Span) -> (AST, Position),
+    do_parse!(
+        position: here >>
+        value: value >>
+        ((value, Position::of(&position)))
+    )
+);
+
+named!(located_exprs(Span) -> Vec<(AST, Position)>,
+    do_parse!(
+        values: delimited!(
+            multispace0,
+            separated_list_complete!(multispace1, located_value),
+            multispace0
+        ) >>
+        eof!() >>
+        (values)
+    )
+);
+
 pub fn parse<'a>(input: &'a str) -> Result<Vec<AST>, ParseError<'a>> {
     let input = Span::new(CompleteStr(input));
 
     exprs(input).map(|(_, v)| v).map_err(ParseError)
 }
 
+/// Parses `input` like `parse`, along with where each expression starts.
+pub fn parse_located<'a>(
+    input: &'a str,
+) -> Result<Vec<(AST, Position)>, ParseError<'a>> {
+    let input = Span::new(CompleteStr(input));
+
+    located_exprs(input).map(|(_, v)| v).map_err(ParseError)
+}
+
 #[cfg(test)]
 mod test {
     use crate::ast::AST::{self, *};
@@ -183,6 +384,7 @@ mod test {
         assert_parse!([Atom("%".into())], "%");
         assert_parse!([Atom("*".into())], "*");
         assert_parse!([Atom("/".into())], "/");
+        assert_parse!([Atom(":".into())], ":");
         assert_parse!([Atom("<".into())], "<");
         assert_parse!([Atom("=".into())], "=");
         assert_parse!([Atom(">".into())], ">");
@@ -193,6 +395,7 @@ mod test {
 
         assert_parse!([Atom("str=>int".into())], "str=>int");
         assert_parse!([Atom("true?".into())], "true?");
+        assert_parse!([Atom("list:map".into())], "list:map");
 
         assert_parse!([Atom("what+is+this".into())], "what+is+this");
         assert_parse!([Atom("what-is-this".into())], "what-is-this");
@@ -209,6 +412,60 @@ mod test {
         assert_parse!([Atom("->".into())], " \n\r\t-> \n\r\t");
     }
 
+    #[test]
+    fn characters() {
+        assert_parse!([Char('a')], "#\\a");
+        assert_parse!([Char('Z')], "#\\Z");
+        assert_parse!([Char('(')], "#\\(");
+        assert_parse!([Char('x')], "#\\x");
+        assert_parse!([Char('λ')], "#\\λ");
+        assert_parse!([Char(' ')], "#\\space");
+        assert_parse!([Char('\n')], "#\\newline");
+        assert_parse!([Char('\t')], "#\\tab");
+        assert_parse!([Char('\0')], "#\\nul");
+        assert_parse!([Char('A')], "#\\x41");
+        assert_parse!([Char('λ')], "#\\x3bb");
+        assert_parse!(
+            [List(vec![Char('a'), Char(' '), Char(')')])],
+            "(#\\a #\\space #\\))"
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_parse!([Str("".into())], r#""""#);
+        assert_parse!([Str("abc".into())], r#""abc""#);
+        assert_parse!([Str("λx".into())], r#""λx""#);
+        assert_parse!([Str("a\"b\\c".into())], r#""a\"b\\c""#);
+        assert_parse!([Str("a\nb\tc".into())], r#""a\nb\tc""#);
+        assert_parse!([Str("AB".into())], r#""\x41;\x42;""#);
+        assert_parse!([Str("ab".into())], "\"a\\  \n    b\"");
+        assert_parse!(
+            [List(vec![Str("a".into()), Str("b c".into())])],
+            r#"("a" "b c")"#
+        );
+    }
+
+    #[test]
+    fn malformed_strings() {
+        assert!(super::parse(r#""abc"#).is_err());
+        assert!(super::parse(r#""\q""#).is_err());
+        assert!(super::parse(r#""\x41""#).is_err());
+    }
+
+    #[test]
+    fn vectors() {
+        assert_parse!([Vector(vec![])], "#()");
+        assert_parse!([Vector(vec![Integer(1), Atom("a".into())])], "#(1 a)");
+        assert_parse!(
+            [Vector(vec![
+                List(vec![Integer(1)]),
+                Vector(vec![Str("s".into())])
+            ])],
+            "#((1) #(\"s\"))"
+        );
+    }
+
     #[test]
     fn quoted_literals() {
         assert_parse!([List(vec![Atom("quote".into()), Integer(1)])], "'1");
@@ -225,6 +482,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn syntax_literals() {
+        assert_parse!(
+            [List(vec![Atom("syntax".into()), Atom("x".into())])],
+            "#'x"
+        );
+        assert_parse!(
+            [List(vec![
+                Atom("syntax".into()),
+                List(vec![Atom("a".into()), Integer(1)]),
+            ])],
+            "#'(a 1)"
+        );
+    }
+
     #[test]
     fn lists() {
         let empty = [List(vec![])];
@@ -0,0 +1,45 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+};
+
+#[derive(Clone)]
+pub enum Port {
+    Stdin,
+    Stdout,
+    Stderr,
+    /// Accumulates what is written to it, see `get-output-string`.
+    StringOutput(RefCell<String>),
+}
+
+impl Port {
+    pub fn is_input(&self) -> bool {
+        matches!(*self, Port::Stdin)
+    }
+
+    pub fn is_output(&self) -> bool {
+        !self.is_input()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Port::Stdin => "stdin",
+            Port::Stdout => "stdout",
+            Port::Stderr => "stderr",
+            Port::StringOutput(..) => "string",
+        }
+    }
+
+    /// Writes `text` to an output port. Writing to an input port does nothing.
+    pub fn write(&self, text: &str) {
+        match *self {
+            Port::Stdin => (),
+            Port::Stdout => {
+                print!("{}", text);
+                let _ = io::stdout().flush();
+            }
+            Port::Stderr => eprint!("{}", text),
+            Port::StringOutput(ref buffer) => buffer.borrow_mut().push_str(text),
+        }
+    }
+}
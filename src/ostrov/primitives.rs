@@ -1,19 +1,91 @@
 use crate::{
+    enumerations::EnumSetProcedure,
     errors::RuntimeError,
     memory::Memory,
-    values::{RcValue, Value},
+    parameters::Parameter,
+    records::RecordProcedure,
+    values::{self, RcValue, Value},
+    vm::VM,
 };
-
-pub static PRIMITIVES: [&str; 20] = [
-    "*", "+", "-", "/", "<", "<=", "=", ">", ">=", "car", "cdr", "cons",
-    "length", "list", "list?", "not", "null?", "pair?", "display", "newline",
+use std::rc::Rc;
+
+mod conditions;
+mod enumerations;
+mod eval;
+mod exceptions;
+mod hashtables;
+mod parameters;
+mod ports;
+mod promises;
+mod records;
+mod strings;
+mod syntax;
+mod vectors;
+
+pub static PRIMITIVES: [&str; 36] = [
+    "*",
+    "+",
+    "-",
+    "/",
+    "<",
+    "<=",
+    "=",
+    ">",
+    ">=",
+    "car",
+    "cdr",
+    "cons",
+    "length",
+    "list",
+    "list?",
+    "not",
+    "null?",
+    "pair?",
+    "eq?",
+    "eqv?",
+    "equal?",
+    "memq",
+    "memv",
+    "member",
+    "assq",
+    "assv",
+    "assoc",
+    "set-car!",
+    "set-cdr!",
+    "values",
+    "call-with-values",
+    "div",
+    "mod",
+    "div-and-mod",
+    "%case-lambda",
+    "%memv",
 ];
 
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PRIMITIVES
+        .iter()
+        .chain(strings::PRIMITIVES.iter())
+        .chain(vectors::PRIMITIVES.iter())
+        .chain(hashtables::PRIMITIVES.iter())
+        .chain(records::PRIMITIVES.iter())
+        .chain(conditions::PRIMITIVES.iter())
+        .chain(enumerations::PRIMITIVES.iter())
+        .chain(promises::PRIMITIVES.iter())
+        .chain(parameters::PRIMITIVES.iter())
+        .chain(ports::PRIMITIVES.iter())
+        .chain(syntax::PRIMITIVES.iter())
+        .chain(eval::PRIMITIVES.iter())
+        .chain(exceptions::PRIMITIVES.iter())
+        .cloned()
+}
+
 pub fn apply(
     name: &str,
     args: &[RcValue],
-    mem: &mut Memory,
+    vm: &mut VM,
 ) -> Result<RcValue, RuntimeError> {
+    let mem = &mut vm.memory;
+
     match name {
         "*" => product(args, mem),
         "+" => plus(args, mem),
@@ -26,6 +98,8 @@ pub fn apply(
         ">=" => greater_than_or_equal(args, mem),
         "car" => car(args),
         "cdr" => cdr(args),
+        "set-car!" => set_car(args, mem),
+        "set-cdr!" => set_cdr(args, mem),
         "cons" => cons(args, mem),
         "length" => length(args, mem),
         "list" => list(args, mem),
@@ -33,12 +107,105 @@ pub fn apply(
         "not" => not(args, mem),
         "null?" => null(args, mem),
         "pair?" => pair(args, mem),
-        "display" => display(args, mem),
-        "newline" => newline(args, mem),
+        "eq?" => equivalence("eq?", args, mem, values::eq),
+        "eqv?" => equivalence("eqv?", args, mem, values::eqv),
+        "equal?" => equivalence("equal?", args, mem, values::equal),
+        "memq" => member_by("memq", args, mem, values::eq),
+        "memv" => member_by("memv", args, mem, values::eqv),
+        "member" => member_by("member", args, mem, values::equal),
+        "assq" => assoc_by("assq", args, mem, values::eq),
+        "assv" => assoc_by("assv", args, mem, values::eqv),
+        "assoc" => assoc_by("assoc", args, mem, values::equal),
+        "values" => Ok(mem.values(args.to_vec())),
+        "div" => div_and_mod("div", args, mem, |div, _| vec![div]),
+        "mod" => div_and_mod("mod", args, mem, |_, modulo| vec![modulo]),
+        "div-and-mod" => {
+            div_and_mod("div-and-mod", args, mem, |div, modulo| vec![div, modulo])
+        }
+        "%case-lambda" => case_lambda(args, mem),
+        "%memv" => member_by("memv", args, mem, values::eqv),
+        _ if strings::PRIMITIVES.contains(&name) => {
+            strings::apply(name, args, vm)
+        }
+        _ if vectors::PRIMITIVES.contains(&name) => {
+            vectors::apply(name, args, vm)
+        }
+        _ if hashtables::PRIMITIVES.contains(&name) => {
+            hashtables::apply(name, args, vm)
+        }
+        _ if records::PRIMITIVES.contains(&name) => {
+            records::apply(name, args, vm)
+        }
+        _ if conditions::PRIMITIVES.contains(&name) => {
+            conditions::apply(name, args, vm)
+        }
+        _ if enumerations::PRIMITIVES.contains(&name) => {
+            enumerations::apply(name, args, vm)
+        }
+        _ if promises::PRIMITIVES.contains(&name) => {
+            promises::apply(name, args, vm)
+        }
+        _ if parameters::PRIMITIVES.contains(&name) => {
+            parameters::apply(name, args, vm)
+        }
+        _ if ports::PRIMITIVES.contains(&name) => ports::apply(name, args, vm),
+        _ if syntax::PRIMITIVES.contains(&name) => syntax::apply(name, args, vm),
+        _ if eval::PRIMITIVES.contains(&name) => eval::apply(name, args, vm),
+        _ if exceptions::PRIMITIVES.contains(&name) => {
+            exceptions::apply(name, args, vm)
+        }
         _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
     }
 }
 
+/// Applies the built-in procedure `name` to `args` in place, if it calls back
+/// into Scheme code: rather than returning a value, it sets the VM up to run
+/// that code. Returns `None` for the other built-in procedures.
+pub fn apply_in_place(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    tail: bool,
+) -> Option<Result<(), RuntimeError>> {
+    match name {
+        "%parameterize" => Some(parameters::parameterize(args, vm, tail)),
+        "with-exception-handler" => {
+            Some(exceptions::with_exception_handler(args, vm, tail))
+        }
+        "%guard" => Some(exceptions::guard(args, vm, tail)),
+        "force" => promises::force_in_place(args, vm, tail),
+        _ => None,
+    }
+}
+
+/// Settles `promise` with the value its thunk returned.
+pub fn settle_promise(promise: &RcValue, value: RcValue) {
+    promises::settle(promise, value)
+}
+
+pub fn apply_record_procedure(
+    procedure: &RecordProcedure,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    records::apply_procedure(procedure, args, vm)
+}
+
+pub fn apply_parameter(
+    parameter: &Parameter,
+    args: &[RcValue],
+) -> Result<RcValue, RuntimeError> {
+    parameters::apply_parameter(parameter, args)
+}
+
+pub fn apply_enum_set_procedure(
+    procedure: &EnumSetProcedure,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    enumerations::apply_procedure(procedure, args, vm)
+}
+
 fn plus(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
     let integers = list_of_integers(args)?;
     let sum = integers.into_iter().sum();
@@ -77,6 +244,49 @@ fn division(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError>
     }
 }
 
+/// Euclidean division, as in R6RS: the modulo is never negative. Dividing the
+/// smallest integer by -1 overflows, there being no bignums.
+fn div_and_mod<F>(
+    name: &str,
+    args: &[RcValue],
+    mem: &mut Memory,
+    f: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(i64, i64) -> Vec<i64>,
+{
+    check_arity(name, args, 2, 2)?;
+
+    let integers = list_of_integers(args)?;
+    let (n, d) = (integers[0], integers[1]);
+
+    if d == 0 {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    let results = match (n.checked_div_euclid(d), n.checked_rem_euclid(d)) {
+        (Some(div), Some(modulo)) => f(div, modulo),
+        _ => return Err(RuntimeError::IntegerOverflow(name.to_owned())),
+    };
+
+    let values = results.into_iter().map(|n| mem.integer(n)).collect();
+
+    Ok(mem.values(values))
+}
+
+/// `(%case-lambda closure ...)`, which `case-lambda` compiles to: a procedure
+/// applying the first of the closures that accepts its arguments.
+fn case_lambda(
+    args: &[RcValue],
+    mem: &mut Memory,
+) -> Result<RcValue, RuntimeError> {
+    if let Some(arg) = args.iter().find(|arg| arg.arity().is_none()) {
+        return Err(RuntimeError::WrongArgumentType(arg.clone()));
+    }
+
+    Ok(mem.case_lambda(args.to_vec()))
+}
+
 fn product(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
     let integers = list_of_integers(args)?;
     let product = integers.into_iter().product();
@@ -128,7 +338,7 @@ fn not(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
         return Err(RuntimeError::BadArity(Some("not".to_owned())));
     }
 
-    Ok(mem.boolean(args[0] == mem.b_false()))
+    Ok(mem.boolean(args[0].is_false()))
 }
 
 fn list(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
@@ -170,10 +380,9 @@ fn car(args: &[RcValue]) -> Result<RcValue, RuntimeError> {
         return Err(RuntimeError::BadArity(Some("car".to_owned())));
     }
 
-    match *args[0] {
-        Value::Pair(ref left, ref _right) => Ok(left.clone()),
-        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
-    }
+    args[0]
+        .car()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[0].clone()))
 }
 
 fn cdr(args: &[RcValue]) -> Result<RcValue, RuntimeError> {
@@ -181,10 +390,49 @@ fn cdr(args: &[RcValue]) -> Result<RcValue, RuntimeError> {
         return Err(RuntimeError::BadArity(Some("cdr".to_owned())));
     }
 
+    args[0]
+        .cdr()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[0].clone()))
+}
+
+fn set_car(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some("set-car!".to_owned())));
+    }
+
+    match *args[0] {
+        Value::Pair {
+            ref car,
+            mutable: true,
+            ..
+        } => *car.borrow_mut() = args[1].clone(),
+        Value::Pair { .. } => {
+            return Err(RuntimeError::ImmutableValue(args[0].clone()))
+        }
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+
+    Ok(mem.unspecified())
+}
+
+fn set_cdr(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some("set-cdr!".to_owned())));
+    }
+
     match *args[0] {
-        Value::Pair(ref _left, ref right) => Ok(right.clone()),
-        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+        Value::Pair {
+            ref cdr,
+            mutable: true,
+            ..
+        } => *cdr.borrow_mut() = args[1].clone(),
+        Value::Pair { .. } => {
+            return Err(RuntimeError::ImmutableValue(args[0].clone()))
+        }
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
     }
+
+    Ok(mem.unspecified())
 }
 
 fn null(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
@@ -192,7 +440,7 @@ fn null(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
         return Err(RuntimeError::BadArity(Some("null?".to_owned())));
     }
 
-    Ok(mem.boolean(args[0] == mem.nil()))
+    Ok(mem.boolean(args[0].is_nil()))
 }
 
 fn is_list(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
@@ -204,6 +452,125 @@ fn is_list(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError>
     Ok(mem.boolean(outcome))
 }
 
+fn equivalence<F>(
+    name: &str,
+    args: &[RcValue],
+    mem: &mut Memory,
+    cmp: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&RcValue, &RcValue) -> bool,
+{
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some(name.to_owned())));
+    }
+
+    Ok(mem.boolean(cmp(&args[0], &args[1])))
+}
+
+fn member_by<F>(
+    name: &str,
+    args: &[RcValue],
+    mem: &mut Memory,
+    cmp: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&RcValue, &RcValue) -> bool,
+{
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some(name.to_owned())));
+    }
+
+    match find_pair(&args[1], |item| Ok(cmp(&args[0], item)))? {
+        Some(pair) => Ok(pair),
+        None => Ok(mem.b_false()),
+    }
+}
+
+fn assoc_by<F>(
+    name: &str,
+    args: &[RcValue],
+    mem: &mut Memory,
+    cmp: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&RcValue, &RcValue) -> bool,
+{
+    if args.len() != 2 {
+        return Err(RuntimeError::BadArity(Some(name.to_owned())));
+    }
+
+    let found = find_pair(&args[1], |entry| match entry.car() {
+        Some(ref key) => Ok(cmp(&args[0], key)),
+        None => Err(RuntimeError::WrongArgumentType(entry.clone())),
+    })?;
+
+    match found.and_then(|pair| pair.car()) {
+        Some(entry) => Ok(entry),
+        None => Ok(mem.b_false()),
+    }
+}
+
+/// The first pair of `list` whose car `found` accepts. Anything but a proper
+/// list is a wrong argument, circular lists included, which are told apart
+/// the way `pair_len` does.
+fn find_pair<F>(
+    list: &RcValue,
+    mut found: F,
+) -> Result<Option<RcValue>, RuntimeError>
+where
+    F: FnMut(&RcValue) -> Result<bool, RuntimeError>,
+{
+    let mut pair = list.clone();
+    let mut slow = list.clone();
+    let mut steps = 0;
+
+    loop {
+        let next = match *pair {
+            Value::Pair {
+                ref car, ref cdr, ..
+            } => {
+                if found(&car.borrow())? {
+                    return Ok(Some(pair.clone()));
+                }
+
+                cdr.borrow().clone()
+            }
+            Value::Nil => return Ok(None),
+            _ => return Err(RuntimeError::WrongArgumentType(list.clone())),
+        };
+
+        pair = next;
+        steps += 1;
+        if steps % 2 == 0 {
+            slow = slow.cdr().unwrap();
+        }
+        if Rc::ptr_eq(&pair, &slow) {
+            return Err(RuntimeError::WrongArgumentType(list.clone()));
+        }
+    }
+}
+
+fn check_arity(
+    name: &str,
+    args: &[RcValue],
+    min: usize,
+    max: usize,
+) -> Result<(), RuntimeError> {
+    if args.len() < min || args.len() > max {
+        return Err(RuntimeError::BadArity(Some(name.to_owned())));
+    }
+
+    Ok(())
+}
+
+fn index_arg(value: &RcValue) -> Result<usize, RuntimeError> {
+    match **value {
+        Value::Integer(n) if n >= 0 => Ok(n as usize),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
 fn list_of_integers(list: &[RcValue]) -> Result<Vec<i64>, RuntimeError> {
     let mut integers = Vec::with_capacity(list.len());
 
@@ -235,23 +602,3 @@ where
 
     Ok(mem.boolean(outcome))
 }
-
-fn display(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
-    if args.len() != 1 {
-        return Err(RuntimeError::BadArity(Some("display".to_owned())));
-    }
-
-    print!("{}", args.first().unwrap());
-
-    Ok(mem.b_true())
-}
-
-fn newline(args: &[RcValue], mem: &mut Memory) -> Result<RcValue, RuntimeError> {
-    if !args.is_empty() {
-        return Err(RuntimeError::BadArity(Some("newline".to_owned())));
-    }
-
-    println!();
-
-    Ok(mem.b_true())
-}
@@ -0,0 +1,144 @@
+use crate::{
+    errors::RuntimeError,
+    records::{RecordProcedure, RecordType},
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::rc::Rc;
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 5] = [
+    "condition",
+    "simple-conditions",
+    "condition?",
+    "condition-predicate",
+    "condition-accessor",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "condition" => condition(args, vm),
+        "simple-conditions" => simple_conditions(args, vm),
+        "condition?" => is_condition(args, vm),
+        "condition-predicate" => condition_predicate(args, vm),
+        "condition-accessor" => condition_accessor(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// The simple conditions making up `value`, or `None` if it isn't a
+/// condition.
+fn components(value: &RcValue, vm: &VM) -> Option<Vec<RcValue>> {
+    match **value {
+        Value::Condition(ref components) => Some(components.clone()),
+        Value::Record { ref rtd, .. }
+            if rtd.is_subtype_of(&vm.conditions.condition) =>
+        {
+            Some(vec![value.clone()])
+        }
+        _ => None,
+    }
+}
+
+/// First simple condition of type `rtd` in `value`.
+pub fn component(value: &RcValue, rtd: &RecordType) -> Option<RcValue> {
+    let is_instance = |value: &RcValue| match **value {
+        Value::Record { rtd: ref other, .. } => other.is_subtype_of(rtd),
+        _ => false,
+    };
+
+    match **value {
+        Value::Condition(ref components) => {
+            components.iter().find(|c| is_instance(c)).cloned()
+        }
+        _ if is_instance(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn condition_type_arg(
+    value: &RcValue,
+    vm: &VM,
+) -> Result<Rc<RecordType>, RuntimeError> {
+    let rtd = match **value {
+        Value::RecordTypeDescriptor(ref rtd) => rtd.clone(),
+        Value::RecordConstructorDescriptor(ref descriptor) => {
+            descriptor.rtd.clone()
+        }
+        _ => return Err(RuntimeError::WrongArgumentType(value.clone())),
+    };
+
+    if rtd.is_subtype_of(&vm.conditions.condition) {
+        Ok(rtd)
+    } else {
+        Err(RuntimeError::WrongArgumentType(value.clone()))
+    }
+}
+
+fn condition(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let mut simple = vec![];
+
+    for arg in args {
+        match components(arg, vm) {
+            Some(components) => simple.extend(components),
+            None => return Err(RuntimeError::WrongArgumentType(arg.clone())),
+        }
+    }
+
+    Ok(vm.memory.condition(simple))
+}
+
+fn simple_conditions(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("simple-conditions", args, 1, 1)?;
+
+    match components(&args[0], vm) {
+        Some(components) => Ok(vm.memory.list(components)),
+        None => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn is_condition(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("condition?", args, 1, 1)?;
+
+    let is_condition = components(&args[0], vm).is_some();
+    Ok(vm.memory.boolean(is_condition))
+}
+
+fn condition_predicate(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("condition-predicate", args, 1, 1)?;
+
+    let rtd = condition_type_arg(&args[0], vm)?;
+    Ok(vm
+        .memory
+        .record_procedure(RecordProcedure::ConditionPredicate(rtd)))
+}
+
+fn condition_accessor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("condition-accessor", args, 2, 2)?;
+
+    let rtd = condition_type_arg(&args[0], vm)?;
+    if !args[1].is_procedure() {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    Ok(vm
+        .memory
+        .record_procedure(RecordProcedure::ConditionAccessor(
+            rtd,
+            args[1].clone(),
+        )))
+}
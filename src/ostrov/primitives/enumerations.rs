@@ -0,0 +1,250 @@
+use crate::{
+    enumerations::{EnumSet, EnumSetProcedure, Enumeration},
+    errors::RuntimeError,
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::rc::Rc;
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 14] = [
+    "make-enumeration",
+    "enum-set?",
+    "enum-set-universe",
+    "enum-set-indexer",
+    "enum-set-constructor",
+    "enum-set->list",
+    "enum-set-member?",
+    "enum-set-subset?",
+    "enum-set=?",
+    "enum-set-union",
+    "enum-set-intersection",
+    "enum-set-difference",
+    "enum-set-complement",
+    "enum-set-projection",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "make-enumeration" => make_enumeration(args, vm),
+        "enum-set?" => is_enum_set(args, vm),
+        "enum-set-universe" => enum_set_universe(args, vm),
+        "enum-set-indexer" => enum_set_procedure(name, args, vm),
+        "enum-set-constructor" => enum_set_procedure(name, args, vm),
+        "enum-set->list" => enum_set_to_list(args, vm),
+        "enum-set-member?" => is_enum_set_member(args, vm),
+        "enum-set-subset?" => is_enum_set_subset(args, vm),
+        "enum-set=?" => are_enum_sets_equal(args, vm),
+        "enum-set-union" => combine(name, args, vm, |a, b| a || b),
+        "enum-set-intersection" => combine(name, args, vm, |a, b| a && b),
+        "enum-set-difference" => combine(name, args, vm, |a, b| a && !b),
+        "enum-set-complement" => enum_set_complement(args, vm),
+        "enum-set-projection" => enum_set_projection(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// Applies a procedure returned by `enum-set-indexer` or
+/// `enum-set-constructor`.
+pub fn apply_procedure(
+    procedure: &EnumSetProcedure,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity(procedure.name(), args, 1, 1)?;
+
+    match *procedure {
+        EnumSetProcedure::Indexer(ref universe) => {
+            match universe.index(&symbol_arg(&args[0])?) {
+                Some(index) => Ok(vm.memory.integer(index as i64)),
+                None => Ok(vm.memory.b_false()),
+            }
+        }
+        EnumSetProcedure::Constructor(ref universe) => {
+            let mut set = EnumSet::empty(universe.clone());
+
+            for symbol in symbols_arg(&args[0])? {
+                match universe.index(&symbol) {
+                    Some(index) => set.members[index] = true,
+                    None => {
+                        let symbol = vm.memory.intern(symbol);
+                        return Err(RuntimeError::WrongArgumentType(symbol));
+                    }
+                }
+            }
+
+            Ok(vm.memory.enum_set(set))
+        }
+    }
+}
+
+fn symbol_arg(value: &RcValue) -> Result<String, RuntimeError> {
+    match **value {
+        Value::Atom(ref symbol) => Ok(symbol.clone()),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn symbols_arg(value: &RcValue) -> Result<Vec<String>, RuntimeError> {
+    value
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(value.clone()))?
+        .iter()
+        .map(symbol_arg)
+        .collect()
+}
+
+fn enum_set_arg(value: &RcValue) -> Result<&EnumSet, RuntimeError> {
+    match **value {
+        Value::EnumSet(ref set) => Ok(set),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn make_enumeration(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("make-enumeration", args, 1, 1)?;
+
+    let universe = Rc::new(Enumeration::new(symbols_arg(&args[0])?));
+    Ok(vm.memory.enum_set(EnumSet::full(universe)))
+}
+
+fn is_enum_set(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::EnumSet(..))))
+}
+
+fn enum_set_universe(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set-universe", args, 1, 1)?;
+
+    let universe = enum_set_arg(&args[0])?.universe.clone();
+    Ok(vm.memory.enum_set(EnumSet::full(universe)))
+}
+
+fn enum_set_procedure(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity(name, args, 1, 1)?;
+
+    let universe = enum_set_arg(&args[0])?.universe.clone();
+    let procedure = if name == "enum-set-indexer" {
+        EnumSetProcedure::Indexer(universe)
+    } else {
+        EnumSetProcedure::Constructor(universe)
+    };
+
+    Ok(vm.memory.enum_set_procedure(procedure))
+}
+
+fn enum_set_to_list(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set->list", args, 1, 1)?;
+
+    let symbols = enum_set_arg(&args[0])?
+        .symbols()
+        .iter()
+        .map(|&symbol| vm.memory.intern(symbol.to_owned()))
+        .collect();
+
+    Ok(vm.memory.list(symbols))
+}
+
+fn is_enum_set_member(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set-member?", args, 2, 2)?;
+
+    let symbol = symbol_arg(&args[0])?;
+    let is_member = enum_set_arg(&args[1])?.contains(&symbol);
+
+    Ok(vm.memory.boolean(is_member))
+}
+
+fn is_enum_set_subset(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set-subset?", args, 2, 2)?;
+
+    let is_subset = enum_set_arg(&args[0])?.is_subset_of(enum_set_arg(&args[1])?);
+    Ok(vm.memory.boolean(is_subset))
+}
+
+fn are_enum_sets_equal(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set=?", args, 2, 2)?;
+
+    let (a, b) = (enum_set_arg(&args[0])?, enum_set_arg(&args[1])?);
+    Ok(vm.memory.boolean(a.is_subset_of(b) && b.is_subset_of(a)))
+}
+
+/// Set operations between two sets, which must share their universe.
+fn combine<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    f: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(bool, bool) -> bool,
+{
+    check_arity(name, args, 2, 2)?;
+
+    let (a, b) = (enum_set_arg(&args[0])?, enum_set_arg(&args[1])?);
+    if !a.same_universe(b) {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    let combined = a.combine(b, f);
+    Ok(vm.memory.enum_set(combined))
+}
+
+fn enum_set_complement(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set-complement", args, 1, 1)?;
+
+    let set = enum_set_arg(&args[0])?;
+    let full = EnumSet::full(set.universe.clone());
+    let complement = full.combine(set, |all, member| all && !member);
+
+    Ok(vm.memory.enum_set(complement))
+}
+
+fn enum_set_projection(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("enum-set-projection", args, 2, 2)?;
+
+    let set = enum_set_arg(&args[0])?;
+    let universe = enum_set_arg(&args[1])?.universe.clone();
+    let mut projection = EnumSet::empty(universe.clone());
+
+    for symbol in set.symbols() {
+        if let Some(index) = universe.index(symbol) {
+            projection.members[index] = true;
+        }
+    }
+
+    Ok(vm.memory.enum_set(projection))
+}
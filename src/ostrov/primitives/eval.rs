@@ -0,0 +1,104 @@
+use crate::{
+    errors::RuntimeError,
+    expander::Expander,
+    files,
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 5] = [
+    "eval",
+    "environment",
+    "interaction-environment",
+    "load",
+    "%located",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "eval" => eval(args, vm),
+        "environment" => environment(args, vm),
+        "interaction-environment" => interaction_environment(args, vm),
+        "load" => load(args, vm),
+        "%located" => located(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// `(eval expression environment)`: expands, compiles and runs the datum
+/// `expression` at the top level of `environment`.
+fn eval(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("eval", args, 2, 2)?;
+
+    let form = args[0]
+        .to_ast()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[0].clone()))?;
+    let form = vm.identifiers.strip(&form);
+    let top = match *args[1] {
+        Value::Environment(ref scope) => scope.clone(),
+        _ => return Err(RuntimeError::WrongArgumentType(args[1].clone())),
+    };
+
+    Expander::new(vm).eval(&form, &top)
+}
+
+/// `(environment import-spec ...)`: an environment where only the imported
+/// bindings are visible, which can't be defined in nor assigned.
+fn environment(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let specs = args
+        .iter()
+        .map(|spec| {
+            spec.to_ast()
+                .ok_or_else(|| RuntimeError::WrongArgumentType(spec.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let scope = Expander::new(vm).environment(&specs)?;
+    Ok(vm.memory.environment(scope))
+}
+
+/// `(interaction-environment)`: the top level of the REPL.
+fn interaction_environment(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("interaction-environment", args, 0, 0)?;
+
+    let scope = vm.identifiers.global().clone();
+    Ok(vm.memory.environment(scope))
+}
+
+/// `(load file-name)`: runs the forms of the file at the top level being run.
+/// A relative file name is resolved against the file being loaded, if any.
+fn load(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("load", args, 1, 1)?;
+
+    let name: String = match *args[0] {
+        Value::Str { ref chars, .. } => chars.borrow().iter().collect(),
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    };
+    let path = files::resolve(&name, &vm.loading);
+
+    Expander::new(vm).load(&path)?;
+    Ok(vm.memory.unspecified())
+}
+
+/// `(%located location thunk)`: calls `thunk`, locating the errors it raises
+/// at `location`, where the form it runs was read from a file.
+fn located(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("%located", args, 2, 2)?;
+
+    let location: String = match *args[0] {
+        Value::Str { ref chars, .. } => chars.borrow().iter().collect(),
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    };
+
+    vm.call(args[1].clone(), vec![])
+        .map_err(|error| error.located(location))
+}
@@ -0,0 +1,124 @@
+use crate::{
+    errors::RuntimeError,
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 6] = [
+    "with-exception-handler",
+    "raise",
+    "raise-continuable",
+    "error",
+    "assertion-violation",
+    "%guard",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "raise" => raise(args, vm, false),
+        "raise-continuable" => raise(args, vm, true),
+        "error" => error(args, vm, false),
+        "assertion-violation" => error(args, vm, true),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+fn procedure_arg(value: &RcValue) -> Result<RcValue, RuntimeError> {
+    if value.is_procedure() {
+        Ok(value.clone())
+    } else {
+        Err(RuntimeError::WrongArgumentType(value.clone()))
+    }
+}
+
+/// `(with-exception-handler handler thunk)` calls `thunk` in place, with
+/// `handler` installed until it returns.
+pub fn with_exception_handler(
+    args: &[RcValue],
+    vm: &mut VM,
+    tail: bool,
+) -> Result<(), RuntimeError> {
+    check_arity("with-exception-handler", args, 2, 2)?;
+
+    let handler = procedure_arg(&args[0])?;
+    let thunk = procedure_arg(&args[1])?;
+
+    vm.call_with_handler(handler, thunk, tail)
+}
+
+fn raise(
+    args: &[RcValue],
+    vm: &mut VM,
+    continuable: bool,
+) -> Result<RcValue, RuntimeError> {
+    let name = if continuable {
+        "raise-continuable"
+    } else {
+        "raise"
+    };
+    check_arity(name, args, 1, 1)?;
+
+    vm.raise(args[0].clone(), continuable)
+}
+
+/// `(error who message irritant ...)`, or `assertion-violation` with the
+/// same arguments, raises a non-continuable `&error` or `&assertion`
+/// condition. `who` is a string, a symbol or `#f`.
+fn error(
+    args: &[RcValue],
+    vm: &mut VM,
+    assertion: bool,
+) -> Result<RcValue, RuntimeError> {
+    let name = if assertion {
+        "assertion-violation"
+    } else {
+        "error"
+    };
+    check_arity(name, args, 2, usize::MAX)?;
+
+    match *args[0] {
+        Value::Str { .. } | Value::Atom(..) | Value::Bool(false) => (),
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+    if !matches!(*args[1], Value::Str { .. }) {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    let rtd = if assertion {
+        vm.conditions.assertion.clone()
+    } else {
+        vm.conditions.error.clone()
+    };
+    let irritants = vm.memory.list(args[2..].to_vec());
+    let condition = vm.conditions.error(
+        &rtd,
+        args[0].clone(),
+        args[1].clone(),
+        irritants,
+        &mut vm.memory,
+    );
+
+    vm.raise(condition, false)
+}
+
+/// `(%guard thunk clauses)`, which `guard` compiles to, calls `thunk` in
+/// place. `clauses` is called on the objects raised by `thunk`, and the value
+/// of the clause accepting one is returned.
+pub fn guard(
+    args: &[RcValue],
+    vm: &mut VM,
+    tail: bool,
+) -> Result<(), RuntimeError> {
+    check_arity("guard", args, 2, 2)?;
+
+    let thunk = procedure_arg(&args[0])?;
+    let clauses = procedure_arg(&args[1])?;
+
+    vm.call_guarded(clauses, thunk, tail)
+}
@@ -0,0 +1,421 @@
+use crate::{
+    errors::RuntimeError,
+    hashtable::{Equivalence, Hashtable},
+    values::{self, RcValue, Value},
+    vm::VM,
+};
+use std::{
+    cell::RefCell,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 18] = [
+    "make-eq-hashtable",
+    "make-eqv-hashtable",
+    "make-hashtable",
+    "hashtable?",
+    "hashtable-size",
+    "hashtable-ref",
+    "hashtable-set!",
+    "hashtable-update!",
+    "hashtable-delete!",
+    "hashtable-contains?",
+    "hashtable-clear!",
+    "hashtable-keys",
+    "hashtable-entries",
+    "hashtable-copy",
+    "hashtable-mutable?",
+    "equal-hash",
+    "string-hash",
+    "symbol-hash",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "make-eq-hashtable" => make_hashtable(name, args, vm, Equivalence::Eq),
+        "make-eqv-hashtable" => make_hashtable(name, args, vm, Equivalence::Eqv),
+        "make-hashtable" => make_custom_hashtable(args, vm),
+        "hashtable?" => is_hashtable(args, vm),
+        "hashtable-size" => hashtable_size(args, vm),
+        "hashtable-ref" => hashtable_ref(args, vm),
+        "hashtable-set!" => hashtable_set(args, vm),
+        "hashtable-update!" => hashtable_update(args, vm),
+        "hashtable-delete!" => hashtable_delete(args, vm),
+        "hashtable-contains?" => hashtable_contains(args, vm),
+        "hashtable-clear!" => hashtable_clear(args, vm),
+        "hashtable-keys" => hashtable_keys(args, vm),
+        "hashtable-entries" => hashtable_entries(args, vm),
+        "hashtable-copy" => hashtable_copy(args, vm),
+        "hashtable-mutable?" => hashtable_is_mutable(args, vm),
+        "equal-hash" => equal_hash(args, vm),
+        "string-hash" => string_hash(args, vm),
+        "symbol-hash" => symbol_hash(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn eq_hash_of(value: &RcValue) -> u64 {
+    values::identity(value) as usize as u64
+}
+
+fn eqv_hash_of(value: &RcValue) -> u64 {
+    match **value {
+        Value::Integer(n) => hash_of(&n),
+        Value::Char(c) => hash_of(&c),
+        _ => eq_hash_of(value),
+    }
+}
+
+/// Hash consistent with `equal?`. Only the first few levels of nested pairs
+/// and vectors are looked at, so that cyclic structures can be hashed.
+fn equal_hash_of(value: &RcValue, depth: usize) -> u64 {
+    if depth == 0 {
+        return 0;
+    }
+
+    match **value {
+        Value::Integer(n) => hash_of(&n),
+        Value::Char(c) => hash_of(&c),
+        Value::Bool(b) => hash_of(&b),
+        Value::Atom(ref name) => hash_of(name),
+        Value::Nil => hash_of(&"()"),
+        Value::Str { ref chars, .. } => hash_of(&*chars.borrow()),
+        Value::Pair {
+            ref car, ref cdr, ..
+        } => hash_of(&(
+            equal_hash_of(&car.borrow(), depth - 1),
+            equal_hash_of(&cdr.borrow(), depth - 1),
+        )),
+        Value::Vector { ref items, .. } => hash_of(
+            &items
+                .borrow()
+                .iter()
+                .map(|item| equal_hash_of(item, depth - 1))
+                .collect::<Vec<_>>(),
+        ),
+        _ => eq_hash_of(value),
+    }
+}
+
+fn hash_integer(hash: u64, vm: &mut VM) -> RcValue {
+    vm.memory.integer((hash >> 1) as i64)
+}
+
+fn table_arg(value: &RcValue) -> Result<&RefCell<Hashtable>, RuntimeError> {
+    match **value {
+        Value::Hashtable(ref table) => Ok(table),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn mutable_table_arg(
+    value: &RcValue,
+) -> Result<&RefCell<Hashtable>, RuntimeError> {
+    let table = table_arg(value)?;
+
+    if !table.borrow().mutable {
+        return Err(RuntimeError::ImmutableValue(value.clone()));
+    }
+
+    Ok(table)
+}
+
+fn procedure_arg(value: &RcValue) -> Result<RcValue, RuntimeError> {
+    if value.is_procedure() {
+        Ok(value.clone())
+    } else {
+        Err(RuntimeError::WrongArgumentType(value.clone()))
+    }
+}
+
+fn hash_key(
+    equivalence: &Equivalence,
+    key: &RcValue,
+    vm: &mut VM,
+) -> Result<u64, RuntimeError> {
+    match *equivalence {
+        Equivalence::Eq => Ok(eq_hash_of(key)),
+        Equivalence::Eqv => Ok(eqv_hash_of(key)),
+        Equivalence::Custom { ref hash, .. } => {
+            let result = vm.call(hash.clone(), vec![key.clone()])?;
+
+            match *result {
+                Value::Integer(n) => Ok(n as u64),
+                _ => Err(RuntimeError::WrongArgumentType(result.clone())),
+            }
+        }
+    }
+}
+
+fn equivalent(
+    equivalence: &Equivalence,
+    a: &RcValue,
+    b: &RcValue,
+    vm: &mut VM,
+) -> Result<bool, RuntimeError> {
+    match *equivalence {
+        Equivalence::Eq => Ok(values::eq(a, b)),
+        Equivalence::Eqv => Ok(values::eqv(a, b)),
+        Equivalence::Custom { ref equiv, .. } => {
+            let result = vm.call(equiv.clone(), vec![a.clone(), b.clone()])?;
+            Ok(!result.is_false())
+        }
+    }
+}
+
+/// Finds `key` in `table`, returning its hash and, if present, the index of
+/// its entry. The table is not borrowed while Scheme procedures run, so they
+/// are free to use it; if they add or remove entries, the lookup starts over,
+/// since the index found may no longer be that of the key.
+fn lookup(
+    table: &RefCell<Hashtable>,
+    key: &RcValue,
+    vm: &mut VM,
+) -> Result<(u64, Option<usize>), RuntimeError> {
+    loop {
+        let (equivalence, generation) = {
+            let table = table.borrow();
+            (table.equivalence.clone(), table.generation())
+        };
+        let hash = hash_key(&equivalence, key, vm)?;
+        let candidates = table.borrow().candidates(hash);
+
+        let mut found = None;
+        for (index, candidate) in candidates {
+            if equivalent(&equivalence, &candidate, key, vm)? {
+                found = Some(index);
+                break;
+            }
+        }
+
+        if table.borrow().generation() == generation {
+            return Ok((hash, found));
+        }
+    }
+}
+
+fn make_hashtable(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    equivalence: Equivalence,
+) -> Result<RcValue, RuntimeError> {
+    check_arity(name, args, 0, 1)?;
+
+    Ok(vm.memory.hashtable(Hashtable::new(equivalence)))
+}
+
+fn make_custom_hashtable(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("make-hashtable", args, 2, 3)?;
+
+    let equivalence = Equivalence::Custom {
+        hash: procedure_arg(&args[0])?,
+        equiv: procedure_arg(&args[1])?,
+    };
+
+    Ok(vm.memory.hashtable(Hashtable::new(equivalence)))
+}
+
+fn is_hashtable(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::Hashtable(..))))
+}
+
+fn hashtable_size(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-size", args, 1, 1)?;
+
+    let len = table_arg(&args[0])?.borrow().len();
+    Ok(vm.memory.integer(len as i64))
+}
+
+fn hashtable_ref(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-ref", args, 3, 3)?;
+
+    let table = table_arg(&args[0])?;
+
+    match lookup(table, &args[1], vm)? {
+        (_, Some(index)) => Ok(table.borrow().value(index)),
+        (_, None) => Ok(args[2].clone()),
+    }
+}
+
+fn hashtable_set(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-set!", args, 3, 3)?;
+
+    let table = mutable_table_arg(&args[0])?;
+
+    match lookup(table, &args[1], vm)? {
+        (_, Some(index)) => table.borrow_mut().set_value(index, args[2].clone()),
+        (hash, None) => {
+            table
+                .borrow_mut()
+                .insert(hash, args[1].clone(), args[2].clone())
+        }
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn hashtable_update(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-update!", args, 4, 4)?;
+
+    let table = mutable_table_arg(&args[0])?;
+    let procedure = procedure_arg(&args[2])?;
+
+    let current = match lookup(table, &args[1], vm)? {
+        (_, Some(index)) => table.borrow().value(index),
+        (_, None) => args[3].clone(),
+    };
+    let updated = vm.call(procedure, vec![current])?;
+
+    match lookup(table, &args[1], vm)? {
+        (_, Some(index)) => table.borrow_mut().set_value(index, updated),
+        (hash, None) => table.borrow_mut().insert(hash, args[1].clone(), updated),
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn hashtable_delete(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-delete!", args, 2, 2)?;
+
+    let table = mutable_table_arg(&args[0])?;
+
+    if let (_, Some(index)) = lookup(table, &args[1], vm)? {
+        table.borrow_mut().remove(index);
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn hashtable_contains(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-contains?", args, 2, 2)?;
+
+    let table = table_arg(&args[0])?;
+    let (_, index) = lookup(table, &args[1], vm)?;
+
+    Ok(vm.memory.boolean(index.is_some()))
+}
+
+fn hashtable_clear(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-clear!", args, 1, 2)?;
+
+    mutable_table_arg(&args[0])?.borrow_mut().clear();
+
+    Ok(vm.memory.unspecified())
+}
+
+fn hashtable_keys(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-keys", args, 1, 1)?;
+
+    let keys = table_arg(&args[0])?
+        .borrow()
+        .entries()
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    Ok(vm.memory.vector(keys))
+}
+
+/// Returns the keys and the values as two vectors.
+fn hashtable_entries(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-entries", args, 1, 1)?;
+
+    let (keys, values) = table_arg(&args[0])?
+        .borrow()
+        .entries()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .unzip();
+
+    let keys = vm.memory.vector(keys);
+    let values = vm.memory.vector(values);
+
+    Ok(vm.memory.values(vec![keys, values]))
+}
+
+fn hashtable_copy(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-copy", args, 1, 2)?;
+
+    let mut copy = table_arg(&args[0])?.borrow().clone();
+    copy.mutable = args.get(1).is_some_and(|mutable| !mutable.is_false());
+
+    Ok(vm.memory.hashtable(copy))
+}
+
+fn hashtable_is_mutable(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("hashtable-mutable?", args, 1, 1)?;
+
+    let mutable = table_arg(&args[0])?.borrow().mutable;
+    Ok(vm.memory.boolean(mutable))
+}
+
+fn equal_hash(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("equal-hash", args, 1, 1)?;
+
+    Ok(hash_integer(equal_hash_of(&args[0], 8), vm))
+}
+
+fn string_hash(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-hash", args, 1, 1)?;
+
+    match *args[0] {
+        Value::Str { ref chars, .. } => {
+            let hash = hash_of(&*chars.borrow());
+            Ok(hash_integer(hash, vm))
+        }
+        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn symbol_hash(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("symbol-hash", args, 1, 1)?;
+
+    match *args[0] {
+        Value::Atom(ref name) => Ok(hash_integer(hash_of(name), vm)),
+        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    errors::RuntimeError,
+    instructions::Instruction::Unbind,
+    parameters::Parameter,
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 2] = ["make-parameter", "%parameterize"];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "make-parameter" => make_parameter(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// Applies a parameter object, which returns its current value.
+pub fn apply_parameter(
+    parameter: &Parameter,
+    args: &[RcValue],
+) -> Result<RcValue, RuntimeError> {
+    if !args.is_empty() {
+        return Err(RuntimeError::BadArity(None));
+    }
+
+    Ok(parameter.get())
+}
+
+fn parameter_arg(value: &RcValue) -> Result<&Parameter, RuntimeError> {
+    match **value {
+        Value::Parameter(ref parameter) => Ok(parameter),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn convert(
+    parameter: &Parameter,
+    value: RcValue,
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match parameter.converter {
+        Some(ref converter) => vm.call(converter.clone(), vec![value]),
+        None => Ok(value),
+    }
+}
+
+fn make_parameter(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("make-parameter", args, 1, 2)?;
+
+    let converter = match args.get(1) {
+        Some(converter) if converter.is_procedure() => Some(converter.clone()),
+        Some(other) => {
+            return Err(RuntimeError::WrongArgumentType(other.clone()))
+        }
+        None => None,
+    };
+
+    let value = match converter {
+        Some(ref converter) => {
+            vm.call(converter.clone(), vec![args[0].clone()])?
+        }
+        None => args[0].clone(),
+    };
+
+    Ok(vm.memory.parameter(value, converter))
+}
+
+/// `(%parameterize (list parameter ...) (list value ...) thunk)`, which
+/// `parameterize` compiles to. The converted values are bound for the
+/// duration of the thunk; the previous ones are restored however it exits.
+pub fn parameterize(
+    args: &[RcValue],
+    vm: &mut VM,
+    tail: bool,
+) -> Result<(), RuntimeError> {
+    check_arity("parameterize", args, 3, 3)?;
+
+    let parameters = list_arg(&args[0])?;
+    let values = list_arg(&args[1])?;
+
+    let mut converted = Vec::with_capacity(values.len());
+    for (parameter, value) in parameters.iter().zip(values) {
+        converted.push(convert(parameter_arg(parameter)?, value, vm)?);
+    }
+
+    let depth = vm.parameterized.len();
+    for (parameter, value) in parameters.iter().zip(converted) {
+        vm.bind_parameter(parameter, value);
+    }
+
+    vm.call_then(args[2].clone(), vec![], vec![Unbind(depth)], vec![], tail)
+}
+
+fn list_arg(value: &RcValue) -> Result<Vec<RcValue>, RuntimeError> {
+    value
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(value.clone()))
+}
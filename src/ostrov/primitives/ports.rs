@@ -0,0 +1,129 @@
+use crate::{
+    errors::RuntimeError,
+    ports::Port,
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::cell::RefCell;
+
+use super::check_arity;
+
+pub static PRIMITIVES: [&str; 7] = [
+    "display",
+    "newline",
+    "port?",
+    "input-port?",
+    "output-port?",
+    "open-output-string",
+    "get-output-string",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "display" => display(args, vm),
+        "newline" => newline(args, vm),
+        "port?" => is_port(name, args, vm, |_| true),
+        "input-port?" => is_port(name, args, vm, Port::is_input),
+        "output-port?" => is_port(name, args, vm, Port::is_output),
+        "open-output-string" => open_output_string(args, vm),
+        "get-output-string" => get_output_string(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// The port at `args[index]`, or the value of `current-output-port`.
+fn output_port(
+    args: &[RcValue],
+    index: usize,
+    vm: &VM,
+) -> Result<RcValue, RuntimeError> {
+    let port = match args.get(index) {
+        Some(port) => port.clone(),
+        None => vm.current_output_port(),
+    };
+
+    match *port {
+        Value::Port(ref p) if p.is_output() => Ok(port.clone()),
+        _ => Err(RuntimeError::WrongArgumentType(port.clone())),
+    }
+}
+
+fn write(port: &RcValue, text: &str) {
+    if let Value::Port(ref port) = **port {
+        port.write(text);
+    }
+}
+
+fn display(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("display", args, 1, 2)?;
+
+    let port = output_port(args, 1, vm)?;
+
+    match *args[0] {
+        Value::Char(c) => write(&port, &c.to_string()),
+        Value::Str { ref chars, .. } => {
+            write(&port, &chars.borrow().iter().collect::<String>())
+        }
+        ref value => write(&port, &value.to_string()),
+    }
+
+    Ok(vm.memory.b_true())
+}
+
+fn newline(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("newline", args, 0, 1)?;
+
+    let port = output_port(args, 0, vm)?;
+    write(&port, "\n");
+
+    Ok(vm.memory.b_true())
+}
+
+fn is_port<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    predicate: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&Port) -> bool,
+{
+    check_arity(name, args, 1, 1)?;
+
+    let is_port = match *args[0] {
+        Value::Port(ref port) => predicate(port),
+        _ => false,
+    };
+
+    Ok(vm.memory.boolean(is_port))
+}
+
+fn open_output_string(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("open-output-string", args, 0, 0)?;
+
+    Ok(vm
+        .memory
+        .port(Port::StringOutput(RefCell::new(String::new()))))
+}
+
+fn get_output_string(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("get-output-string", args, 1, 1)?;
+
+    match *args[0] {
+        Value::Port(Port::StringOutput(ref buffer)) => {
+            let chars = buffer.borrow().chars().collect();
+            Ok(vm.memory.string(chars))
+        }
+        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
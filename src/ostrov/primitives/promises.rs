@@ -0,0 +1,403 @@
+use crate::{
+    errors::RuntimeError,
+    instructions::Instruction::Resolve,
+    promises::{Pending, PromiseState},
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::{check_arity, index_arg};
+
+pub static PRIMITIVES: [&str; 14] = [
+    "force",
+    "make-promise",
+    "promise?",
+    "stream?",
+    "stream-null?",
+    "stream-pair?",
+    "stream-car",
+    "stream-cdr",
+    "stream-take",
+    "stream-map",
+    "stream-filter",
+    "stream->list",
+    "list->stream",
+    "stream",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "force" => force_primitive(args, vm),
+        "make-promise" => make_promise(args, vm),
+        "promise?" | "stream?" => is_promise(name, args, vm),
+        "stream-null?" => is_stream_null(args, vm),
+        "stream-pair?" => is_stream_pair(args, vm),
+        "stream-car" => stream_car(args, vm),
+        "stream-cdr" => stream_cdr(args, vm),
+        "stream-take" => stream_take(args, vm),
+        "stream-map" => stream_map(args, vm),
+        "stream-filter" => stream_filter(args, vm),
+        "stream->list" => stream_to_list(args, vm),
+        "list->stream" => list_to_stream(args, vm),
+        "stream" => stream(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// Forces `value` if it is a promise, returning it unchanged otherwise.
+/// Chains of `delay-force` are followed in a loop, so that they run in
+/// constant space.
+pub fn force(value: &RcValue, vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let cell = match **value {
+        Value::Promise(ref cell) => cell,
+        _ => return Ok(value.clone()),
+    };
+
+    loop {
+        let state = cell.borrow().clone();
+        let pending = match *state.borrow() {
+            PromiseState::Forced(ref value) => return Ok(value.clone()),
+            PromiseState::Pending(ref pending) => pending.clone(),
+        };
+
+        let result = run(pending, vm)?;
+        settle(value, result);
+    }
+}
+
+/// Forces the promise `args` holds in place of the `force` being applied:
+/// the thunk of a `delay` or a `delay-force` runs on the VM stack, after which
+/// `Resolve` settles the promise with its value. Returns `None` if there is no
+/// such thunk to run, for `force` to be applied as usual.
+pub fn force_in_place(
+    args: &[RcValue],
+    vm: &mut VM,
+    tail: bool,
+) -> Option<Result<(), RuntimeError>> {
+    let promise = match args {
+        [promise] => promise,
+        _ => return None,
+    };
+    let cell = match **promise {
+        Value::Promise(ref cell) => cell,
+        _ => return None,
+    };
+
+    let thunk = match *cell.borrow().borrow() {
+        PromiseState::Pending(Pending::Delay(ref thunk))
+        | PromiseState::Pending(Pending::DelayForce(ref thunk)) => thunk.clone(),
+        _ => return None,
+    };
+
+    Some(vm.call_then(thunk, vec![], vec![Resolve], vec![promise.clone()], tail))
+}
+
+/// Settles `promise` with `value`, what running its pending computation
+/// produced. The promise of a `delay-force` takes the state of the promise
+/// its thunk returned, which then shares it. If forcing the promise forced it
+/// already, the first value sticks.
+pub fn settle(promise: &RcValue, value: RcValue) {
+    let cell = match **promise {
+        Value::Promise(ref cell) => cell,
+        _ => return,
+    };
+
+    let state = cell.borrow().clone();
+    let chains = match *state.borrow() {
+        PromiseState::Forced(..) => return,
+        PromiseState::Pending(ref pending) => {
+            matches!(*pending, Pending::DelayForce(..))
+        }
+    };
+
+    match *value {
+        Value::Promise(ref other) if chains => {
+            let other_state = other.borrow().borrow().clone();
+            *state.borrow_mut() = other_state;
+            *other.borrow_mut() = state.clone();
+        }
+        _ => *state.borrow_mut() = PromiseState::Forced(value),
+    }
+}
+
+fn run(pending: Pending, vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    match pending {
+        Pending::Delay(thunk) | Pending::DelayForce(thunk) => {
+            vm.call(thunk, vec![])
+        }
+        Pending::Apply(procedure, promises) => {
+            let args = promises
+                .iter()
+                .map(|promise| force(promise, vm))
+                .collect::<Result<_, _>>()?;
+
+            vm.call(procedure, args)
+        }
+        Pending::Take(0, _) => Ok(vm.memory.nil()),
+        Pending::Take(n, stream) => match stream_next(&stream, vm)? {
+            Some((car, cdr)) => {
+                let rest = lazy(Pending::Take(n - 1, cdr), vm);
+                Ok(vm.memory.pair(car, rest))
+            }
+            None => Ok(vm.memory.nil()),
+        },
+        Pending::Map(procedure, streams) => {
+            let mut cars = Vec::with_capacity(streams.len());
+            let mut cdrs = Vec::with_capacity(streams.len());
+
+            for stream in &streams {
+                match stream_next(stream, vm)? {
+                    Some((car, cdr)) => {
+                        cars.push(car);
+                        cdrs.push(cdr);
+                    }
+                    None => return Ok(vm.memory.nil()),
+                }
+            }
+
+            let car = lazy(Pending::Apply(procedure.clone(), cars), vm);
+            let rest = lazy(Pending::Map(procedure, cdrs), vm);
+            Ok(vm.memory.pair(car, rest))
+        }
+        Pending::Filter(predicate, mut stream) => loop {
+            match stream_next(&stream, vm)? {
+                Some((car, cdr)) => {
+                    let element = force(&car, vm)?;
+
+                    if !vm.call(predicate.clone(), vec![element])?.is_false() {
+                        let rest = lazy(Pending::Filter(predicate, cdr), vm);
+                        return Ok(vm.memory.pair(car, rest));
+                    }
+
+                    stream = cdr;
+                }
+                None => return Ok(vm.memory.nil()),
+            }
+        },
+    }
+}
+
+fn lazy(pending: Pending, vm: &mut VM) -> RcValue {
+    vm.memory.promise(PromiseState::Pending(pending))
+}
+
+fn eager(value: RcValue, vm: &mut VM) -> RcValue {
+    vm.memory.promise(PromiseState::Forced(value))
+}
+
+/// Forces a stream, returning the promise of its first element and the rest
+/// of the stream, or `None` if it is empty.
+fn stream_next(
+    stream: &RcValue,
+    vm: &mut VM,
+) -> Result<Option<(RcValue, RcValue)>, RuntimeError> {
+    if !stream.is_promise() {
+        return Err(RuntimeError::WrongArgumentType(stream.clone()));
+    }
+
+    let forced = force(stream, vm)?;
+
+    match *forced {
+        Value::Nil => Ok(None),
+        Value::Pair { .. } => {
+            Ok(Some((forced.car().unwrap(), forced.cdr().unwrap())))
+        }
+        _ => Err(RuntimeError::WrongArgumentType(stream.clone())),
+    }
+}
+
+fn stream_arg(value: &RcValue) -> Result<RcValue, RuntimeError> {
+    if value.is_promise() {
+        Ok(value.clone())
+    } else {
+        Err(RuntimeError::WrongArgumentType(value.clone()))
+    }
+}
+
+fn procedure_arg(value: &RcValue) -> Result<RcValue, RuntimeError> {
+    if value.is_procedure() {
+        Ok(value.clone())
+    } else {
+        Err(RuntimeError::WrongArgumentType(value.clone()))
+    }
+}
+
+fn force_primitive(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("force", args, 1, 1)?;
+
+    force(&args[0], vm)
+}
+
+fn make_promise(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("make-promise", args, 1, 1)?;
+
+    if args[0].is_promise() {
+        Ok(args[0].clone())
+    } else {
+        Ok(eager(args[0].clone(), vm))
+    }
+}
+
+fn is_promise(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity(name, args, 1, 1)?;
+
+    Ok(vm.memory.boolean(args[0].is_promise()))
+}
+
+fn is_stream_null(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-null?", args, 1, 1)?;
+
+    let is_null = args[0].is_promise() && stream_next(&args[0], vm)?.is_none();
+    Ok(vm.memory.boolean(is_null))
+}
+
+fn is_stream_pair(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-pair?", args, 1, 1)?;
+
+    let is_pair = args[0].is_promise() && stream_next(&args[0], vm)?.is_some();
+    Ok(vm.memory.boolean(is_pair))
+}
+
+fn stream_car(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-car", args, 1, 1)?;
+
+    match stream_next(&args[0], vm)? {
+        Some((car, _)) => force(&car, vm),
+        None => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn stream_cdr(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-cdr", args, 1, 1)?;
+
+    match stream_next(&args[0], vm)? {
+        Some((_, cdr)) => Ok(cdr),
+        None => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn stream_take(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-take", args, 2, 2)?;
+
+    let n = index_arg(&args[0])?;
+    let stream = stream_arg(&args[1])?;
+
+    Ok(lazy(Pending::Take(n, stream), vm))
+}
+
+fn stream_map(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("stream-map".into())));
+    }
+
+    let procedure = procedure_arg(&args[0])?;
+    let streams = args[1..].iter().map(stream_arg).collect::<Result<_, _>>()?;
+
+    Ok(lazy(Pending::Map(procedure, streams), vm))
+}
+
+fn stream_filter(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("stream-filter", args, 2, 2)?;
+
+    let predicate = procedure_arg(&args[0])?;
+    let stream = stream_arg(&args[1])?;
+
+    Ok(lazy(Pending::Filter(predicate, stream), vm))
+}
+
+fn stream_to_list(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("stream->list", args, 1, 2)?;
+
+    let (mut stream, mut limit) = match args.len() {
+        1 => (stream_arg(&args[0])?, None),
+        _ => (stream_arg(&args[1])?, Some(index_arg(&args[0])?)),
+    };
+    let mut items = vec![];
+
+    while limit != Some(0) {
+        match stream_next(&stream, vm)? {
+            Some((car, cdr)) => {
+                items.push(force(&car, vm)?);
+                stream = cdr;
+                limit = limit.map(|n| n - 1);
+            }
+            None => break,
+        }
+    }
+
+    Ok(vm.memory.list(items))
+}
+
+fn list_to_stream(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("list->stream", args, 1, 1)?;
+
+    let items = args[0]
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[0].clone()))?;
+
+    Ok(items_to_stream(items, vm))
+}
+
+fn stream(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    Ok(items_to_stream(args.to_vec(), vm))
+}
+
+fn items_to_stream(items: Vec<RcValue>, vm: &mut VM) -> RcValue {
+    let nil = vm.memory.nil();
+    let empty = eager(nil, vm);
+
+    items.into_iter().rev().fold(empty, |rest, item| {
+        let car = eager(item, vm);
+        let pair = vm.memory.pair(car, rest);
+        eager(pair, vm)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{promises::PromiseState, vm::VM};
+    use std::rc::Rc;
+
+    #[test]
+    fn force_returns_non_promises_unchanged() {
+        let mut vm = VM::new();
+        let value = vm.memory.integer(1);
+
+        assert!(Rc::ptr_eq(&value, &super::force(&value, &mut vm).unwrap()));
+    }
+
+    #[test]
+    fn force_forced_promise() {
+        let mut vm = VM::new();
+        let value = vm.memory.integer(1);
+        let promise = vm.memory.promise(PromiseState::Forced(value.clone()));
+
+        assert!(Rc::ptr_eq(
+            &value,
+            &super::force(&promise, &mut vm).unwrap()
+        ));
+    }
+}
@@ -0,0 +1,579 @@
+use crate::{
+    errors::RuntimeError,
+    records::{ConstructorDescriptor, Field, RecordProcedure, RecordType},
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::{cell::RefCell, rc::Rc};
+
+use super::{check_arity, conditions};
+
+pub static PRIMITIVES: [&str; 19] = [
+    "make-record-type-descriptor",
+    "record-type-descriptor?",
+    "make-record-constructor-descriptor",
+    "record-constructor",
+    "record-predicate",
+    "record-accessor",
+    "record-mutator",
+    "record?",
+    "record-rtd",
+    "record-type-name",
+    "record-type-parent",
+    "record-type-uid",
+    "record-type-generative?",
+    "record-type-sealed?",
+    "record-type-opaque?",
+    "record-type-field-names",
+    "record-field-mutable?",
+    "record-type-descriptor",
+    "record-constructor-descriptor",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "make-record-type-descriptor" => make_record_type_descriptor(args, vm),
+        "record-type-descriptor?" => is_record_type_descriptor(args, vm),
+        "make-record-constructor-descriptor" => {
+            make_record_constructor_descriptor(args, vm)
+        }
+        "record-constructor" => record_constructor(args, vm),
+        "record-predicate" => record_predicate(args, vm),
+        "record-accessor" => record_accessor(args, vm),
+        "record-mutator" => record_mutator(args, vm),
+        "record?" => is_record(args, vm),
+        "record-rtd" => record_rtd(args, vm),
+        "record-type-name" => record_type_name(args, vm),
+        "record-type-parent" => record_type_parent(args, vm),
+        "record-type-uid" => record_type_uid(args, vm),
+        "record-type-generative?" => {
+            rtd_property(name, args, vm, |rtd| rtd.uid.is_none())
+        }
+        "record-type-sealed?" => rtd_property(name, args, vm, |rtd| rtd.sealed),
+        "record-type-opaque?" => {
+            rtd_property(name, args, vm, |rtd| rtd.is_opaque())
+        }
+        "record-type-field-names" => record_type_field_names(args, vm),
+        "record-field-mutable?" => is_record_field_mutable(args, vm),
+        "record-type-descriptor" => record_type_descriptor(args, vm),
+        "record-constructor-descriptor" => {
+            record_constructor_descriptor(args, vm)
+        }
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+/// Applies one of the procedures generated by the records system.
+pub fn apply_procedure(
+    procedure: &RecordProcedure,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match *procedure {
+        RecordProcedure::Constructor {
+            ref rtd,
+            arity,
+            ref collected,
+        } => {
+            if args.len() != arity {
+                return Err(RuntimeError::BadArity(Some(procedure.name())));
+            }
+
+            let mut fields = args.to_vec();
+            fields.extend(collected.iter().cloned());
+
+            Ok(vm.memory.record(rtd.clone(), fields))
+        }
+        RecordProcedure::ParentInitializer {
+            ref descriptor,
+            ref rtd,
+            ref collected,
+        } => Ok(vm
+            .memory
+            .record_procedure(RecordProcedure::ChildInitializer {
+                descriptor: descriptor.clone(),
+                rtd: rtd.clone(),
+                collected: collected.clone(),
+                parent_args: args.to_vec(),
+            })),
+        RecordProcedure::ChildInitializer {
+            ref descriptor,
+            ref rtd,
+            ref collected,
+            ref parent_args,
+        } => {
+            if args.len() != descriptor.rtd.fields.len() {
+                return Err(RuntimeError::BadArity(Some(procedure.name())));
+            }
+
+            let parent = parent_descriptor(descriptor);
+            let mut fields = args.to_vec();
+            fields.extend(collected.iter().cloned());
+
+            let constructor = constructor(&parent, rtd, fields, vm)?;
+            vm.call(constructor, parent_args.clone())
+        }
+        RecordProcedure::Predicate(ref rtd) => {
+            check_arity(&procedure.name(), args, 1, 1)?;
+
+            let is_instance = match *args[0] {
+                Value::Record { rtd: ref other, .. } => other.is_subtype_of(rtd),
+                _ => false,
+            };
+
+            Ok(vm.memory.boolean(is_instance))
+        }
+        RecordProcedure::Accessor(ref rtd, index) => {
+            check_arity(&procedure.name(), args, 1, 1)?;
+
+            let value = instance_fields(&args[0], rtd)?.borrow()[index].clone();
+            Ok(value)
+        }
+        RecordProcedure::Mutator(ref rtd, index) => {
+            check_arity(&procedure.name(), args, 2, 2)?;
+
+            instance_fields(&args[0], rtd)?.borrow_mut()[index] = args[1].clone();
+            Ok(vm.memory.unspecified())
+        }
+        RecordProcedure::ConditionPredicate(ref rtd) => {
+            check_arity(&procedure.name(), args, 1, 1)?;
+
+            let component = conditions::component(&args[0], rtd);
+            Ok(vm.memory.boolean(component.is_some()))
+        }
+        RecordProcedure::ConditionAccessor(ref rtd, ref accessor) => {
+            check_arity(&procedure.name(), args, 1, 1)?;
+
+            match conditions::component(&args[0], rtd) {
+                Some(component) => vm.call(accessor.clone(), vec![component]),
+                None => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+            }
+        }
+    }
+}
+
+/// Builds the procedure that makes records of `rtd` through `descriptor`,
+/// running its protocol if it has one.
+fn constructor(
+    descriptor: &Rc<ConstructorDescriptor>,
+    rtd: &Rc<RecordType>,
+    collected: Vec<RcValue>,
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    let default = RecordProcedure::Constructor {
+        rtd: rtd.clone(),
+        arity: descriptor.rtd.field_count(),
+        collected: collected.clone(),
+    };
+
+    match descriptor.protocol {
+        None => Ok(vm.memory.record_procedure(default)),
+        Some(ref protocol) => {
+            let maker = if descriptor.rtd.parent.is_some() {
+                RecordProcedure::ParentInitializer {
+                    descriptor: descriptor.clone(),
+                    rtd: rtd.clone(),
+                    collected,
+                }
+            } else {
+                default
+            };
+            let maker = vm.memory.record_procedure(maker);
+
+            vm.call(protocol.clone(), vec![maker])
+        }
+    }
+}
+
+fn parent_descriptor(
+    descriptor: &ConstructorDescriptor,
+) -> Rc<ConstructorDescriptor> {
+    descriptor.parent.clone().unwrap_or_else(|| {
+        let parent = descriptor.rtd.parent.clone().unwrap();
+        Rc::new(ConstructorDescriptor::default_for(parent))
+    })
+}
+
+fn instance_fields<'a>(
+    value: &'a RcValue,
+    rtd: &RecordType,
+) -> Result<&'a RefCell<Vec<RcValue>>, RuntimeError> {
+    match **value {
+        Value::Record {
+            rtd: ref other,
+            ref fields,
+        } if other.is_subtype_of(rtd) => Ok(fields),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+/// Record type descriptor of `value`, which may also be a constructor
+/// descriptor, as `define-record-type` binds record names to those.
+fn rtd_arg(value: &RcValue) -> Result<Rc<RecordType>, RuntimeError> {
+    match **value {
+        Value::RecordTypeDescriptor(ref rtd) => Ok(rtd.clone()),
+        Value::RecordConstructorDescriptor(ref descriptor) => {
+            Ok(descriptor.rtd.clone())
+        }
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn descriptor_arg(
+    value: &RcValue,
+) -> Result<Rc<ConstructorDescriptor>, RuntimeError> {
+    match **value {
+        Value::RecordConstructorDescriptor(ref descriptor) => {
+            Ok(descriptor.clone())
+        }
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn symbol_arg(value: &RcValue) -> Result<String, RuntimeError> {
+    match **value {
+        Value::Atom(ref name) => Ok(name.clone()),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn field_index_arg(
+    value: &RcValue,
+    rtd: &RecordType,
+) -> Result<usize, RuntimeError> {
+    match **value {
+        Value::Integer(n) if n >= 0 && (n as usize) < rtd.fields.len() => {
+            Ok(n as usize)
+        }
+        Value::Integer(..) => Err(RuntimeError::IndexOutOfRange(value.clone())),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+/// Parses a vector of `(mutable name)` and `(immutable name)` specs.
+fn fields_arg(value: &RcValue) -> Result<Vec<Field>, RuntimeError> {
+    let specs = match **value {
+        Value::Vector { ref items, .. } => items.borrow().clone(),
+        _ => return Err(RuntimeError::WrongArgumentType(value.clone())),
+    };
+
+    specs
+        .iter()
+        .map(|spec| {
+            let malformed = || RuntimeError::WrongArgumentType(spec.clone());
+            let parts = spec.list_to_vec().ok_or_else(malformed)?;
+
+            match parts.as_slice() {
+                [kind, name] => {
+                    let mutable = match **kind {
+                        Value::Atom(ref kind) if kind == "mutable" => true,
+                        Value::Atom(ref kind) if kind == "immutable" => false,
+                        _ => return Err(malformed()),
+                    };
+
+                    Ok(Field {
+                        name: symbol_arg(name)?,
+                        mutable,
+                    })
+                }
+                _ => Err(malformed()),
+            }
+        })
+        .collect()
+}
+
+fn same_parent(a: &Option<Rc<RecordType>>, b: &Option<Rc<RecordType>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn make_record_type_descriptor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("make-record-type-descriptor", args, 6, 6)?;
+
+    let name = symbol_arg(&args[0])?;
+    let parent = if args[1].is_false() {
+        None
+    } else {
+        let parent = rtd_arg(&args[1])?;
+        if parent.sealed {
+            return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+        }
+        Some(parent)
+    };
+    let uid = if args[2].is_false() {
+        None
+    } else {
+        Some(symbol_arg(&args[2])?)
+    };
+
+    let rtd = RecordType {
+        name,
+        parent,
+        uid,
+        sealed: !args[3].is_false(),
+        opaque: !args[4].is_false(),
+        fields: fields_arg(&args[5])?,
+    };
+
+    if let Some(ref uid) = rtd.uid {
+        if let Some(existing) = vm.memory.nongenerative_record_type(uid) {
+            let compatible = same_parent(&existing.parent, &rtd.parent)
+                && existing.sealed == rtd.sealed
+                && existing.opaque == rtd.opaque
+                && existing.fields == rtd.fields;
+
+            if !compatible {
+                return Err(RuntimeError::WrongArgumentType(args[2].clone()));
+            }
+
+            return Ok(vm.memory.record_type_descriptor(existing));
+        }
+    }
+
+    let rtd = Rc::new(rtd);
+    if let Some(ref uid) = rtd.uid {
+        vm.memory.register_record_type(uid.clone(), rtd.clone());
+    }
+
+    Ok(vm.memory.record_type_descriptor(rtd))
+}
+
+fn is_record_type_descriptor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-descriptor?", args, 1, 1)?;
+
+    let is_rtd = matches!(*args[0], Value::RecordTypeDescriptor(..));
+    Ok(vm.memory.boolean(is_rtd))
+}
+
+fn make_record_constructor_descriptor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("make-record-constructor-descriptor", args, 3, 3)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    let parent = if args[1].is_false() {
+        None
+    } else {
+        let parent = descriptor_arg(&args[1])?;
+        let is_parent = rtd
+            .parent
+            .as_ref()
+            .is_some_and(|rtd_parent| Rc::ptr_eq(rtd_parent, &parent.rtd));
+
+        if !is_parent {
+            return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+        }
+        Some(parent)
+    };
+    let protocol = if args[2].is_false() {
+        None
+    } else if args[2].is_procedure() {
+        Some(args[2].clone())
+    } else {
+        return Err(RuntimeError::WrongArgumentType(args[2].clone()));
+    };
+
+    // The default protocol passes all field values straight through, which
+    // only works if the parent's constructor does the same.
+    let parent_has_protocol = parent
+        .as_ref()
+        .is_some_and(|parent| parent.protocol.is_some());
+    if protocol.is_none() && parent_has_protocol {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    let descriptor = ConstructorDescriptor {
+        rtd,
+        parent,
+        protocol,
+    };
+
+    Ok(vm.memory.record_constructor_descriptor(Rc::new(descriptor)))
+}
+
+fn record_constructor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-constructor", args, 1, 1)?;
+
+    let descriptor = descriptor_arg(&args[0])?;
+    let rtd = descriptor.rtd.clone();
+
+    constructor(&descriptor, &rtd, vec![], vm)
+}
+
+fn record_predicate(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-predicate", args, 1, 1)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    Ok(vm.memory.record_procedure(RecordProcedure::Predicate(rtd)))
+}
+
+fn record_accessor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-accessor", args, 2, 2)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    let index = rtd.parent_field_count() + field_index_arg(&args[1], &rtd)?;
+
+    Ok(vm
+        .memory
+        .record_procedure(RecordProcedure::Accessor(rtd, index)))
+}
+
+fn record_mutator(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-mutator", args, 2, 2)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    let k = field_index_arg(&args[1], &rtd)?;
+
+    if !rtd.fields[k].mutable {
+        return Err(RuntimeError::WrongArgumentType(args[1].clone()));
+    }
+
+    let index = rtd.parent_field_count() + k;
+    Ok(vm
+        .memory
+        .record_procedure(RecordProcedure::Mutator(rtd, index)))
+}
+
+fn is_record(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("record?", args, 1, 1)?;
+
+    let is_record = match *args[0] {
+        Value::Record { ref rtd, .. } => !rtd.is_opaque(),
+        _ => false,
+    };
+
+    Ok(vm.memory.boolean(is_record))
+}
+
+fn record_rtd(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("record-rtd", args, 1, 1)?;
+
+    match *args[0] {
+        Value::Record { ref rtd, .. } if !rtd.is_opaque() => {
+            Ok(vm.memory.record_type_descriptor(rtd.clone()))
+        }
+        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn record_type_name(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-name", args, 1, 1)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    Ok(vm.memory.intern(rtd.name.clone()))
+}
+
+fn record_type_parent(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-parent", args, 1, 1)?;
+
+    match rtd_arg(&args[0])?.parent {
+        Some(ref parent) => Ok(vm.memory.record_type_descriptor(parent.clone())),
+        None => Ok(vm.memory.b_false()),
+    }
+}
+
+fn record_type_uid(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-uid", args, 1, 1)?;
+
+    match rtd_arg(&args[0])?.uid {
+        Some(ref uid) => Ok(vm.memory.intern(uid.clone())),
+        None => Ok(vm.memory.b_false()),
+    }
+}
+
+fn rtd_property<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    property: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&RecordType) -> bool,
+{
+    check_arity(name, args, 1, 1)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    Ok(vm.memory.boolean(property(&rtd)))
+}
+
+fn record_type_field_names(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-field-names", args, 1, 1)?;
+
+    let names = rtd_arg(&args[0])?
+        .fields
+        .iter()
+        .map(|field| vm.memory.intern(field.name.clone()))
+        .collect();
+
+    Ok(vm.memory.vector(names))
+}
+
+fn is_record_field_mutable(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-field-mutable?", args, 2, 2)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    let k = field_index_arg(&args[1], &rtd)?;
+
+    Ok(vm.memory.boolean(rtd.fields[k].mutable))
+}
+
+fn record_type_descriptor(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-type-descriptor", args, 1, 1)?;
+
+    let rtd = rtd_arg(&args[0])?;
+    Ok(vm.memory.record_type_descriptor(rtd))
+}
+
+fn record_constructor_descriptor(
+    args: &[RcValue],
+    _vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("record-constructor-descriptor", args, 1, 1)?;
+
+    descriptor_arg(&args[0])?;
+    Ok(args[0].clone())
+}
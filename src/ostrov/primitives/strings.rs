@@ -0,0 +1,837 @@
+use crate::{
+    errors::RuntimeError,
+    values::{RcValue, Value},
+    vm::VM,
+};
+use std::rc::Rc;
+
+use super::{check_arity, index_arg};
+
+pub static PRIMITIVES: [&str; 56] = [
+    "char?",
+    "char->integer",
+    "integer->char",
+    "char=?",
+    "char<?",
+    "char>?",
+    "char<=?",
+    "char>=?",
+    "char-ci=?",
+    "char-upcase",
+    "char-downcase",
+    "char-foldcase",
+    "char-alphabetic?",
+    "char-numeric?",
+    "char-whitespace?",
+    "char-upper-case?",
+    "char-lower-case?",
+    "string?",
+    "string",
+    "make-string",
+    "string-length",
+    "string-ref",
+    "string-set!",
+    "string-fill!",
+    "substring",
+    "string-append",
+    "string-copy",
+    "string->list",
+    "list->string",
+    "string->symbol",
+    "symbol->string",
+    "number->string",
+    "string->number",
+    "string=?",
+    "string<?",
+    "string>?",
+    "string<=?",
+    "string>=?",
+    "string-ci=?",
+    "string-upcase",
+    "string-downcase",
+    "string-titlecase",
+    "string-foldcase",
+    "string-for-each",
+    "string-map",
+    "string-contains",
+    "string-index",
+    "string-split",
+    "string-join",
+    "string-pad",
+    "string-pad-right",
+    "string-trim",
+    "string-trim-right",
+    "string-trim-both",
+    "symbol?",
+    "procedure?",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "char?" => is_char(args, vm),
+        "char->integer" => char_to_integer(args, vm),
+        "integer->char" => integer_to_char(args, vm),
+        "char=?" => char_cmp(name, args, vm, |a, b| a == b),
+        "char<?" => char_cmp(name, args, vm, |a, b| a < b),
+        "char>?" => char_cmp(name, args, vm, |a, b| a > b),
+        "char<=?" => char_cmp(name, args, vm, |a, b| a <= b),
+        "char>=?" => char_cmp(name, args, vm, |a, b| a >= b),
+        "char-ci=?" => {
+            char_cmp(name, args, vm, |a, b| char_foldcase(a) == char_foldcase(b))
+        }
+        "char-upcase" => char_map(name, args, vm, char_upcase),
+        "char-downcase" => char_map(name, args, vm, char_downcase),
+        "char-foldcase" => char_map(name, args, vm, char_foldcase),
+        "char-alphabetic?" => char_test(name, args, vm, char::is_alphabetic),
+        "char-numeric?" => char_test(name, args, vm, char::is_numeric),
+        "char-whitespace?" => char_test(name, args, vm, char::is_whitespace),
+        "char-upper-case?" => char_test(name, args, vm, char::is_uppercase),
+        "char-lower-case?" => char_test(name, args, vm, char::is_lowercase),
+        "string?" => is_string(args, vm),
+        "string" => string(args, vm),
+        "make-string" => make_string(args, vm),
+        "string-length" => string_length(args, vm),
+        "string-ref" => string_ref(args, vm),
+        "string-set!" => string_set(args, vm),
+        "string-fill!" => string_fill(args, vm),
+        "substring" => substring(args, vm),
+        "string-append" => string_append(args, vm),
+        "string-copy" => string_copy(args, vm),
+        "string->list" => string_to_list(args, vm),
+        "list->string" => list_to_string(args, vm),
+        "string->symbol" => string_to_symbol(args, vm),
+        "symbol->string" => symbol_to_string(args, vm),
+        "number->string" => number_to_string(args, vm),
+        "string->number" => string_to_number(args, vm),
+        "string=?" => string_cmp(name, args, vm, |a, b| a == b),
+        "string<?" => string_cmp(name, args, vm, |a, b| a < b),
+        "string>?" => string_cmp(name, args, vm, |a, b| a > b),
+        "string<=?" => string_cmp(name, args, vm, |a, b| a <= b),
+        "string>=?" => string_cmp(name, args, vm, |a, b| a >= b),
+        "string-ci=?" => string_cmp(name, args, vm, |a, b| {
+            string_foldcase(a) == string_foldcase(b)
+        }),
+        "string-upcase" => string_convert(name, args, vm, |s| {
+            s.iter()
+                .collect::<String>()
+                .to_uppercase()
+                .chars()
+                .collect()
+        }),
+        "string-downcase" => string_convert(name, args, vm, |s| {
+            s.iter()
+                .collect::<String>()
+                .to_lowercase()
+                .chars()
+                .collect()
+        }),
+        "string-titlecase" => string_convert(name, args, vm, string_titlecase),
+        "string-foldcase" => string_convert(name, args, vm, string_foldcase),
+        "string-for-each" => string_for_each(args, vm),
+        "string-map" => string_map(args, vm),
+        "string-contains" => string_contains(args, vm),
+        "string-index" => string_index(args, vm),
+        "string-split" => string_split(args, vm),
+        "string-join" => string_join(args, vm),
+        "string-pad" => string_pad(name, args, vm, Side::Left),
+        "string-pad-right" => string_pad(name, args, vm, Side::Right),
+        "string-trim" => string_trim(name, args, vm, &[Side::Left]),
+        "string-trim-right" => string_trim(name, args, vm, &[Side::Right]),
+        "string-trim-both" => {
+            string_trim(name, args, vm, &[Side::Left, Side::Right])
+        }
+        "symbol?" => is_symbol(args, vm),
+        "procedure?" => is_procedure(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// What `string-index` and the `string-trim` family look for: a character or
+/// a predicate procedure, defaulting to whitespace.
+enum CharMatcher {
+    Char(char),
+    Procedure(RcValue),
+    Whitespace,
+}
+
+impl CharMatcher {
+    fn from_arg(arg: Option<&RcValue>) -> Result<CharMatcher, RuntimeError> {
+        match arg {
+            None => Ok(CharMatcher::Whitespace),
+            Some(value) => match **value {
+                Value::Char(c) => Ok(CharMatcher::Char(c)),
+                _ if value.is_procedure() => {
+                    Ok(CharMatcher::Procedure(value.clone()))
+                }
+                _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+            },
+        }
+    }
+
+    fn matches(&self, c: char, vm: &mut VM) -> Result<bool, RuntimeError> {
+        match *self {
+            CharMatcher::Char(expected) => Ok(c == expected),
+            CharMatcher::Whitespace => Ok(c.is_whitespace()),
+            CharMatcher::Procedure(ref procedure) => {
+                let c = vm.memory.character(c);
+                let outcome = vm.call(procedure.clone(), vec![c])?;
+                Ok(!outcome.is_false())
+            }
+        }
+    }
+}
+
+fn char_arg(value: &RcValue) -> Result<char, RuntimeError> {
+    match **value {
+        Value::Char(c) => Ok(c),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn string_arg(value: &RcValue) -> Result<Vec<char>, RuntimeError> {
+    match **value {
+        Value::Str { ref chars, .. } => Ok(chars.borrow().clone()),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+/// Reads the optional `start` and `end` arguments found at `args[from..]`,
+/// defaulting to the whole of a string of length `len`.
+fn range_args(
+    args: &[RcValue],
+    from: usize,
+    len: usize,
+) -> Result<(usize, usize), RuntimeError> {
+    let start = match args.get(from) {
+        Some(value) => index_arg(value)?,
+        None => 0,
+    };
+    let end = match args.get(from + 1) {
+        Some(value) => index_arg(value)?,
+        None => len,
+    };
+
+    if end > len {
+        return Err(RuntimeError::IndexOutOfRange(args[from + 1].clone()));
+    }
+    if start > end {
+        return Err(RuntimeError::IndexOutOfRange(args[from].clone()));
+    }
+
+    Ok((start, end))
+}
+
+fn char_upcase(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(u), None) => u,
+        _ => c,
+    }
+}
+
+fn char_downcase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+fn char_foldcase(c: char) -> char {
+    match c {
+        'ς' => 'σ',
+        c => char_downcase(c),
+    }
+}
+
+fn string_foldcase(chars: &[char]) -> Vec<char> {
+    chars
+        .iter()
+        .flat_map(|&c| match c {
+            'ß' => vec!['s', 's'],
+            'ς' => vec!['σ'],
+            c => c.to_lowercase().collect(),
+        })
+        .collect()
+}
+
+fn string_titlecase(chars: &[char]) -> Vec<char> {
+    let mut in_word = false;
+    let mut result = Vec::with_capacity(chars.len());
+
+    for &c in chars {
+        if in_word {
+            result.extend(c.to_lowercase());
+        } else {
+            result.extend(c.to_uppercase());
+        }
+
+        in_word = c.is_alphabetic();
+    }
+
+    result
+}
+
+fn is_char(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("char?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::Char(..))))
+}
+
+fn char_to_integer(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("char->integer", args, 1, 1)?;
+
+    let c = char_arg(&args[0])?;
+    Ok(vm.memory.integer(i64::from(c as u32)))
+}
+
+fn integer_to_char(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("integer->char", args, 1, 1)?;
+
+    let c = match *args[0] {
+        Value::Integer(n) if n >= 0 => std::char::from_u32(n as u32),
+        _ => None,
+    };
+
+    match c {
+        Some(c) => Ok(vm.memory.character(c)),
+        None => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn char_cmp<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    cmp: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(char, char) -> bool,
+{
+    check_arity(name, args, 1, usize::MAX)?;
+
+    let chars = args.iter().map(char_arg).collect::<Result<Vec<_>, _>>()?;
+    let outcome = chars.windows(2).all(|w| cmp(w[0], w[1]));
+
+    Ok(vm.memory.boolean(outcome))
+}
+
+fn char_map<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    f: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(char) -> char,
+{
+    check_arity(name, args, 1, 1)?;
+
+    let c = char_arg(&args[0])?;
+    Ok(vm.memory.character(f(c)))
+}
+
+fn char_test<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    test: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(char) -> bool,
+{
+    check_arity(name, args, 1, 1)?;
+
+    let c = char_arg(&args[0])?;
+    Ok(vm.memory.boolean(test(c)))
+}
+
+fn is_string(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::Str { .. })))
+}
+
+fn string(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let chars = args.iter().map(char_arg).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(vm.memory.string(chars))
+}
+
+fn make_string(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("make-string", args, 1, 2)?;
+
+    let len = index_arg(&args[0])?;
+    let fill = match args.get(1) {
+        Some(value) => char_arg(value)?,
+        None => ' ',
+    };
+
+    Ok(vm.memory.string(vec![fill; len]))
+}
+
+fn string_length(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-length", args, 1, 1)?;
+
+    let chars = string_arg(&args[0])?;
+    Ok(vm.memory.integer(chars.len() as i64))
+}
+
+fn string_ref(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-ref", args, 2, 2)?;
+
+    let chars = string_arg(&args[0])?;
+    let k = index_arg(&args[1])?;
+
+    match chars.get(k) {
+        Some(&c) => Ok(vm.memory.character(c)),
+        None => Err(RuntimeError::IndexOutOfRange(args[1].clone())),
+    }
+}
+
+fn string_set(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-set!", args, 3, 3)?;
+
+    let k = index_arg(&args[1])?;
+    let c = char_arg(&args[2])?;
+
+    match *args[0] {
+        Value::Str {
+            ref chars,
+            mutable: true,
+        } => match chars.borrow_mut().get_mut(k) {
+            Some(slot) => *slot = c,
+            None => return Err(RuntimeError::IndexOutOfRange(args[1].clone())),
+        },
+        Value::Str { .. } => {
+            return Err(RuntimeError::ImmutableValue(args[0].clone()))
+        }
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn string_fill(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-fill!", args, 2, 4)?;
+
+    let len = string_arg(&args[0])?.len();
+    let c = char_arg(&args[1])?;
+    let (start, end) = range_args(args, 2, len)?;
+
+    match *args[0] {
+        Value::Str {
+            ref chars,
+            mutable: true,
+        } => {
+            for slot in &mut chars.borrow_mut()[start..end] {
+                *slot = c;
+            }
+        }
+        _ => return Err(RuntimeError::ImmutableValue(args[0].clone())),
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn substring(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("substring", args, 3, 3)?;
+
+    let chars = string_arg(&args[0])?;
+    let (start, end) = range_args(args, 1, chars.len())?;
+
+    Ok(vm.memory.string(chars[start..end].to_vec()))
+}
+
+fn string_append(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let mut result = Vec::new();
+
+    for arg in args {
+        result.append(&mut string_arg(arg)?);
+    }
+
+    Ok(vm.memory.string(result))
+}
+
+fn string_copy(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-copy", args, 1, 3)?;
+
+    let chars = string_arg(&args[0])?;
+    let (start, end) = range_args(args, 1, chars.len())?;
+
+    Ok(vm.memory.string(chars[start..end].to_vec()))
+}
+
+fn string_to_list(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("string->list", args, 1, 3)?;
+
+    let chars = string_arg(&args[0])?;
+    let (start, end) = range_args(args, 1, chars.len())?;
+    let values = chars[start..end]
+        .iter()
+        .map(|&c| vm.memory.character(c))
+        .collect();
+
+    Ok(vm.memory.list(values))
+}
+
+fn list_to_string(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("list->string", args, 1, 1)?;
+
+    let mut chars = Vec::new();
+    let mut list = args[0].clone();
+
+    loop {
+        let next = match *list {
+            Value::Nil => break,
+            Value::Pair {
+                ref car, ref cdr, ..
+            } => {
+                chars.push(char_arg(&car.borrow())?);
+                cdr.borrow().clone()
+            }
+            _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+        };
+
+        list = next;
+    }
+
+    Ok(vm.memory.string(chars))
+}
+
+fn string_to_symbol(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("string->symbol", args, 1, 1)?;
+
+    let chars = string_arg(&args[0])?;
+    Ok(vm.memory.intern(chars.into_iter().collect()))
+}
+
+fn symbol_to_string(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("symbol->string", args, 1, 1)?;
+
+    match *args[0] {
+        Value::Atom(ref name) => {
+            Ok(vm.memory.literal_string(name.chars().collect()))
+        }
+        _ => Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    }
+}
+
+fn radix_arg(args: &[RcValue], at: usize) -> Result<u32, RuntimeError> {
+    match args.get(at) {
+        None => Ok(10),
+        Some(value) => match **value {
+            Value::Integer(radix @ 2) | Value::Integer(radix @ 8) => {
+                Ok(radix as u32)
+            }
+            Value::Integer(radix @ 10) | Value::Integer(radix @ 16) => {
+                Ok(radix as u32)
+            }
+            _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+        },
+    }
+}
+
+fn number_to_string(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("number->string", args, 1, 2)?;
+
+    let n = match *args[0] {
+        Value::Integer(n) => n,
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    };
+
+    let digits = match radix_arg(args, 1)? {
+        2 => format!("{:b}", n.unsigned_abs()),
+        8 => format!("{:o}", n.unsigned_abs()),
+        16 => format!("{:x}", n.unsigned_abs()),
+        _ => format!("{}", n.unsigned_abs()),
+    };
+    let sign = if n < 0 { "-" } else { "" };
+
+    Ok(vm
+        .memory
+        .string(sign.chars().chain(digits.chars()).collect()))
+}
+
+fn string_to_number(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("string->number", args, 1, 2)?;
+
+    let string: String = string_arg(&args[0])?.into_iter().collect();
+    let radix = radix_arg(args, 1)?;
+
+    match i64::from_str_radix(&string, radix) {
+        Ok(n) => Ok(vm.memory.integer(n)),
+        Err(_) => Ok(vm.memory.b_false()),
+    }
+}
+
+fn string_cmp<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    cmp: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&[char], &[char]) -> bool,
+{
+    check_arity(name, args, 1, usize::MAX)?;
+
+    let strings = args.iter().map(string_arg).collect::<Result<Vec<_>, _>>()?;
+    let outcome = strings.windows(2).all(|w| cmp(&w[0], &w[1]));
+
+    Ok(vm.memory.boolean(outcome))
+}
+
+fn string_convert<F>(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    convert: F,
+) -> Result<RcValue, RuntimeError>
+where
+    F: Fn(&[char]) -> Vec<char>,
+{
+    check_arity(name, args, 1, 1)?;
+
+    let chars = string_arg(&args[0])?;
+    Ok(vm.memory.string(convert(&chars)))
+}
+
+/// Splits the arguments of `string-for-each` and `string-map` into the
+/// procedure and the characters at each position of the strings.
+fn procedure_and_strings(
+    name: &str,
+    args: &[RcValue],
+) -> Result<(RcValue, Vec<Vec<char>>), RuntimeError> {
+    check_arity(name, args, 2, usize::MAX)?;
+
+    if !args[0].is_procedure() {
+        return Err(RuntimeError::WrongArgumentType(args[0].clone()));
+    }
+
+    let strings = args[1..]
+        .iter()
+        .map(string_arg)
+        .collect::<Result<Vec<_>, _>>()?;
+    let len = strings.iter().map(Vec::len).min().unwrap_or(0);
+    let columns = (0..len)
+        .map(|i| strings.iter().map(|s| s[i]).collect())
+        .collect();
+
+    Ok((args[0].clone(), columns))
+}
+
+fn string_for_each(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    let (procedure, columns) = procedure_and_strings("string-for-each", args)?;
+
+    for column in columns {
+        let chars = column.into_iter().map(|c| vm.memory.character(c)).collect();
+        vm.call(procedure.clone(), chars)?;
+    }
+
+    Ok(vm.memory.unspecified())
+}
+
+fn string_map(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    let (procedure, columns) = procedure_and_strings("string-map", args)?;
+    let mut result = Vec::with_capacity(columns.len());
+
+    for column in columns {
+        let chars = column.into_iter().map(|c| vm.memory.character(c)).collect();
+        let c = vm.call(procedure.clone(), chars)?;
+        result.push(char_arg(&c)?);
+    }
+
+    Ok(vm.memory.string(result))
+}
+
+fn string_contains(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("string-contains", args, 2, 2)?;
+
+    let haystack = string_arg(&args[0])?;
+    let needle = string_arg(&args[1])?;
+
+    let position = if needle.is_empty() {
+        Some(0)
+    } else {
+        haystack
+            .windows(needle.len())
+            .position(|w| w == &needle[..])
+    };
+
+    match position {
+        Some(i) => Ok(vm.memory.integer(i as i64)),
+        None => Ok(vm.memory.b_false()),
+    }
+}
+
+fn string_index(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-index", args, 2, 4)?;
+
+    let chars = string_arg(&args[0])?;
+    let matcher = CharMatcher::from_arg(args.get(1))?;
+    let (start, end) = range_args(args, 2, chars.len())?;
+
+    for (i, &c) in chars.iter().enumerate().take(end).skip(start) {
+        if matcher.matches(c, vm)? {
+            return Ok(vm.memory.integer(i as i64));
+        }
+    }
+
+    Ok(vm.memory.b_false())
+}
+
+fn string_split(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-split", args, 2, 2)?;
+
+    let chars = string_arg(&args[0])?;
+    let delimiter = char_arg(&args[1])?;
+    let parts = chars
+        .split(|&c| c == delimiter)
+        .map(|part| vm.memory.string(part.to_vec()))
+        .collect();
+
+    Ok(vm.memory.list(parts))
+}
+
+fn string_join(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("string-join", args, 1, 2)?;
+
+    let delimiter = match args.get(1) {
+        Some(value) => string_arg(value)?,
+        None => vec![' '],
+    };
+
+    let mut result = Vec::new();
+    let mut list = args[0].clone();
+
+    loop {
+        let next = match *list {
+            Value::Nil => break,
+            Value::Pair {
+                ref car, ref cdr, ..
+            } => {
+                if !Rc::ptr_eq(&list, &args[0]) {
+                    result.extend_from_slice(&delimiter);
+                }
+                result.append(&mut string_arg(&car.borrow())?);
+                cdr.borrow().clone()
+            }
+            _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+        };
+
+        list = next;
+    }
+
+    Ok(vm.memory.string(result))
+}
+
+fn string_pad(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    side: Side,
+) -> Result<RcValue, RuntimeError> {
+    check_arity(name, args, 2, 3)?;
+
+    let chars = string_arg(&args[0])?;
+    let len = index_arg(&args[1])?;
+    let fill = match args.get(2) {
+        Some(value) => char_arg(value)?,
+        None => ' ',
+    };
+
+    let padded = if chars.len() >= len {
+        match side {
+            Side::Left => chars[chars.len() - len..].to_vec(),
+            Side::Right => chars[..len].to_vec(),
+        }
+    } else {
+        let padding = vec![fill; len - chars.len()];
+        match side {
+            Side::Left => [padding, chars].concat(),
+            Side::Right => [chars, padding].concat(),
+        }
+    };
+
+    Ok(vm.memory.string(padded))
+}
+
+fn string_trim(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+    sides: &[Side],
+) -> Result<RcValue, RuntimeError> {
+    check_arity(name, args, 1, 2)?;
+
+    let chars = string_arg(&args[0])?;
+    let matcher = CharMatcher::from_arg(args.get(1))?;
+    let (mut start, mut end) = (0, chars.len());
+
+    if sides.contains(&Side::Left) {
+        while start < end && matcher.matches(chars[start], vm)? {
+            start += 1;
+        }
+    }
+
+    if sides.contains(&Side::Right) {
+        while end > start && matcher.matches(chars[end - 1], vm)? {
+            end -= 1;
+        }
+    }
+
+    Ok(vm.memory.string(chars[start..end].to_vec()))
+}
+
+fn is_symbol(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("symbol?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::Atom(..))))
+}
+
+fn is_procedure(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("procedure?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(args[0].is_procedure()))
+}
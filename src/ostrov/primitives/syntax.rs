@@ -0,0 +1,351 @@
+use crate::{
+    ast::AST,
+    errors::RuntimeError,
+    expander::Expander,
+    syntax_rules::{
+        instantiate, match_pattern, pattern_variables, Bindings, CaseContext,
+        Match,
+    },
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::{check_arity, index_arg};
+
+pub static PRIMITIVES: [&str; 11] = [
+    "%syntax-case",
+    "%syntax",
+    "syntax->datum",
+    "datum->syntax",
+    "identifier?",
+    "bound-identifier=?",
+    "free-identifier=?",
+    "generate-temporaries",
+    "syntax-violation",
+    "expand",
+    "expand-once",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "%syntax-case" => syntax_case(args, vm),
+        "%syntax" => syntax(args, vm),
+        "syntax->datum" => syntax_to_datum(args, vm),
+        "datum->syntax" => datum_to_syntax(args, vm),
+        "identifier?" => is_identifier(args, vm),
+        "bound-identifier=?" => bound_identifier_eq(args, vm),
+        "free-identifier=?" => free_identifier_eq(args, vm),
+        "generate-temporaries" => generate_temporaries(args, vm),
+        "syntax-violation" => syntax_violation(args, vm),
+        "expand" => expand(args, vm),
+        "expand-once" => expand_once(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+fn form_arg(value: &RcValue) -> Result<AST, RuntimeError> {
+    value
+        .to_ast()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(value.clone()))
+}
+
+fn identifier_arg(value: &RcValue) -> Result<String, RuntimeError> {
+    match **value {
+        Value::Syntax(AST::Atom(ref identifier)) => Ok(identifier.clone()),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn atoms_arg(value: &RcValue) -> Result<Vec<String>, RuntimeError> {
+    match form_arg(value)? {
+        AST::List(ref items) => items
+            .iter()
+            .map(|item| match *item {
+                AST::Atom(ref atom) => Ok(atom.clone()),
+                _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+            })
+            .collect(),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+/// The datum of `value`, with the aliases of its identifiers stripped.
+fn datum(value: &RcValue, vm: &mut VM) -> RcValue {
+    match value.to_ast() {
+        Some(form) => {
+            let form = vm.identifiers.strip(&form);
+            Value::from_ast(&form, &mut vm.memory)
+        }
+        None => value.clone(),
+    }
+}
+
+/// What a pattern variable matched, as handed to the procedures of a
+/// `syntax-case` clause: a syntax object, or a list of what each repetition
+/// matched.
+fn match_value(matched: &Match, vm: &mut VM) -> RcValue {
+    match *matched {
+        Match::One(ref form) => vm.memory.syntax(form.clone()),
+        Match::Many(ref matches) => {
+            let values = matches.iter().map(|matched| match_value(matched, vm));
+            let values = values.collect();
+            vm.memory.list(values)
+        }
+    }
+}
+
+fn value_match(value: &RcValue, depth: usize) -> Result<Match, RuntimeError> {
+    if depth == 0 {
+        return form_arg(value).map(Match::One);
+    }
+
+    value
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(value.clone()))?
+        .iter()
+        .map(|value| value_match(value, depth - 1))
+        .collect::<Result<_, _>>()
+        .map(Match::Many)
+}
+
+/// `(%syntax-case form literals pattern fender output ...)`, which
+/// `syntax-case` expands to. Fenders and outputs are procedures of the pattern
+/// variables of their clause, and a clause without fender has `#f`.
+fn syntax_case(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    if args.len() < 2 || !(args.len() - 2).is_multiple_of(3) {
+        return Err(RuntimeError::BadArity(Some("syntax-case".into())));
+    }
+
+    let form = form_arg(&args[0])?;
+    let literals = atoms_arg(&args[1])?;
+
+    for clause in args[2..].chunks(3) {
+        let pattern = form_arg(&clause[0])?;
+        let mut bindings = Bindings::new();
+
+        let variables = {
+            let context = CaseContext {
+                literals: &literals,
+                identifiers: &vm.identifiers,
+            };
+
+            if !match_pattern(&pattern, &form, &context, &mut bindings) {
+                continue;
+            }
+
+            pattern_variables(&pattern, &context)
+        };
+
+        let values: Vec<RcValue> = variables
+            .iter()
+            .map(|(variable, _)| match_value(&bindings[variable], vm))
+            .collect();
+
+        if clause[1].is_procedure()
+            && vm.call(clause[1].clone(), values.clone())?.is_false()
+        {
+            continue;
+        }
+
+        return vm.call(clause[2].clone(), values);
+    }
+
+    let form = datum(&args[0], vm);
+    Err(RuntimeError::SyntaxViolation(
+        None,
+        "invalid syntax".into(),
+        form,
+        vm.memory.b_false(),
+    ))
+}
+
+/// `(%syntax template ((variable . depth) ...) value ...)`, which `syntax`
+/// expands to.
+fn syntax(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    if args.len() < 2 {
+        return Err(RuntimeError::BadArity(Some("syntax".into())));
+    }
+
+    let template = form_arg(&args[0])?;
+    let variables = args[1]
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[1].clone()))?;
+
+    let mut bindings = Bindings::new();
+    for (variable, value) in variables.iter().zip(&args[2..]) {
+        let name = match variable.car().as_deref() {
+            Some(Value::Atom(ref name)) => name.clone(),
+            _ => return Err(RuntimeError::WrongArgumentType(variable.clone())),
+        };
+        let depth = match variable.cdr() {
+            Some(ref depth) => index_arg(depth)?,
+            None => {
+                return Err(RuntimeError::WrongArgumentType(variable.clone()))
+            }
+        };
+
+        bindings.insert(name, value_match(value, depth)?);
+    }
+
+    let mut context = CaseContext {
+        literals: &[],
+        identifiers: &vm.identifiers,
+    };
+    let form = instantiate(&template, &bindings, &mut context)?;
+
+    Ok(vm.memory.syntax(form))
+}
+
+fn syntax_to_datum(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("syntax->datum", args, 1, 1)?;
+
+    form_arg(&args[0])?;
+    Ok(datum(&args[0], vm))
+}
+
+/// `(datum->syntax template-id datum)`: `datum` as a syntax object whose
+/// identifiers have the lexical context of `template-id`.
+fn datum_to_syntax(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("datum->syntax", args, 2, 2)?;
+
+    let template = identifier_arg(&args[0])?;
+    let form = form_arg(&args[1])?;
+    let form = with_context(&form, &template, vm);
+
+    Ok(vm.memory.syntax(form))
+}
+
+fn with_context(form: &AST, template: &str, vm: &mut VM) -> AST {
+    let with_context_all = |items: &[AST], vm: &mut VM| {
+        items
+            .iter()
+            .map(|item| with_context(item, template, vm))
+            .collect()
+    };
+
+    match *form {
+        AST::Atom(ref symbol) => {
+            AST::Atom(vm.identifiers.with_context(symbol, template))
+        }
+        AST::List(ref items) => AST::List(with_context_all(items, vm)),
+        AST::DottedList(ref items, ref tail) => AST::DottedList(
+            with_context_all(items, vm),
+            Box::new(with_context(tail, template, vm)),
+        ),
+        AST::Vector(ref items) => AST::Vector(with_context_all(items, vm)),
+        _ => form.clone(),
+    }
+}
+
+fn is_identifier(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("identifier?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(identifier_arg(&args[0]).is_ok()))
+}
+
+/// `bound-identifier=?`: identifiers are interchangeable when bound if they
+/// are the same alias, or the same plain identifier.
+fn bound_identifier_eq(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("bound-identifier=?", args, 2, 2)?;
+
+    let identifier = identifier_arg(&args[0])?;
+    let other = identifier_arg(&args[1])?;
+
+    Ok(vm.memory.boolean(identifier == other))
+}
+
+fn free_identifier_eq(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("free-identifier=?", args, 2, 2)?;
+
+    let identifier = identifier_arg(&args[0])?;
+    let other = identifier_arg(&args[1])?;
+    let same = vm.identifiers.free_identifier_eq(&identifier, &other);
+
+    Ok(vm.memory.boolean(same))
+}
+
+fn generate_temporaries(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("generate-temporaries", args, 1, 1)?;
+
+    let count = match form_arg(&args[0])? {
+        AST::List(ref items) => items.len(),
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    };
+
+    let temporaries = (0..count)
+        .map(|_| {
+            let temporary = vm.identifiers.fresh("t");
+            vm.memory.syntax(AST::Atom(temporary))
+        })
+        .collect();
+
+    Ok(vm.memory.list(temporaries))
+}
+
+/// `(syntax-violation who message form [subform])`, `who` being `#f`, a
+/// string or a symbol.
+fn syntax_violation(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("syntax-violation", args, 3, 4)?;
+
+    let who = match *args[0] {
+        Value::Bool(false) => None,
+        Value::Atom(ref who) => Some(who.clone()),
+        Value::Str { ref chars, .. } => Some(chars.borrow().iter().collect()),
+        _ => return Err(RuntimeError::WrongArgumentType(args[0].clone())),
+    };
+    let message = match *args[1] {
+        Value::Str { ref chars, .. } => chars.borrow().iter().collect(),
+        _ => return Err(RuntimeError::WrongArgumentType(args[1].clone())),
+    };
+    let form = datum(&args[2], vm);
+    let subform = match args.get(3) {
+        Some(subform) => datum(subform, vm),
+        None => vm.memory.b_false(),
+    };
+
+    Err(RuntimeError::SyntaxViolation(who, message, form, subform))
+}
+
+/// `(expand form)`: the core form `form` expands to.
+fn expand(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("expand", args, 1, 1)?;
+
+    let form = form_arg(&args[0])?;
+    let expanded = Expander::new(vm).expand(&form)?;
+
+    Ok(Value::from_ast(&expanded, &mut vm.memory))
+}
+
+/// `(expand-once form)`: `form` rewritten by the macro it is a use of, or
+/// `form` itself if it is none.
+fn expand_once(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("expand-once", args, 1, 1)?;
+
+    let form = form_arg(&args[0])?;
+    match Expander::new(vm).expand_once(&form)? {
+        Some(expanded) => Ok(Value::from_ast(&expanded, &mut vm.memory)),
+        None => Ok(args[0].clone()),
+    }
+}
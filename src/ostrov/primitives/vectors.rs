@@ -0,0 +1,152 @@
+use crate::{
+    errors::RuntimeError,
+    values::{RcValue, Value},
+    vm::VM,
+};
+
+use super::{check_arity, index_arg};
+
+pub static PRIMITIVES: [&str; 9] = [
+    "vector?",
+    "vector",
+    "make-vector",
+    "vector-length",
+    "vector-ref",
+    "vector-set!",
+    "vector-fill!",
+    "vector->list",
+    "list->vector",
+];
+
+pub fn apply(
+    name: &str,
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    match name {
+        "vector?" => is_vector(args, vm),
+        "vector" => vector(args, vm),
+        "make-vector" => make_vector(args, vm),
+        "vector-length" => vector_length(args, vm),
+        "vector-ref" => vector_ref(args, vm),
+        "vector-set!" => vector_set(args, vm),
+        "vector-fill!" => vector_fill(args, vm),
+        "vector->list" => vector_to_list(args, vm),
+        "list->vector" => list_to_vector(args, vm),
+        _ => Err(RuntimeError::PrimitiveFailed(name.to_owned())),
+    }
+}
+
+fn vector_arg(value: &RcValue) -> Result<Vec<RcValue>, RuntimeError> {
+    match **value {
+        Value::Vector { ref items, .. } => Ok(items.borrow().clone()),
+        _ => Err(RuntimeError::WrongArgumentType(value.clone())),
+    }
+}
+
+fn mutate<F>(vector: &RcValue, f: F) -> Result<(), RuntimeError>
+where
+    F: FnOnce(&mut Vec<RcValue>) -> Result<(), RuntimeError>,
+{
+    match **vector {
+        Value::Vector {
+            ref items,
+            mutable: true,
+        } => f(&mut items.borrow_mut()),
+        Value::Vector { .. } => Err(RuntimeError::ImmutableValue(vector.clone())),
+        _ => Err(RuntimeError::WrongArgumentType(vector.clone())),
+    }
+}
+
+fn is_vector(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("vector?", args, 1, 1)?;
+
+    Ok(vm.memory.boolean(matches!(*args[0], Value::Vector { .. })))
+}
+
+fn vector(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    Ok(vm.memory.vector(args.to_vec()))
+}
+
+fn make_vector(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("make-vector", args, 1, 2)?;
+
+    let len = index_arg(&args[0])?;
+    let fill = match args.get(1) {
+        Some(value) => value.clone(),
+        None => vm.memory.unspecified(),
+    };
+
+    Ok(vm.memory.vector(vec![fill; len]))
+}
+
+fn vector_length(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("vector-length", args, 1, 1)?;
+
+    let items = vector_arg(&args[0])?;
+    Ok(vm.memory.integer(items.len() as i64))
+}
+
+fn vector_ref(args: &[RcValue], _vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("vector-ref", args, 2, 2)?;
+
+    let items = vector_arg(&args[0])?;
+    let k = index_arg(&args[1])?;
+
+    items
+        .get(k)
+        .cloned()
+        .ok_or_else(|| RuntimeError::IndexOutOfRange(args[1].clone()))
+}
+
+fn vector_set(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("vector-set!", args, 3, 3)?;
+
+    let k = index_arg(&args[1])?;
+
+    mutate(&args[0], |items| match items.get_mut(k) {
+        Some(slot) => {
+            *slot = args[2].clone();
+            Ok(())
+        }
+        None => Err(RuntimeError::IndexOutOfRange(args[1].clone())),
+    })?;
+
+    Ok(vm.memory.unspecified())
+}
+
+fn vector_fill(args: &[RcValue], vm: &mut VM) -> Result<RcValue, RuntimeError> {
+    check_arity("vector-fill!", args, 2, 2)?;
+
+    mutate(&args[0], |items| {
+        for slot in items.iter_mut() {
+            *slot = args[1].clone();
+        }
+        Ok(())
+    })?;
+
+    Ok(vm.memory.unspecified())
+}
+
+fn vector_to_list(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("vector->list", args, 1, 1)?;
+
+    let items = vector_arg(&args[0])?;
+    Ok(vm.memory.list(items))
+}
+
+fn list_to_vector(
+    args: &[RcValue],
+    vm: &mut VM,
+) -> Result<RcValue, RuntimeError> {
+    check_arity("list->vector", args, 1, 1)?;
+
+    let items = args[0]
+        .list_to_vec()
+        .ok_or_else(|| RuntimeError::WrongArgumentType(args[0].clone()))?;
+
+    Ok(vm.memory.vector(items))
+}
@@ -0,0 +1,35 @@
+use crate::values::RcValue;
+use std::{cell::RefCell, rc::Rc};
+
+/// What forcing a pending promise computes. Streams are promises whose value
+/// is either `()` or a pair of a promise for the element and a stream.
+#[derive(Clone)]
+pub enum Pending {
+    /// `(delay expr)`: calling the thunk yields the value.
+    Delay(RcValue),
+    /// `(delay-force expr)`: calling the thunk yields another promise, whose
+    /// value becomes this one's.
+    DelayForce(RcValue),
+    /// A procedure applied to the values of some promises.
+    Apply(RcValue, Vec<RcValue>),
+    /// The first elements of a stream.
+    Take(usize, RcValue),
+    /// A procedure mapped over the elements of some streams.
+    Map(RcValue, Vec<RcValue>),
+    /// The elements of a stream satisfying a predicate.
+    Filter(RcValue, RcValue),
+}
+
+#[derive(Clone)]
+pub enum PromiseState {
+    Forced(RcValue),
+    Pending(Pending),
+}
+
+/// The state of a promise lives in a box of its own, so that `delay-force`
+/// can make a chain of promises share one box and force it iteratively.
+pub type PromiseBox = Rc<RefCell<PromiseState>>;
+
+pub fn new_box(state: PromiseState) -> RefCell<PromiseBox> {
+    RefCell::new(Rc::new(RefCell::new(state)))
+}
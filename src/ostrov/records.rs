@@ -0,0 +1,187 @@
+use crate::values::RcValue;
+use std::rc::Rc;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field {
+    pub name: String,
+    pub mutable: bool,
+}
+
+/// A record type descriptor. Fields of the parent come first in the record,
+/// followed by the ones declared here.
+pub struct RecordType {
+    pub name: String,
+    pub parent: Option<Rc<RecordType>>,
+    pub uid: Option<String>,
+    pub sealed: bool,
+    pub opaque: bool,
+    pub fields: Vec<Field>,
+}
+
+impl RecordType {
+    /// Number of fields, including the inherited ones.
+    pub fn field_count(&self) -> usize {
+        self.parent_field_count() + self.fields.len()
+    }
+
+    pub fn parent_field_count(&self) -> usize {
+        self.parent
+            .as_ref()
+            .map_or(0, |parent| parent.field_count())
+    }
+
+    /// Fields, including the inherited ones, in record order.
+    pub fn all_fields(&self) -> Vec<&Field> {
+        let mut fields = match self.parent {
+            Some(ref parent) => parent.all_fields(),
+            None => Vec::new(),
+        };
+        fields.extend(self.fields.iter());
+        fields
+    }
+
+    /// Whether this type is `other` or one of its descendants.
+    pub fn is_subtype_of(&self, other: &RecordType) -> bool {
+        let mut rtd = self;
+
+        loop {
+            if std::ptr::eq(rtd, other) {
+                return true;
+            }
+
+            match rtd.parent {
+                Some(ref parent) => rtd = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Whether records of this type hide their contents from `record?`,
+    /// `record-rtd` and printing. Opacity is inherited.
+    pub fn is_opaque(&self) -> bool {
+        self.opaque || self.parent.as_ref().is_some_and(|p| p.is_opaque())
+    }
+}
+
+/// A record constructor descriptor: how to build records of `rtd`.
+pub struct ConstructorDescriptor {
+    pub rtd: Rc<RecordType>,
+    pub parent: Option<Rc<ConstructorDescriptor>>,
+    pub protocol: Option<RcValue>,
+}
+
+impl ConstructorDescriptor {
+    pub fn default_for(rtd: Rc<RecordType>) -> ConstructorDescriptor {
+        ConstructorDescriptor {
+            rtd,
+            parent: None,
+            protocol: None,
+        }
+    }
+}
+
+/// Procedures generated by the records system. `Constructor` builds a record
+/// of `rtd` from its arguments followed by the field values `collected` so far
+/// by the constructors of descendant types; the two initializers are the `n`
+/// and `p` procedures handed to protocols of types with a parent.
+#[derive(Clone)]
+pub enum RecordProcedure {
+    Constructor {
+        rtd: Rc<RecordType>,
+        arity: usize,
+        collected: Vec<RcValue>,
+    },
+    ParentInitializer {
+        descriptor: Rc<ConstructorDescriptor>,
+        rtd: Rc<RecordType>,
+        collected: Vec<RcValue>,
+    },
+    ChildInitializer {
+        descriptor: Rc<ConstructorDescriptor>,
+        rtd: Rc<RecordType>,
+        collected: Vec<RcValue>,
+        parent_args: Vec<RcValue>,
+    },
+    Predicate(Rc<RecordType>),
+    Accessor(Rc<RecordType>, usize),
+    Mutator(Rc<RecordType>, usize),
+    ConditionPredicate(Rc<RecordType>),
+    ConditionAccessor(Rc<RecordType>, RcValue),
+}
+
+impl RecordProcedure {
+    pub fn name(&self) -> String {
+        match *self {
+            RecordProcedure::Constructor { ref rtd, .. } => {
+                format!("make-{}", rtd.name)
+            }
+            RecordProcedure::ParentInitializer { ref rtd, .. }
+            | RecordProcedure::ChildInitializer { ref rtd, .. } => {
+                format!("{} initializer", rtd.name)
+            }
+            RecordProcedure::Predicate(ref rtd) => format!("{}?", rtd.name),
+            RecordProcedure::Accessor(ref rtd, index) => {
+                format!("{}-{}", rtd.name, rtd.all_fields()[index].name)
+            }
+            RecordProcedure::Mutator(ref rtd, index) => {
+                format!("{}-{}-set!", rtd.name, rtd.all_fields()[index].name)
+            }
+            RecordProcedure::ConditionPredicate(ref rtd) => {
+                format!("{} predicate", rtd.name)
+            }
+            RecordProcedure::ConditionAccessor(ref rtd, ..) => {
+                format!("{} accessor", rtd.name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Field, RecordType};
+    use std::rc::Rc;
+
+    fn record_type(name: &str, parent: Option<Rc<RecordType>>) -> Rc<RecordType> {
+        Rc::new(RecordType {
+            name: name.into(),
+            parent,
+            uid: None,
+            sealed: false,
+            opaque: false,
+            fields: vec![Field {
+                name: format!("{}-field", name),
+                mutable: false,
+            }],
+        })
+    }
+
+    #[test]
+    fn inherited_fields() {
+        let point = record_type("point", None);
+        let point3 = record_type("point3", Some(point.clone()));
+
+        assert_eq!(1, point.field_count());
+        assert_eq!(2, point3.field_count());
+        assert_eq!(1, point3.parent_field_count());
+        assert_eq!(
+            vec!["point-field", "point3-field"],
+            point3
+                .all_fields()
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn subtypes() {
+        let point = record_type("point", None);
+        let point3 = record_type("point3", Some(point.clone()));
+        let other = record_type("other", None);
+
+        assert!(point3.is_subtype_of(&point));
+        assert!(point3.is_subtype_of(&point3));
+        assert!(!point.is_subtype_of(&point3));
+        assert!(!point3.is_subtype_of(&other));
+    }
+}
@@ -1,16 +1,25 @@
-use crate::{runtime::Runtime, values::Value};
+use crate::{ast::AST, errors::RuntimeError, runtime::Runtime, values::Value};
 use std::{
+    env,
     io::{self, Write},
     path::Path,
+    process,
 };
 
 pub fn repl(args: &[String]) {
     let mut runtime = Runtime::new();
 
+    if let Some(path) = env::var_os("OSTROV_LIBRARY_PATH") {
+        runtime.set_library_path(env::split_paths(&path).collect());
+    }
+
     if args.len() > 1 {
         let path = Path::new(&args[1]);
 
-        runtime.eval_file(path);
+        if let Err(error) = runtime.eval_file(path) {
+            eprintln!("Error: {:?}", error);
+            process::exit(1);
+        }
     } else {
         enter_repl(&mut runtime);
     }
@@ -31,11 +40,16 @@ fn enter_repl(runtime: &mut Runtime) {
                 "dump-heap\n" => {
                     runtime.dump_heap();
                 }
+                line if line.starts_with("expand-steps ") => {
+                    expand_steps(runtime, &line["expand-steps ".len()..]);
+                }
                 line => match runtime.eval_str(line) {
                     Ok(exprs) => {
                         for expr in &exprs {
-                            if **expr != Value::Unspecified {
-                                println!("=> {}", expr);
+                            for value in Value::values(expr) {
+                                if *value != Value::Unspecified {
+                                    println!("=> {}", value);
+                                }
                             }
                         }
                     }
@@ -46,3 +60,37 @@ fn enter_repl(runtime: &mut Runtime) {
         }
     }
 }
+
+/// Shows each macro expansion step of the forms in `line`, the macro uses
+/// nested in them included, then the core form they expand to. Identifiers
+/// renamed for hygiene read `name%n`.
+fn expand_steps(runtime: &mut Runtime, line: &str) {
+    let exprs = match runtime.parse_str(line) {
+        Ok(exprs) => exprs,
+        Err(error) => return println!("Error: {:?}", error),
+    };
+
+    for expr in exprs {
+        if let Err(error) = print_steps(runtime, expr) {
+            println!("Error: {:?}", error);
+        }
+    }
+}
+
+fn print_steps(runtime: &mut Runtime, expr: AST) -> Result<(), RuntimeError> {
+    let mut form = expr;
+    let mut step = 0;
+
+    loop {
+        println!("{}: {}", step, form);
+
+        match runtime.expand_step(&form)? {
+            Some(expanded) => form = expanded,
+            None => break,
+        }
+        step += 1;
+    }
+
+    println!("=> {}", runtime.expand(&form)?);
+    Ok(())
+}
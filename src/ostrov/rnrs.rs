@@ -0,0 +1,388 @@
+use crate::{expander::Scope, libraries::Library};
+use std::rc::Rc;
+
+/// The standard libraries, and the built-in bindings each exports.
+static LIBRARIES: [(&[&str], &[&str]); 17] = [
+    (&["rnrs", "base"], &BASE),
+    (
+        &["rnrs", "control"],
+        &["case-lambda", "do", "unless", "when"],
+    ),
+    (
+        &["rnrs", "lists"],
+        &["assoc", "assq", "assv", "member", "memq", "memv"],
+    ),
+    (&["rnrs", "io", "simple"], &IO_SIMPLE),
+    (&["rnrs", "hashtables"], &HASHTABLES),
+    (&["rnrs", "unicode"], &UNICODE),
+    (&["rnrs", "records", "procedural"], &RECORDS_PROCEDURAL),
+    (&["rnrs", "records", "inspection"], &RECORDS_INSPECTION),
+    (
+        &["rnrs", "records", "syntactic"],
+        &[
+            "define-record-type",
+            "record-constructor-descriptor",
+            "record-type-descriptor",
+        ],
+    ),
+    (
+        &["rnrs", "exceptions"],
+        &[
+            "=>",
+            "else",
+            "guard",
+            "raise",
+            "raise-continuable",
+            "with-exception-handler",
+        ],
+    ),
+    (&["rnrs", "conditions"], &CONDITIONS),
+    (&["rnrs", "enums"], &ENUMS),
+    (&["rnrs", "syntax-case"], &SYNTAX_CASE),
+    (&["rnrs", "mutable-pairs"], &["set-car!", "set-cdr!"]),
+    (
+        &["rnrs", "mutable-strings"],
+        &["string-fill!", "string-set!"],
+    ),
+    (&["rnrs", "eval"], &["environment", "eval"]),
+    (&["rnrs", "r5rs"], &["delay", "force"]),
+];
+
+/// The libraries `(rnrs)` doesn't re-export.
+static NOT_COMPOSED: [&str; 4] =
+    ["eval", "mutable-pairs", "mutable-strings", "r5rs"];
+
+static BASE: [&str; 93] = [
+    "=>",
+    "_",
+    "...",
+    "and",
+    "assert",
+    "begin",
+    "case",
+    "cond",
+    "define",
+    "define-syntax",
+    "else",
+    "identifier-syntax",
+    "if",
+    "lambda",
+    "let",
+    "let*",
+    "let*-values",
+    "let-syntax",
+    "let-values",
+    "letrec",
+    "letrec*",
+    "letrec-syntax",
+    "or",
+    "quote",
+    "set!",
+    "syntax-rules",
+    "*",
+    "+",
+    "-",
+    "/",
+    "<",
+    "<=",
+    "=",
+    ">",
+    ">=",
+    "assertion-violation",
+    "call-with-values",
+    "car",
+    "cdr",
+    "char->integer",
+    "char<=?",
+    "char<?",
+    "char=?",
+    "char>=?",
+    "char>?",
+    "char?",
+    "cons",
+    "div",
+    "div-and-mod",
+    "eq?",
+    "equal?",
+    "eqv?",
+    "error",
+    "integer->char",
+    "length",
+    "list",
+    "list->string",
+    "list->vector",
+    "list?",
+    "make-string",
+    "make-vector",
+    "mod",
+    "not",
+    "null?",
+    "number->string",
+    "pair?",
+    "procedure?",
+    "string",
+    "string->list",
+    "string->number",
+    "string->symbol",
+    "string-append",
+    "string-copy",
+    "string-for-each",
+    "string-length",
+    "string-ref",
+    "string<=?",
+    "string<?",
+    "string=?",
+    "string>=?",
+    "string>?",
+    "string?",
+    "substring",
+    "symbol->string",
+    "symbol?",
+    "values",
+    "vector",
+    "vector->list",
+    "vector-fill!",
+    "vector-length",
+    "vector-ref",
+    "vector-set!",
+    "vector?",
+];
+
+static IO_SIMPLE: [&str; 13] = [
+    "&i/o",
+    "&i/o-filename",
+    "current-error-port",
+    "current-input-port",
+    "current-output-port",
+    "display",
+    "i/o-error-filename",
+    "i/o-error?",
+    "i/o-filename-error?",
+    "input-port?",
+    "make-i/o-filename-error",
+    "newline",
+    "output-port?",
+];
+
+static HASHTABLES: [&str; 18] = [
+    "equal-hash",
+    "hashtable-clear!",
+    "hashtable-contains?",
+    "hashtable-copy",
+    "hashtable-delete!",
+    "hashtable-entries",
+    "hashtable-keys",
+    "hashtable-mutable?",
+    "hashtable-ref",
+    "hashtable-set!",
+    "hashtable-size",
+    "hashtable-update!",
+    "hashtable?",
+    "make-eq-hashtable",
+    "make-eqv-hashtable",
+    "make-hashtable",
+    "string-hash",
+    "symbol-hash",
+];
+
+static UNICODE: [&str; 14] = [
+    "char-alphabetic?",
+    "char-ci=?",
+    "char-downcase",
+    "char-foldcase",
+    "char-lower-case?",
+    "char-numeric?",
+    "char-upcase",
+    "char-upper-case?",
+    "char-whitespace?",
+    "string-ci=?",
+    "string-downcase",
+    "string-foldcase",
+    "string-titlecase",
+    "string-upcase",
+];
+
+static RECORDS_PROCEDURAL: [&str; 7] = [
+    "make-record-constructor-descriptor",
+    "make-record-type-descriptor",
+    "record-accessor",
+    "record-constructor",
+    "record-mutator",
+    "record-predicate",
+    "record-type-descriptor?",
+];
+
+static RECORDS_INSPECTION: [&str; 10] = [
+    "record-field-mutable?",
+    "record-rtd",
+    "record-type-field-names",
+    "record-type-generative?",
+    "record-type-name",
+    "record-type-opaque?",
+    "record-type-parent",
+    "record-type-sealed?",
+    "record-type-uid",
+    "record?",
+];
+
+static CONDITIONS: [&str; 47] = [
+    "&assertion",
+    "&condition",
+    "&error",
+    "&implementation-restriction",
+    "&irritants",
+    "&lexical",
+    "&message",
+    "&non-continuable",
+    "&serious",
+    "&syntax",
+    "&undefined",
+    "&violation",
+    "&warning",
+    "&who",
+    "assertion-violation?",
+    "condition",
+    "condition-accessor",
+    "condition-irritants",
+    "condition-message",
+    "condition-predicate",
+    "condition-who",
+    "condition?",
+    "error?",
+    "implementation-restriction-violation?",
+    "irritants-condition?",
+    "lexical-violation?",
+    "make-assertion-violation",
+    "make-error",
+    "make-implementation-restriction-violation",
+    "make-irritants-condition",
+    "make-lexical-violation",
+    "make-message-condition",
+    "make-non-continuable-violation",
+    "make-serious-condition",
+    "make-syntax-violation",
+    "make-undefined-violation",
+    "make-violation",
+    "make-warning",
+    "make-who-condition",
+    "message-condition?",
+    "non-continuable-violation?",
+    "serious-condition?",
+    "simple-conditions",
+    "syntax-violation-form",
+    "syntax-violation-subform",
+    "syntax-violation?",
+    "undefined-violation?",
+];
+
+static ENUMS: [&str; 14] = [
+    "define-enumeration",
+    "enum-set->list",
+    "enum-set-complement",
+    "enum-set-constructor",
+    "enum-set-difference",
+    "enum-set-indexer",
+    "enum-set-intersection",
+    "enum-set-member?",
+    "enum-set-projection",
+    "enum-set-subset?",
+    "enum-set-union",
+    "enum-set-universe",
+    "enum-set=?",
+    "make-enumeration",
+];
+
+static SYNTAX_CASE: [&str; 12] = [
+    "_",
+    "...",
+    "bound-identifier=?",
+    "datum->syntax",
+    "free-identifier=?",
+    "generate-temporaries",
+    "identifier?",
+    "syntax",
+    "syntax->datum",
+    "syntax-case",
+    "syntax-violation",
+    "with-syntax",
+];
+
+/// The built-in bindings that aren't part of the standard, which `(ostrov)`
+/// exports.
+static OSTROV: [&str; 40] = [
+    "define-values",
+    "delay-force",
+    "enum-set?",
+    "expand",
+    "expand-once",
+    "get-output-string",
+    "include",
+    "include-ci",
+    "interaction-environment",
+    "list->stream",
+    "load",
+    "make-parameter",
+    "make-promise",
+    "open-output-string",
+    "parameterize",
+    "port?",
+    "promise?",
+    "receive",
+    "stream",
+    "stream->list",
+    "stream-car",
+    "stream-cdr",
+    "stream-cons",
+    "stream-filter",
+    "stream-map",
+    "stream-null",
+    "stream-null?",
+    "stream-pair?",
+    "stream-take",
+    "stream?",
+    "string-contains",
+    "string-index",
+    "string-join",
+    "string-map",
+    "string-pad",
+    "string-pad-right",
+    "string-split",
+    "string-trim",
+    "string-trim-both",
+    "string-trim-right",
+];
+
+/// The standard libraries, version `(6)`, exporting the bindings of the
+/// built-in top level `scope`. `(rnrs)` gathers most of the others. The
+/// bindings of the implementation are exported by `(ostrov)`, which has no
+/// version.
+pub fn libraries(scope: &Rc<Scope>) -> Vec<Library> {
+    let library =
+        |name: &[&str], version: Vec<u64>, exports: Vec<&str>| Library {
+            name: name.iter().map(|part| (*part).to_owned()).collect(),
+            version,
+            scope: scope.clone(),
+            exports: exports
+                .into_iter()
+                .map(|export| (export.to_owned(), export.to_owned()))
+                .collect(),
+        };
+
+    let mut composite: Vec<&str> = vec![];
+    let mut libraries = vec![];
+
+    for &(name, exports) in LIBRARIES.iter() {
+        if !NOT_COMPOSED.contains(&name[1]) {
+            for export in exports {
+                if !composite.contains(export) {
+                    composite.push(export);
+                }
+            }
+        }
+        libraries.push(library(name, vec![6], exports.to_vec()));
+    }
+    libraries.push(library(&["rnrs"], vec![6], composite));
+    libraries.push(library(&["ostrov"], vec![], OSTROV.to_vec()));
+
+    libraries
+}
@@ -1,12 +1,12 @@
 use crate::{
     ast::AST,
-    compiler::compile_single,
-    errors::Error,
+    errors::{Error, RuntimeError},
+    expander::Expander,
     parser::{parse, ParseError},
     values::RcValue,
     vm::VM,
 };
-use std::{fs, path::Path};
+use std::path::{Path, PathBuf};
 
 pub struct Runtime {
     vm: VM,
@@ -29,33 +29,76 @@ impl Runtime {
         input: &'a str,
     ) -> Result<Vec<RcValue>, Error<'a>> {
         let exprs = self.parse_str(input)?;
+        Ok(Expander::new(&mut self.vm).run(&exprs)?)
+    }
+
+    /// Runs the top-level program `input`: an `(import spec ...)` form
+    /// followed by definitions and expressions, which only see the bindings
+    /// it imports. Returns the values of the latter.
+    pub fn eval_program<'a>(
+        &mut self,
+        input: &'a str,
+    ) -> Result<Vec<RcValue>, Error<'a>> {
+        let exprs = self.parse_str(input)?;
+        Ok(Expander::new(&mut self.vm).run_program(&exprs)?)
+    }
+
+    /// The core form `expr` expands to. Local variables that would capture
+    /// identifiers introduced by macros are renamed `name%n`.
+    pub fn expand(&mut self, expr: &AST) -> Result<AST, RuntimeError> {
+        Expander::new(&mut self.vm).expand(expr)
+    }
+
+    /// `expr` rewritten once by the macro it is a use of, if any. The
+    /// identifiers the macro introduced are renamed `name%n`.
+    pub fn expand_once(
+        &mut self,
+        expr: &AST,
+    ) -> Result<Option<AST>, RuntimeError> {
+        Expander::new(&mut self.vm).expand_once(expr)
+    }
+
+    /// `expr` with its first macro use rewritten once, looking at `expr`
+    /// itself, then into its subforms. Applying it until it returns `None`
+    /// shows every step of the expansion of `expr`.
+    pub fn expand_step(
+        &mut self,
+        expr: &AST,
+    ) -> Result<Option<AST>, RuntimeError> {
+        Expander::new(&mut self.vm).expand_step(expr)
+    }
 
-        let mut evalued_exprs = Vec::new();
-        for expr in &exprs {
-            let bytecode = compile_single(expr)?;
-            let evalued_expr = self.vm.execute(bytecode)?;
-            evalued_exprs.push(evalued_expr);
-        }
+    /// Runs the file at `path`: a top-level program if it starts with an
+    /// `import` form, otherwise its forms one after the other at the top
+    /// level. Errors raised by its forms are `Located` at them, and the files
+    /// it loads or includes are found relative to it.
+    pub fn eval_file(&mut self, path: &Path) -> Result<(), RuntimeError> {
+        Expander::new(&mut self.vm).run_file(path).map(|_| ())
+    }
 
-        Ok(evalued_exprs)
+    /// The directories searched, in order, for the files of imported
+    /// libraries: `(foo bar)` is defined in `foo/bar.sls`, `foo/bar.ss` or
+    /// `foo/bar.scm` under one of them.
+    pub fn library_path(&self) -> &[PathBuf] {
+        &self.vm.libraries.path
     }
 
-    pub fn eval_file(&mut self, path: &Path) {
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!("Failed loading {:?}: {:?}", path, e);
-                return;
-            }
-        };
+    pub fn set_library_path(&mut self, path: Vec<PathBuf>) {
+        self.vm.libraries.path = path;
+    }
 
-        match self.eval_str(&content) {
-            Ok(_) => (),
-            Err(e) => eprintln!("{:?}", e),
-        }
+    /// The condition object describing `error`, as Scheme handlers see it.
+    pub fn condition(&mut self, error: &RuntimeError) -> RcValue {
+        self.vm.condition(error)
     }
 
     pub fn dump_heap(&self) {
         self.vm.memory.dump();
     }
 }
+
+impl Default for Runtime {
+    fn default() -> Runtime {
+        Runtime::new()
+    }
+}
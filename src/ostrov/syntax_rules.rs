@@ -0,0 +1,635 @@
+use crate::{
+    ast::AST::{self, *},
+    errors::RuntimeError,
+    expander::{Identifiers, Scope},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// What a pattern variable matched: a form, or one match for each repetition
+/// of the ellipsis following it.
+#[derive(Clone)]
+pub enum Match {
+    One(AST),
+    Many(Vec<Match>),
+}
+
+pub type Bindings = HashMap<String, Match>;
+
+/// How patterns and templates tell their identifiers apart, which differs
+/// between `syntax-rules` and `syntax-case`.
+pub trait Context {
+    fn is_ellipsis(&self, atom: &str) -> bool;
+
+    fn is_literal(&self, atom: &str) -> bool;
+
+    fn is_underscore(&self, atom: &str) -> bool;
+
+    /// Whether the identifier `identifier` of a form matches the literal
+    /// `literal` of a pattern.
+    fn matches_literal(&self, identifier: &str, literal: &str) -> bool;
+
+    /// What a template identifier that isn't a pattern variable becomes.
+    fn rename(&mut self, atom: &str) -> String;
+
+    fn is_pattern_variable(&self, atom: &str) -> bool {
+        !self.is_literal(atom)
+            && !self.is_ellipsis(atom)
+            && !self.is_underscore(atom)
+    }
+}
+
+/// A `syntax-rules` transformer, closed over the scope it was defined in.
+pub struct SyntaxRules {
+    ellipsis: String,
+    literals: Vec<String>,
+    rules: Vec<(AST, AST)>,
+    scope: Rc<Scope>,
+}
+
+impl SyntaxRules {
+    /// Parses the arguments of `(syntax-rules [ellipsis] (literal ...)
+    /// (pattern template) ...)`.
+    pub fn new(
+        args: &[AST],
+        scope: Rc<Scope>,
+    ) -> Result<SyntaxRules, RuntimeError> {
+        let (ellipsis, args) = match args.split_first() {
+            Some((Atom(ellipsis), args)) => (ellipsis.clone(), args),
+            _ => ("...".to_owned(), args),
+        };
+
+        let (literals, rules) = match args.split_first() {
+            Some((List(literals), rules)) => (literals, rules),
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let literals = literals
+            .iter()
+            .map(|literal| match *literal {
+                Atom(ref literal) => Ok(literal.clone()),
+                _ => Err(RuntimeError::MalformedExpression),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let rules = rules
+            .iter()
+            .map(|rule| match *rule {
+                List(ref rule) if rule.len() == 2 => match rule[0] {
+                    List(ref pattern) | DottedList(ref pattern, _)
+                        if !pattern.is_empty() =>
+                    {
+                        Ok((rule[0].clone(), rule[1].clone()))
+                    }
+                    _ => Err(RuntimeError::MalformedExpression),
+                },
+                _ => Err(RuntimeError::MalformedExpression),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(SyntaxRules {
+            ellipsis,
+            literals,
+            rules,
+            scope,
+        })
+    }
+
+    /// The identifiers templates introduce into their expansions, as opposed
+    /// to the ones they copy from the macro use.
+    pub fn introduced(&self, identifiers: &mut Identifiers) -> HashSet<String> {
+        let mut introduced = HashSet::new();
+
+        for (pattern, template) in &self.rules {
+            let variables: Vec<String> = {
+                let context = self.context(identifiers, &self.scope);
+                pattern_variables(pattern, &context)
+                    .into_iter()
+                    .map(|(variable, _)| variable)
+                    .collect()
+            };
+
+            let mut atoms = vec![];
+            atoms_of(template, &mut atoms);
+
+            introduced.extend(
+                atoms
+                    .into_iter()
+                    .filter(|atom| !variables.contains(atom))
+                    .map(|atom| identifiers.base(&atom)),
+            );
+        }
+
+        introduced
+    }
+
+    /// Rewrites `form` with the first rule whose pattern matches it. The
+    /// identifiers the template introduces are renamed, so that they refer to
+    /// what they meant where the macro was defined.
+    pub fn expand(
+        &self,
+        form: &AST,
+        identifiers: &mut Identifiers,
+        scope: &Rc<Scope>,
+    ) -> Result<AST, RuntimeError> {
+        let (items, tail) = match *form {
+            List(ref items) => (&items[1..], None),
+            DottedList(ref items, ref tail) => (&items[1..], Some(&**tail)),
+            _ => return Err(RuntimeError::MalformedExpression),
+        };
+
+        let mut context = self.context(identifiers, scope);
+
+        for (pattern, template) in &self.rules {
+            // The keyword position of the pattern is ignored.
+            let (patterns, pattern_tail) = match *pattern {
+                List(ref patterns) => (&patterns[1..], None),
+                DottedList(ref patterns, ref tail) => {
+                    (&patterns[1..], Some(&**tail))
+                }
+                _ => continue,
+            };
+
+            let mut bindings = Bindings::new();
+            let matcher = Matcher(&context);
+
+            if matcher.items(patterns, pattern_tail, items, tail, &mut bindings) {
+                return instantiate(template, &bindings, &mut context);
+            }
+        }
+
+        Err(RuntimeError::MalformedExpression)
+    }
+
+    fn context<'a>(
+        &'a self,
+        identifiers: &'a mut Identifiers,
+        scope: &'a Rc<Scope>,
+    ) -> RulesContext<'a> {
+        RulesContext {
+            rules: self,
+            mark: identifiers.new_mark(),
+            identifiers,
+            scope,
+        }
+    }
+}
+
+/// The identifiers of a `syntax-rules` expansion. The ellipsis may be custom,
+/// literals are compared by binding, and introduced identifiers are renamed
+/// to aliases carrying the mark of the expansion.
+struct RulesContext<'a> {
+    rules: &'a SyntaxRules,
+    identifiers: &'a mut Identifiers,
+    /// The scope of the macro use.
+    scope: &'a Rc<Scope>,
+    mark: usize,
+}
+
+impl<'a> Context for RulesContext<'a> {
+    fn is_ellipsis(&self, atom: &str) -> bool {
+        !self.is_literal(atom)
+            && self.identifiers.base(atom)
+                == self.identifiers.base(&self.rules.ellipsis)
+    }
+
+    fn is_literal(&self, atom: &str) -> bool {
+        self.rules.literals.iter().any(|literal| literal == atom)
+    }
+
+    fn is_underscore(&self, atom: &str) -> bool {
+        !self.is_literal(atom) && self.identifiers.base(atom) == "_"
+    }
+
+    fn matches_literal(&self, identifier: &str, literal: &str) -> bool {
+        self.identifiers.same_binding(
+            identifier,
+            self.scope,
+            literal,
+            &self.rules.scope,
+        )
+    }
+
+    fn rename(&mut self, atom: &str) -> String {
+        self.identifiers
+            .marked_alias(atom, &self.rules.scope, self.mark)
+    }
+}
+
+/// The identifiers of `syntax-case` patterns and `syntax` templates, whose
+/// ellipses and underscores the expander has normalized. Literals are
+/// compared with `free-identifier=?`, and templates are copied as they are:
+/// the expander renames what transformers return.
+pub struct CaseContext<'a> {
+    pub literals: &'a [String],
+    pub identifiers: &'a Identifiers,
+}
+
+impl<'a> Context for CaseContext<'a> {
+    fn is_ellipsis(&self, atom: &str) -> bool {
+        atom == "..." && !self.is_literal(atom)
+    }
+
+    fn is_literal(&self, atom: &str) -> bool {
+        self.literals.iter().any(|literal| literal == atom)
+    }
+
+    fn is_underscore(&self, atom: &str) -> bool {
+        atom == "_" && !self.is_literal(atom)
+    }
+
+    fn matches_literal(&self, identifier: &str, literal: &str) -> bool {
+        self.identifiers.free_identifier_eq(identifier, literal)
+    }
+
+    fn rename(&mut self, atom: &str) -> String {
+        atom.to_owned()
+    }
+}
+
+/// Matches `form` against `pattern`, binding its pattern variables.
+pub fn match_pattern<C: Context>(
+    pattern: &AST,
+    form: &AST,
+    context: &C,
+    bindings: &mut Bindings,
+) -> bool {
+    Matcher(context).pattern(pattern, form, bindings)
+}
+
+/// The pattern variables of `pattern`, in order of appearance, along with the
+/// number of ellipses following each of them.
+pub fn pattern_variables<C: Context>(
+    pattern: &AST,
+    context: &C,
+) -> Vec<(String, usize)> {
+    let mut variables = vec![];
+    collect_variables(pattern, 0, context, &mut variables);
+    variables
+}
+
+fn collect_variables<C: Context>(
+    pattern: &AST,
+    depth: usize,
+    context: &C,
+    variables: &mut Vec<(String, usize)>,
+) {
+    let collect_items = |items: &[AST], variables: &mut Vec<(String, usize)>| {
+        for (i, item) in items.iter().enumerate() {
+            let repeated = match items.get(i + 1) {
+                Some(Atom(ref next)) => context.is_ellipsis(next),
+                _ => false,
+            };
+            collect_variables(
+                item,
+                depth + repeated as usize,
+                context,
+                variables,
+            );
+        }
+    };
+
+    match *pattern {
+        Atom(ref atom)
+            if context.is_pattern_variable(atom)
+                && !variables.iter().any(|(variable, _)| variable == atom) =>
+        {
+            variables.push((atom.clone(), depth));
+        }
+        List(ref items) | Vector(ref items) => collect_items(items, variables),
+        DottedList(ref items, ref tail) => {
+            collect_items(items, variables);
+            collect_variables(tail, depth, context, variables);
+        }
+        _ => (),
+    }
+}
+
+/// Instantiates `template`, replacing its pattern variables with what they
+/// matched.
+pub fn instantiate<C: Context>(
+    template: &AST,
+    bindings: &Bindings,
+    context: &mut C,
+) -> Result<AST, RuntimeError> {
+    Template { context }.instantiate(template, bindings, true)
+}
+
+/// Matches the forms of a macro use against patterns.
+struct Matcher<'a, C: Context>(&'a C);
+
+impl<'a, C: Context> Matcher<'a, C> {
+    fn is_ellipsis(&self, ast: &AST) -> bool {
+        match *ast {
+            Atom(ref atom) => self.0.is_ellipsis(atom),
+            _ => false,
+        }
+    }
+
+    fn pattern(
+        &self,
+        pattern: &AST,
+        form: &AST,
+        bindings: &mut Bindings,
+    ) -> bool {
+        match *pattern {
+            Atom(ref atom) if self.0.is_literal(atom) => match *form {
+                Atom(ref identifier) => self.0.matches_literal(identifier, atom),
+                _ => false,
+            },
+            Atom(ref atom) if self.0.is_underscore(atom) => true,
+            Atom(ref atom) => {
+                bindings.insert(atom.clone(), Match::One(form.clone()));
+                true
+            }
+            List(ref patterns) => match *form {
+                List(ref items) => {
+                    self.items(patterns, None, items, None, bindings)
+                }
+                DottedList(ref items, ref tail) => {
+                    self.items(patterns, None, items, Some(tail), bindings)
+                }
+                _ => false,
+            },
+            DottedList(ref patterns, ref pattern_tail) => match *form {
+                List(ref items) => self.items(
+                    patterns,
+                    Some(pattern_tail),
+                    items,
+                    None,
+                    bindings,
+                ),
+                DottedList(ref items, ref tail) => self.items(
+                    patterns,
+                    Some(pattern_tail),
+                    items,
+                    Some(tail),
+                    bindings,
+                ),
+                _ => false,
+            },
+            Vector(ref patterns) => match *form {
+                Vector(ref items) => {
+                    self.items(patterns, None, items, None, bindings)
+                }
+                _ => false,
+            },
+            _ => pattern == form,
+        }
+    }
+
+    /// Matches a sequence of patterns, at most one of them followed by an
+    /// ellipsis, and an optional pattern for the rest of the forms.
+    fn items(
+        &self,
+        patterns: &[AST],
+        pattern_tail: Option<&AST>,
+        items: &[AST],
+        tail: Option<&AST>,
+        bindings: &mut Bindings,
+    ) -> bool {
+        let ellipsis = patterns
+            .iter()
+            .position(|pattern| self.is_ellipsis(pattern));
+
+        match ellipsis {
+            Some(0) => false,
+            Some(i) => {
+                let (before, repeated, after) =
+                    (&patterns[..i - 1], &patterns[i - 1], &patterns[i + 1..]);
+
+                if items.len() < before.len() + after.len() {
+                    return false;
+                }
+
+                let end = items.len() - after.len();
+                let mut matches = vec![];
+
+                for item in &items[before.len()..end] {
+                    let mut matched = Bindings::new();
+                    if !self.pattern(repeated, item, &mut matched) {
+                        return false;
+                    }
+                    matches.push(matched);
+                }
+
+                for (variable, _) in pattern_variables(repeated, self.0) {
+                    let repetitions = matches
+                        .iter()
+                        .map(|matched| matched[&variable].clone())
+                        .collect();
+                    bindings.insert(variable, Match::Many(repetitions));
+                }
+
+                self.all(before, &items[..before.len()], bindings)
+                    && self.all(after, &items[end..], bindings)
+                    && self.tail(pattern_tail, &[], tail, bindings)
+            }
+            None => {
+                if items.len() < patterns.len()
+                    || (pattern_tail.is_none() && items.len() != patterns.len())
+                {
+                    return false;
+                }
+
+                self.all(patterns, &items[..patterns.len()], bindings)
+                    && self.tail(
+                        pattern_tail,
+                        &items[patterns.len()..],
+                        tail,
+                        bindings,
+                    )
+            }
+        }
+    }
+
+    fn all(
+        &self,
+        patterns: &[AST],
+        items: &[AST],
+        bindings: &mut Bindings,
+    ) -> bool {
+        patterns
+            .iter()
+            .zip(items)
+            .all(|(pattern, item)| self.pattern(pattern, item, bindings))
+    }
+
+    fn tail(
+        &self,
+        pattern_tail: Option<&AST>,
+        rest: &[AST],
+        tail: Option<&AST>,
+        bindings: &mut Bindings,
+    ) -> bool {
+        match (pattern_tail, tail) {
+            (None, None) => rest.is_empty(),
+            (None, Some(..)) => false,
+            (Some(pattern), None) => {
+                self.pattern(pattern, &List(rest.to_vec()), bindings)
+            }
+            (Some(pattern), Some(tail)) if rest.is_empty() => {
+                self.pattern(pattern, tail, bindings)
+            }
+            (Some(pattern), Some(tail)) => self.pattern(
+                pattern,
+                &DottedList(rest.to_vec(), Box::new(tail.clone())),
+                bindings,
+            ),
+        }
+    }
+}
+
+/// Builds forms out of templates and what pattern variables matched.
+struct Template<'a, C: Context> {
+    context: &'a mut C,
+}
+
+impl<'a, C: Context> Template<'a, C> {
+    fn is_ellipsis(&self, ast: &AST) -> bool {
+        match *ast {
+            Atom(ref atom) => self.context.is_ellipsis(atom),
+            _ => false,
+        }
+    }
+
+    fn instantiate(
+        &mut self,
+        template: &AST,
+        bindings: &Bindings,
+        ellipsis: bool,
+    ) -> Result<AST, RuntimeError> {
+        match *template {
+            Atom(ref atom) => match bindings.get(atom) {
+                Some(Match::One(form)) => Ok(form.clone()),
+                Some(Match::Many(..)) => Err(RuntimeError::MalformedExpression),
+                None => Ok(Atom(self.context.rename(atom))),
+            },
+            // `(... template)` copies the ellipses in `template` verbatim.
+            List(ref templates)
+                if ellipsis
+                    && templates.len() == 2
+                    && self.is_ellipsis(&templates[0]) =>
+            {
+                self.instantiate(&templates[1], bindings, false)
+            }
+            List(ref templates) => {
+                self.items(templates, bindings, ellipsis).map(List)
+            }
+            DottedList(ref templates, ref tail) => {
+                let mut items = self.items(templates, bindings, ellipsis)?;
+
+                match self.instantiate(tail, bindings, ellipsis)? {
+                    List(rest) => {
+                        items.extend(rest);
+                        Ok(List(items))
+                    }
+                    DottedList(rest, tail) => {
+                        items.extend(rest);
+                        Ok(DottedList(items, tail))
+                    }
+                    tail => Ok(DottedList(items, Box::new(tail))),
+                }
+            }
+            Vector(ref templates) => {
+                self.items(templates, bindings, ellipsis).map(Vector)
+            }
+            _ => Ok(template.clone()),
+        }
+    }
+
+    fn items(
+        &mut self,
+        templates: &[AST],
+        bindings: &Bindings,
+        ellipsis: bool,
+    ) -> Result<Vec<AST>, RuntimeError> {
+        let mut items = vec![];
+        let mut i = 0;
+
+        while i < templates.len() {
+            let mut depth = 0;
+            while ellipsis
+                && i + depth + 1 < templates.len()
+                && self.is_ellipsis(&templates[i + depth + 1])
+            {
+                depth += 1;
+            }
+
+            if depth == 0 {
+                items.push(self.instantiate(
+                    &templates[i],
+                    bindings,
+                    ellipsis,
+                )?);
+            } else {
+                self.repeat(&templates[i], depth, bindings, &mut items)?;
+            }
+
+            i += depth + 1;
+        }
+
+        Ok(items)
+    }
+
+    /// Instantiates `template` once for each match of the pattern variables
+    /// it repeats, `depth` being the number of ellipses following it.
+    fn repeat(
+        &mut self,
+        template: &AST,
+        depth: usize,
+        bindings: &Bindings,
+        items: &mut Vec<AST>,
+    ) -> Result<(), RuntimeError> {
+        let mut atoms = vec![];
+        atoms_of(template, &mut atoms);
+
+        let repeated: Vec<(&String, &Vec<Match>)> = atoms
+            .iter()
+            .filter_map(|atom| match bindings.get(atom) {
+                Some(Match::Many(matches)) => Some((atom, matches)),
+                _ => None,
+            })
+            .collect();
+
+        let times = match repeated.first() {
+            Some((_, matches)) => matches.len(),
+            None => return Err(RuntimeError::MalformedExpression),
+        };
+
+        if repeated.iter().any(|(_, matches)| matches.len() != times) {
+            return Err(RuntimeError::MalformedExpression);
+        }
+
+        for n in 0..times {
+            let mut iteration = bindings.clone();
+            for (atom, matches) in &repeated {
+                iteration.insert((*atom).clone(), matches[n].clone());
+            }
+
+            if depth > 1 {
+                self.repeat(template, depth - 1, &iteration, items)?;
+            } else {
+                items.push(self.instantiate(template, &iteration, true)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn atoms_of(ast: &AST, atoms: &mut Vec<String>) {
+    match *ast {
+        Atom(ref atom) => atoms.push(atom.clone()),
+        List(ref items) | Vector(ref items) => {
+            items.iter().for_each(|item| atoms_of(item, atoms))
+        }
+        DottedList(ref items, ref tail) => {
+            items.iter().for_each(|item| atoms_of(item, atoms));
+            atoms_of(tail, atoms);
+        }
+        _ => (),
+    }
+}
@@ -1,6 +1,21 @@
-use crate::{ast::AST, env::CellEnv, instructions::Bytecode, memory::Memory};
+use crate::{
+    ast::AST,
+    enumerations,
+    env::CellEnv,
+    expander::Scope,
+    hashtable::Hashtable,
+    instructions::Bytecode,
+    memory::Memory,
+    parameters::Parameter,
+    ports::Port,
+    promises::{PromiseBox, PromiseState},
+    records::{self, ConstructorDescriptor},
+};
 use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display, Error, Formatter},
+    mem,
     rc::Rc,
 };
 
@@ -10,9 +25,16 @@ pub type RcValue = Rc<Value>;
 pub enum Value {
     Atom(String),
     Bool(bool),
+    Char(char),
     Nil,
     Unspecified,
-    Pair(RcValue, RcValue),
+    /// Value of variables declared by `letrec` until they are initialized.
+    Uninitialized,
+    Pair {
+        car: RefCell<RcValue>,
+        cdr: RefCell<RcValue>,
+        mutable: bool,
+    },
     Closure {
         name: Option<String>,
         args_type: ArgumentsType,
@@ -20,8 +42,39 @@ pub enum Value {
         closure: CellEnv,
         code: Bytecode,
     },
+    /// A `case-lambda` procedure: the closures of its clauses, tried in
+    /// order against the number of arguments.
+    CaseLambda(Vec<RcValue>),
     PrimitiveFn(String),
     Integer(i64),
+    Str {
+        chars: RefCell<Vec<char>>,
+        mutable: bool,
+    },
+    Vector {
+        items: RefCell<Vec<RcValue>>,
+        mutable: bool,
+    },
+    Hashtable(RefCell<Hashtable>),
+    RecordTypeDescriptor(Rc<records::RecordType>),
+    RecordConstructorDescriptor(Rc<ConstructorDescriptor>),
+    Record {
+        rtd: Rc<records::RecordType>,
+        fields: RefCell<Vec<RcValue>>,
+    },
+    RecordProcedure(records::RecordProcedure),
+    Condition(Vec<RcValue>),
+    EnumSet(enumerations::EnumSet),
+    EnumSetProcedure(enumerations::EnumSetProcedure),
+    Promise(RefCell<PromiseBox>),
+    Parameter(Parameter),
+    Port(Port),
+    /// Zero or several values returned to a continuation by `values`.
+    Values(Vec<RcValue>),
+    /// A form along with the lexical context of its identifiers.
+    Syntax(AST),
+    /// A top level `eval` can evaluate forms in.
+    Environment(Rc<Scope>),
 }
 
 use self::Value::*;
@@ -35,33 +88,193 @@ pub enum ArgumentsType {
 
 use self::ArgumentsType::*;
 
+thread_local! {
+    /// What the tail of a value being dropped is replaced with.
+    static DETACHED: RcValue = Rc::new(Nil);
+}
+
+impl Drop for Value {
+    /// Lists and forced streams are dropped one element at a time: the tail
+    /// of each value dropped is detached and dropped next, rather than from
+    /// within its own drop, so that long ones don't overflow the stack.
+    fn drop(&mut self) {
+        let mut tail = self.detach_tail();
+
+        while let Some(value) = tail {
+            tail = value.detach_tail();
+        }
+    }
+}
+
 impl Value {
-    pub fn is_list(&self) -> bool {
+    /// The cdr of a pair, or the value of a forced promise, if nothing else
+    /// holds it. It is replaced, so that this value no longer owns it.
+    fn detach_tail(&self) -> Option<RcValue> {
+        fn take_unique(slot: &mut RcValue) -> Option<RcValue> {
+            if Rc::strong_count(slot) != 1 {
+                return None;
+            }
+
+            Some(mem::replace(slot, DETACHED.with(Rc::clone)))
+        }
+
         match *self {
-            Nil => true,
-            Pair(ref _left, ref right) => right.is_list(),
-            _ => false,
+            Pair { ref cdr, .. } => take_unique(&mut cdr.borrow_mut()),
+            Promise(ref promise) => {
+                let promise = promise.borrow();
+                if Rc::strong_count(&promise) != 1 {
+                    return None;
+                }
+
+                let mut state = promise.borrow_mut();
+                match *state {
+                    PromiseState::Forced(ref mut value) => take_unique(value),
+                    PromiseState::Pending(..) => None,
+                }
+            }
+            _ => None,
         }
     }
 
+    pub fn pair(car: RcValue, cdr: RcValue, mutable: bool) -> Value {
+        Pair {
+            car: RefCell::new(car),
+            cdr: RefCell::new(cdr),
+            mutable,
+        }
+    }
+
+    pub fn string(chars: Vec<char>, mutable: bool) -> Value {
+        Str {
+            chars: RefCell::new(chars),
+            mutable,
+        }
+    }
+
+    pub fn vector(items: Vec<RcValue>, mutable: bool) -> Value {
+        Vector {
+            items: RefCell::new(items),
+            mutable,
+        }
+    }
+
+    pub fn car(&self) -> Option<RcValue> {
+        match *self {
+            Pair { ref car, .. } => Some(car.borrow().clone()),
+            _ => None,
+        }
+    }
+
+    pub fn cdr(&self) -> Option<RcValue> {
+        match *self {
+            Pair { ref cdr, .. } => Some(cdr.borrow().clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.pair_len().is_some()
+    }
+
     pub fn is_pair(&self) -> bool {
-        if let Pair(..) = *self {
-            true
-        } else {
-            false
+        matches!(*self, Pair { .. })
+    }
+
+    /// The values this value stands for when passed to a continuation: the
+    /// components of a `Values`, or the value itself.
+    pub fn values(value: &RcValue) -> Vec<RcValue> {
+        match **value {
+            Values(ref values) => values.clone(),
+            _ => vec![value.clone()],
         }
     }
 
+    pub fn is_promise(&self) -> bool {
+        matches!(*self, Promise(..))
+    }
+
+    pub fn is_procedure(&self) -> bool {
+        matches!(
+            *self,
+            Closure { .. }
+                | CaseLambda(..)
+                | PrimitiveFn(..)
+                | RecordProcedure(..)
+                | EnumSetProcedure(..)
+                | Parameter(..)
+        )
+    }
+
+    /// The number of arguments a closure requires, and whether it accepts
+    /// more.
+    pub fn arity(&self) -> Option<(usize, bool)> {
+        match *self {
+            Closure {
+                args_type: Fixed,
+                ref args,
+                ..
+            } => Some((args.len(), false)),
+            Closure {
+                args_type: Variable,
+                ref args,
+                ..
+            } => Some((args.len() - 1, true)),
+            Closure { args_type: Any, .. } => Some((0, true)),
+            _ => None,
+        }
+    }
+
+    pub fn is_false(&self) -> bool {
+        matches!(*self, Bool(false))
+    }
+
+    pub fn is_nil(&self) -> bool {
+        matches!(*self, Nil)
+    }
+
+    /// Elements of a proper list, `None` for anything else.
+    pub fn list_to_vec(&self) -> Option<Vec<RcValue>> {
+        self.pair_len()?;
+
+        let mut items = Vec::new();
+        let mut rest = self.cdr();
+        items.extend(self.car());
+
+        while let Some(pair) = rest {
+            items.extend(pair.car());
+            rest = pair.cdr();
+        }
+
+        Some(items)
+    }
+
+    /// Length of a proper list, `None` for anything else, circular lists
+    /// included.
     pub fn pair_len(&self) -> Option<i64> {
-        fn pair_len_rec(val: &Value, acc: i64) -> Option<i64> {
-            match *val {
-                Nil => Some(acc),
-                Pair(ref _left, ref right) => pair_len_rec(right, acc + 1),
-                _ => None,
+        let mut fast = match *self {
+            Nil => return Some(0),
+            Pair { ref cdr, .. } => cdr.borrow().clone(),
+            _ => return None,
+        };
+        let mut slow = fast.clone();
+        let mut len = 1;
+
+        loop {
+            for _ in 0..2 {
+                fast = match *fast {
+                    Nil => return Some(len),
+                    Pair { ref cdr, .. } => cdr.borrow().clone(),
+                    _ => return None,
+                };
+                len += 1;
             }
-        }
 
-        pair_len_rec(self, 0)
+            slow = slow.cdr().unwrap();
+
+            if Rc::ptr_eq(&slow, &fast) {
+                return None;
+            }
+        }
     }
 }
 
@@ -80,30 +293,151 @@ fn fmt_join_with_spaces<T: Display>(
     Ok(())
 }
 
-fn fmt_list<T: Display>(items: &[T], f: &mut Formatter) -> Result<(), Error> {
+pub(crate) fn fmt_list<T: Display>(
+    items: &[T],
+    f: &mut Formatter,
+) -> Result<(), Error> {
     write!(f, "(")?;
     fmt_join_with_spaces(items, f)?;
     write!(f, ")")
 }
 
-fn fmt_pair(
-    left: &RcValue,
-    right: &RcValue,
+type Labels = HashMap<*const Value, Option<usize>>;
+
+/// The values that `value` holds and that are printed as part of it.
+fn printed_children(value: &Value) -> Vec<RcValue> {
+    match *value {
+        Pair {
+            ref car, ref cdr, ..
+        } => vec![car.borrow().clone(), cdr.borrow().clone()],
+        Vector { ref items, .. } => items.borrow().clone(),
+        Record {
+            ref rtd,
+            ref fields,
+        } if !rtd.is_opaque() => fields.borrow().clone(),
+        _ => vec![],
+    }
+}
+
+/// Finds the pairs, vectors and records reachable from `root` that close a
+/// cycle, so that they can be printed with datum labels (`#0=(a . #0#)`).
+fn cyclic_data(root: &Value) -> Labels {
+    let mut labels = Labels::new();
+    let mut on_path = HashSet::new();
+    let mut done = HashSet::new();
+
+    on_path.insert(root as *const Value);
+    done.insert(root as *const Value);
+
+    let mut stack = vec![];
+    for child in printed_children(root).into_iter().rev() {
+        stack.push((child, false));
+    }
+
+    while let Some((value, exiting)) = stack.pop() {
+        let ptr = Rc::as_ptr(&value);
+
+        if exiting {
+            on_path.remove(&ptr);
+            continue;
+        }
+
+        let children = printed_children(&value);
+        if children.is_empty() {
+            continue;
+        }
+
+        if on_path.contains(&ptr) {
+            labels.insert(ptr, None);
+            continue;
+        }
+
+        if !done.insert(ptr) {
+            continue;
+        }
+
+        on_path.insert(ptr);
+        stack.push((value.clone(), true));
+        for child in children.into_iter().rev() {
+            stack.push((child, false));
+        }
+    }
+
+    labels
+}
+
+fn fmt_datum(
+    value: &Value,
+    labels: &mut Labels,
+    next_label: &mut usize,
     f: &mut Formatter,
 ) -> Result<(), Error> {
-    write!(f, "{}", left)?;
+    match *value {
+        Pair { .. } | Vector { .. } | Record { .. } => {}
+        _ => return write!(f, "{}", value),
+    }
 
-    match **right {
-        Nil => Ok(()),
-        Pair(ref left, ref right) => {
-            write!(f, " ")?;
-            fmt_pair(left, right, f)
+    if let Some(label) = labels.get_mut(&(value as *const Value)) {
+        match *label {
+            Some(n) => return write!(f, "#{}#", n),
+            None => {
+                *label = Some(*next_label);
+                write!(f, "#{}=", next_label)?;
+                *next_label += 1;
+            }
         }
-        _ => write!(f, " . {}", right),
     }
+
+    let (car, cdr) = match *value {
+        Pair {
+            ref car, ref cdr, ..
+        } => (car.borrow().clone(), cdr.borrow().clone()),
+        Vector { ref items, .. } => {
+            let items = items.borrow().clone();
+            return fmt_vector(&items, labels, next_label, f);
+        }
+        Record {
+            ref rtd,
+            ref fields,
+        } => {
+            let fields = fields.borrow().clone();
+            return fmt_record(rtd, &fields, labels, next_label, f);
+        }
+        _ => unreachable!(),
+    };
+
+    write!(f, "(")?;
+    fmt_datum(&car, labels, next_label, f)?;
+
+    let mut rest = cdr;
+    loop {
+        let next = match *rest {
+            Nil => break,
+            Pair { .. } if labels.contains_key(&Rc::as_ptr(&rest)) => {
+                write!(f, " . ")?;
+                fmt_datum(&rest, labels, next_label, f)?;
+                break;
+            }
+            Pair {
+                ref car, ref cdr, ..
+            } => {
+                write!(f, " ")?;
+                fmt_datum(&car.borrow(), labels, next_label, f)?;
+                cdr.borrow().clone()
+            }
+            _ => {
+                write!(f, " . {}", rest)?;
+                break;
+            }
+        };
+
+        rest = next;
+    }
+
+    write!(f, ")")
 }
 
-fn fmt_dotted_list<T: Display>(
+pub(crate) fn fmt_dotted_list<T: Display>(
     items: &[T],
     right: &T,
     f: &mut Formatter,
@@ -113,6 +447,79 @@ fn fmt_dotted_list<T: Display>(
     write!(f, " . {})", right)
 }
 
+pub(crate) fn fmt_char(c: char, f: &mut Formatter) -> Result<(), Error> {
+    match c {
+        '\x07' => write!(f, "#\\alarm"),
+        '\x08' => write!(f, "#\\backspace"),
+        '\x7f' => write!(f, "#\\delete"),
+        '\x1b' => write!(f, "#\\escape"),
+        '\n' => write!(f, "#\\newline"),
+        '\0' => write!(f, "#\\nul"),
+        '\x0c' => write!(f, "#\\page"),
+        '\r' => write!(f, "#\\return"),
+        ' ' => write!(f, "#\\space"),
+        '\t' => write!(f, "#\\tab"),
+        '\x0b' => write!(f, "#\\vtab"),
+        c if c.is_control() => write!(f, "#\\x{:x}", c as u32),
+        c => write!(f, "#\\{}", c),
+    }
+}
+
+pub(crate) fn fmt_string(chars: &[char], f: &mut Formatter) -> Result<(), Error> {
+    write!(f, "\"")?;
+
+    for &c in chars {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            '\r' => write!(f, "\\r")?,
+            c if c.is_control() => write!(f, "\\x{:x};", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+fn fmt_vector(
+    items: &[RcValue],
+    labels: &mut Labels,
+    next_label: &mut usize,
+    f: &mut Formatter,
+) -> Result<(), Error> {
+    write!(f, "#(")?;
+
+    for (i, item) in items.iter().enumerate() {
+        if i != 0 {
+            write!(f, " ")?;
+        }
+        fmt_datum(item, labels, next_label, f)?;
+    }
+
+    write!(f, ")")
+}
+
+fn fmt_record(
+    rtd: &records::RecordType,
+    fields: &[RcValue],
+    labels: &mut Labels,
+    next_label: &mut usize,
+    f: &mut Formatter,
+) -> Result<(), Error> {
+    write!(f, "#<{}", rtd.name)?;
+
+    if !rtd.is_opaque() {
+        for (field, value) in rtd.all_fields().iter().zip(fields) {
+            write!(f, " {}: ", field.name)?;
+            fmt_datum(value, labels, next_label, f)?;
+        }
+    }
+
+    write!(f, ">")
+}
+
 fn fmt_primitive(name: &str, f: &mut Formatter) -> Result<(), Error> {
     write!(f, "<primitive procedure {}>", name)
 }
@@ -135,14 +542,38 @@ fn fmt_procedure(
     };
 
     write!(f, " ")?;
+    fmt_formals(args_type, args, f)?;
+    write!(f, ">")
+}
 
+fn fmt_formals(
+    args_type: ArgumentsType,
+    args: &[String],
+    f: &mut Formatter,
+) -> Result<(), Error> {
     match args_type {
-        Any => write!(f, "{}", args[0])?,
-        Fixed => fmt_list(args, f)?,
+        Any => write!(f, "{}", args[0]),
+        Fixed => fmt_list(args, f),
         Variable => {
             let head = &args[0..args.len() - 1];
             let tail = args.last().unwrap();
-            fmt_dotted_list(head, tail, f)?;
+            fmt_dotted_list(head, tail, f)
+        }
+    }
+}
+
+fn fmt_case_lambda(clauses: &[RcValue], f: &mut Formatter) -> Result<(), Error> {
+    write!(f, "<case-lambda")?;
+
+    for clause in clauses {
+        if let Closure {
+            args_type,
+            ref args,
+            ..
+        } = **clause
+        {
+            write!(f, " ")?;
+            fmt_formals(args_type, args, f)?;
         }
     }
 
@@ -155,12 +586,13 @@ impl Display for Value {
             Atom(ref string) => write!(f, "{}", string),
             Bool(false) => write!(f, "#f"),
             Bool(true) => write!(f, "#t"),
+            Char(c) => fmt_char(c, f),
             Nil => write!(f, "()"),
             Unspecified => write!(f, "<unspecified>"),
-            Pair(ref left, ref right) => {
-                write!(f, "(")?;
-                fmt_pair(left, right, f)?;
-                write!(f, ")")
+            Uninitialized => write!(f, "<uninitialized>"),
+            Pair { .. } | Vector { .. } | Record { .. } => {
+                let mut labels = cyclic_data(self);
+                fmt_datum(self, &mut labels, &mut 0, f)
             }
             Closure {
                 ref name,
@@ -168,8 +600,43 @@ impl Display for Value {
                 ref args,
                 ..
             } => fmt_procedure(name, *args_type, args, f),
+            CaseLambda(ref clauses) => fmt_case_lambda(clauses, f),
             Integer(ref i) => write!(f, "{}", i),
             PrimitiveFn(ref name) => fmt_primitive(name, f),
+            Str { ref chars, .. } => fmt_string(&chars.borrow(), f),
+            Hashtable(..) => write!(f, "<hashtable>"),
+            RecordTypeDescriptor(ref rtd) => {
+                write!(f, "<record type {}>", rtd.name)
+            }
+            RecordConstructorDescriptor(ref descriptor) => {
+                write!(
+                    f,
+                    "<record constructor descriptor {}>",
+                    descriptor.rtd.name
+                )
+            }
+            RecordProcedure(ref procedure) => {
+                write!(f, "<procedure {}>", procedure.name())
+            }
+            Condition(ref components) => {
+                write!(f, "#<condition ")?;
+                fmt_join_with_spaces(components, f)?;
+                write!(f, ">")
+            }
+            EnumSet(ref set) => {
+                write!(f, "#<enum-set ")?;
+                fmt_list(&set.symbols(), f)?;
+                write!(f, ">")
+            }
+            EnumSetProcedure(ref procedure) => {
+                write!(f, "<procedure {}>", procedure.name())
+            }
+            Promise(..) => write!(f, "<promise>"),
+            Parameter(..) => write!(f, "<parameter>"),
+            Port(ref port) => write!(f, "<port {}>", port.name()),
+            Values(ref values) => fmt_join_with_spaces(values, f),
+            Syntax(..) => write!(f, "#<syntax>"),
+            Environment(..) => write!(f, "#<environment>"),
         }
     }
 }
@@ -179,12 +646,31 @@ impl Debug for Value {
         let t = match *self {
             Atom(..) => "Atom",
             Bool(..) => "Bool",
+            Char(..) => "Char",
             Integer(..) => "Integer",
             Nil => "Nil",
             Unspecified => "Unspecified",
-            Pair(..) => "Pair",
+            Uninitialized => "Uninitialized",
+            Pair { .. } => "Pair",
             PrimitiveFn(..) => "PrimitiveFn",
             Closure { .. } => "Closure",
+            CaseLambda(..) => "CaseLambda",
+            Str { .. } => "Str",
+            Vector { .. } => "Vector",
+            Hashtable(..) => "Hashtable",
+            RecordTypeDescriptor(..) => "RecordTypeDescriptor",
+            RecordConstructorDescriptor(..) => "RecordConstructorDescriptor",
+            Record { .. } => "Record",
+            RecordProcedure(..) => "RecordProcedure",
+            Condition(..) => "Condition",
+            EnumSet(..) => "EnumSet",
+            EnumSetProcedure(..) => "EnumSetProcedure",
+            Promise(..) => "Promise",
+            Parameter(..) => "Parameter",
+            Port(..) => "Port",
+            Values(..) => "Values",
+            Syntax(..) => "Syntax",
+            Environment(..) => "Environment",
         };
 
         write!(f, "{}({})", t, self)
@@ -192,22 +678,177 @@ impl Debug for Value {
 }
 
 impl PartialEq for Value {
+    /// Structural equality. Pairs, vectors and records already being compared
+    /// are assumed equal, so comparing cyclic structures terminates.
     fn eq(&self, other: &Value) -> bool {
-        match (self, other) {
-            (&Atom(ref a), &Atom(ref b)) if a == b => true,
-            (&Bool(ref a), &Bool(ref b)) if a == b => true,
-            (&Integer(ref a), &Integer(ref b)) if a == b => true,
-            (&Nil, &Nil) => true,
-            (&Unspecified, &Unspecified) => true,
-            (&Pair(ref left1, ref right1), &Pair(ref left2, ref right2))
-                if (left1, right1) == (left2, right2) =>
-            {
-                true
+        same(self, other, &mut HashSet::new())
+    }
+}
+
+fn same(
+    a: &Value,
+    b: &Value,
+    visited: &mut HashSet<(*const Value, *const Value)>,
+) -> bool {
+    match (a, b) {
+        (Atom(a), Atom(b)) if a == b => true,
+        (Bool(a), Bool(b)) if a == b => true,
+        (Char(a), Char(b)) if a == b => true,
+        (Integer(a), Integer(b)) if a == b => true,
+        (Nil, Nil) => true,
+        (Unspecified, Unspecified) => true,
+        (
+            Pair {
+                car: left1,
+                cdr: right1,
+                ..
+            },
+            Pair {
+                car: left2,
+                cdr: right2,
+                ..
+            },
+        ) => {
+            !visited.insert((a as *const Value, b as *const Value))
+                || same(&left1.borrow(), &left2.borrow(), visited)
+                    && same(&right1.borrow(), &right2.borrow(), visited)
+        }
+        (PrimitiveFn(a), PrimitiveFn(b)) if a == b => true,
+        (Str { chars: a, .. }, Str { chars: b, .. }) if a == b => true,
+        (Vector { items: items1, .. }, Vector { items: items2, .. }) => {
+            !visited.insert((a as *const Value, b as *const Value))
+                || all_same(&items1.borrow(), &items2.borrow(), visited)
+        }
+        (RecordTypeDescriptor(a), RecordTypeDescriptor(b)) => Rc::ptr_eq(a, b),
+        (RecordConstructorDescriptor(a), RecordConstructorDescriptor(b)) => {
+            Rc::ptr_eq(a, b)
+        }
+        (
+            Record {
+                rtd: rtd1,
+                fields: fields1,
+            },
+            Record {
+                rtd: rtd2,
+                fields: fields2,
+            },
+        ) => {
+            Rc::ptr_eq(rtd1, rtd2)
+                && (!visited.insert((a as *const Value, b as *const Value))
+                    || all_same(&fields1.borrow(), &fields2.borrow(), visited))
+        }
+        (Values(a), Values(b)) => all_same(a, b, visited),
+        (Syntax(a), Syntax(b)) => a == b,
+        (Environment(a), Environment(b)) => Rc::ptr_eq(a, b),
+        (EnumSet(a), EnumSet(b)) => a.same_universe(b) && a.members == b.members,
+        _ => false,
+    }
+}
+
+fn all_same(
+    a: &[RcValue],
+    b: &[RcValue],
+    visited: &mut HashSet<(*const Value, *const Value)>,
+) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b, visited))
+}
+
+/// Address identifying the object behind `value`. Record descriptors are
+/// wrapped in a new value each time they are handed back to Scheme, so they
+/// are identified by the descriptor they wrap.
+pub fn identity(value: &RcValue) -> *const () {
+    match **value {
+        RecordTypeDescriptor(ref rtd) => Rc::as_ptr(rtd) as *const (),
+        RecordConstructorDescriptor(ref descriptor) => {
+            Rc::as_ptr(descriptor) as *const ()
+        }
+        _ => Rc::as_ptr(value) as *const (),
+    }
+}
+
+/// `eq?`: two values are the same object.
+pub fn eq(a: &RcValue, b: &RcValue) -> bool {
+    identity(a) == identity(b)
+}
+
+/// `eqv?`: `eq?`, plus numbers and characters compared by value.
+pub fn eqv(a: &RcValue, b: &RcValue) -> bool {
+    if eq(a, b) {
+        return true;
+    }
+
+    match (&**a, &**b) {
+        (Integer(a), Integer(b)) => a == b,
+        (Char(a), Char(b)) => a == b,
+        (Atom(a), Atom(b)) => a == b,
+        (Bool(a), Bool(b)) => a == b,
+        (Nil, Nil) | (Unspecified, Unspecified) => true,
+        _ => false,
+    }
+}
+
+/// `equal?`: `eqv?`, recursing into pairs. Pairs of pairs that are already
+/// being compared are assumed equal, so comparing cyclic structures
+/// terminates. Strings are compared by content.
+pub fn equal(a: &RcValue, b: &RcValue) -> bool {
+    /// Recurses into the cars of pairs, but loops over their cdrs, so that
+    /// long lists don't overflow the stack.
+    fn equal_rec(
+        a: &RcValue,
+        b: &RcValue,
+        visited: &mut HashSet<(*const Value, *const Value)>,
+    ) -> bool {
+        let (mut a, mut b) = (a.clone(), b.clone());
+
+        loop {
+            if eqv(&a, &b) {
+                return true;
             }
-            (&PrimitiveFn(ref a), &PrimitiveFn(ref b)) if a == b => true,
-            _ => false,
+
+            let (next_a, next_b) = match (&*a, &*b) {
+                (
+                    Pair {
+                        car: left1,
+                        cdr: right1,
+                        ..
+                    },
+                    Pair {
+                        car: left2,
+                        cdr: right2,
+                        ..
+                    },
+                ) => {
+                    if !visited.insert((Rc::as_ptr(&a), Rc::as_ptr(&b))) {
+                        return true;
+                    }
+                    if !equal_rec(&left1.borrow(), &left2.borrow(), visited) {
+                        return false;
+                    }
+
+                    (right1.borrow().clone(), right2.borrow().clone())
+                }
+                (Str { chars: a, .. }, Str { chars: b, .. }) => return a == b,
+                (Vector { items: items1, .. }, Vector { items: items2, .. }) => {
+                    if !visited.insert((Rc::as_ptr(&a), Rc::as_ptr(&b))) {
+                        return true;
+                    }
+
+                    let (items1, items2) = (items1.borrow(), items2.borrow());
+                    return items1.len() == items2.len()
+                        && items1
+                            .iter()
+                            .zip(items2.iter())
+                            .all(|(a, b)| equal_rec(a, b, visited));
+                }
+                _ => return false,
+            };
+
+            a = next_a;
+            b = next_b;
         }
     }
+
+    equal_rec(a, b, &mut HashSet::new())
 }
 
 impl Value {
@@ -215,31 +856,84 @@ impl Value {
         match *ast {
             AST::Atom(ref string) => mem.intern(string.to_owned()),
             AST::Bool(b) => mem.boolean(b),
+            AST::Char(c) => mem.character(c),
             AST::Integer(i) => mem.integer(i),
-            AST::List(ref list) => {
+            AST::Str(ref string) => mem.literal_string(string.chars().collect()),
+            AST::Vector(ref items) => {
                 let values =
+                    items.iter().map(|ast| Value::from_ast(ast, mem)).collect();
+                mem.literal_vector(values)
+            }
+            AST::List(ref list) => {
+                let values: Vec<RcValue> =
                     list.iter().map(|ast| Value::from_ast(ast, mem)).collect();
-                mem.list(values)
+                values.iter().rev().fold(mem.nil(), |cdr, car| {
+                    mem.literal_pair(car.clone(), cdr)
+                })
             }
             AST::DottedList(ref list, ref value) => {
                 let values: Vec<RcValue> =
                     list.iter().map(|ast| Value::from_ast(ast, mem)).collect();
-                let value = Value::from_ast(&*value.clone(), mem);
+                let value = Value::from_ast(value, mem);
                 values
                     .iter()
                     .rev()
-                    .fold(value, |cdr, car| mem.pair(car.clone(), cdr))
+                    .fold(value, |cdr, car| mem.literal_pair(car.clone(), cdr))
+            }
+        }
+    }
+
+    /// The form `self` is a datum of, syntax objects standing for the forms
+    /// they wrap. Values with no written form have none.
+    pub fn to_ast(&self) -> Option<AST> {
+        match *self {
+            Syntax(ref form) => Some(form.clone()),
+            Atom(ref string) => Some(AST::Atom(string.clone())),
+            Bool(b) => Some(AST::Bool(b)),
+            Char(c) => Some(AST::Char(c)),
+            Integer(i) => Some(AST::Integer(i)),
+            Str { ref chars, .. } => {
+                Some(AST::Str(chars.borrow().iter().collect()))
+            }
+            Nil => Some(AST::List(vec![])),
+            Vector { ref items, .. } => items
+                .borrow()
+                .iter()
+                .map(|item| item.to_ast())
+                .collect::<Option<_>>()
+                .map(AST::Vector),
+            Pair {
+                ref car, ref cdr, ..
+            } => {
+                let mut items = vec![car.borrow().to_ast()?];
+
+                match cdr.borrow().to_ast()? {
+                    AST::List(rest) => {
+                        items.extend(rest);
+                        Some(AST::List(items))
+                    }
+                    AST::DottedList(rest, tail) => {
+                        items.extend(rest);
+                        Some(AST::DottedList(items, tail))
+                    }
+                    tail => Some(AST::DottedList(items, Box::new(tail))),
+                }
             }
+            _ => None,
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{ArgumentsType::*, Value::*};
+    use super::{ArgumentsType::*, RcValue, Value, Value::*};
     use crate::env::CellEnv;
     use std::rc::Rc;
 
+    fn pair(car: RcValue, cdr: RcValue) -> Value {
+        Value::pair(car, cdr, true)
+    }
+
     macro_rules! assert_fmt {
         ($s:expr, $v:expr) => {
             assert_eq!($s, format!("{}", $v));
@@ -263,6 +957,25 @@ mod test {
         assert_fmt!("->", Atom("->".into()));
     }
 
+    #[test]
+    fn characters() {
+        assert_fmt!("#\\a", Char('a'));
+        assert_fmt!("#\\λ", Char('λ'));
+        assert_fmt!("#\\space", Char(' '));
+        assert_fmt!("#\\newline", Char('\n'));
+        assert_fmt!("#\\x1", Char('\x01'));
+    }
+
+    #[test]
+    fn strings() {
+        assert_fmt!("\"\"", Value::string(vec![], true));
+        assert_fmt!("\"abc\"", Value::string("abc".chars().collect(), true));
+        assert_fmt!(
+            r#""a\"b\\c\n""#,
+            Value::string("a\"b\\c\n".chars().collect(), true)
+        );
+    }
+
     #[test]
     fn nil() {
         assert_fmt!("()", Nil);
@@ -277,18 +990,18 @@ mod test {
     fn pairs() {
         assert_fmt!(
             "(+ 1 2 #f (1 2))",
-            Pair(
+            pair(
                 Rc::new(Atom("+".into())),
-                Rc::new(Pair(
+                Rc::new(pair(
                     Rc::new(Integer(1)),
-                    Rc::new(Pair(
+                    Rc::new(pair(
                         Rc::new(Integer(2)),
-                        Rc::new(Pair(
+                        Rc::new(pair(
                             Rc::new(Bool(false)),
-                            Rc::new(Pair(
-                                Rc::new(Pair(
+                            Rc::new(pair(
+                                Rc::new(pair(
                                     Rc::new(Integer(1)),
-                                    Rc::new(Pair(
+                                    Rc::new(pair(
                                         Rc::new(Integer(2)),
                                         Rc::new(Nil)
                                     ))
@@ -303,12 +1016,12 @@ mod test {
 
         assert_fmt!(
             "(+ (1 2) . a)",
-            Pair(
+            pair(
                 Rc::new(Atom("+".into())),
-                Rc::new(Pair(
-                    Rc::new(Pair(
+                Rc::new(pair(
+                    Rc::new(pair(
                         Rc::new(Integer(1)),
-                        Rc::new(Pair(Rc::new(Integer(2)), Rc::new(Nil))),
+                        Rc::new(pair(Rc::new(Integer(2)), Rc::new(Nil))),
                     )),
                     Rc::new(Atom("a".into()))
                 ))
@@ -316,6 +1029,52 @@ mod test {
         );
     }
 
+    #[test]
+    fn circular_pairs() {
+        let list = Rc::new(pair(
+            Rc::new(Integer(1)),
+            Rc::new(pair(Rc::new(Integer(2)), Rc::new(Nil))),
+        ));
+        if let Pair { ref cdr, .. } = *list.cdr().unwrap() {
+            *cdr.borrow_mut() = list.clone();
+        }
+        assert_fmt!("#0=(1 2 . #0#)", list);
+
+        let list = Rc::new(pair(Rc::new(Integer(1)), Rc::new(Nil)));
+        if let Pair { ref car, .. } = *list {
+            *car.borrow_mut() = list.clone();
+        }
+        assert_fmt!("#0=(#0#)", list);
+
+        let shared = Rc::new(pair(Rc::new(Integer(1)), Rc::new(Nil)));
+        assert_fmt!(
+            "((1) (1))",
+            pair(shared.clone(), Rc::new(pair(shared, Rc::new(Nil))))
+        );
+    }
+
+    #[test]
+    fn circular_vectors() {
+        let vector = Rc::new(Value::vector(vec![Rc::new(Integer(1))], true));
+        if let Vector { ref items, .. } = *vector {
+            items.borrow_mut()[0] = vector.clone();
+        }
+        assert_fmt!("#0=#(#0#)", vector);
+
+        let vector = Rc::new(Value::vector(vec![Rc::new(Nil)], true));
+        let list = Rc::new(pair(vector.clone(), Rc::new(Nil)));
+        if let Vector { ref items, .. } = *vector {
+            items.borrow_mut()[0] = list.clone();
+        }
+        assert_fmt!("#0=(#(#0#))", list);
+
+        let shared = Rc::new(Value::vector(vec![Rc::new(Integer(1))], true));
+        assert_fmt!(
+            "(#(1) #(1))",
+            pair(shared.clone(), Rc::new(pair(shared, Rc::new(Nil))))
+        );
+    }
+
     #[test]
     fn closures() {
         assert_fmt!(
@@ -1,13 +1,67 @@
 use crate::{
     ast::AST,
+    conditions::ConditionTypes,
     env::CellEnv,
     errors::RuntimeError,
+    expander::Identifiers,
     instructions::{Bytecode, Instruction},
+    libraries::Libraries,
     memory::Memory,
+    ports::Port,
     primitives,
+    promises::{Pending, PromiseState},
+    rnrs,
     values::{ArgumentsType, RcValue, Value},
 };
-use std::{collections::LinkedList, iter::FromIterator, mem};
+use std::{
+    collections::LinkedList, iter::FromIterator, mem, path::PathBuf, rc::Rc,
+};
+
+/// Built-in procedures that the code generated by the compiler and the
+/// expander calls by name. Programs and libraries see them whatever they
+/// import, so they all have `%`-prefixed names no program would use.
+static INTERNALS: [&str; 21] = [
+    "%assertion-violation",
+    "%call-with-values",
+    "%case-lambda",
+    "%cons",
+    "%enum-set-constructor",
+    "%guard",
+    "%list",
+    "%located",
+    "%make-enumeration",
+    "%make-promise",
+    "%make-record-constructor-descriptor",
+    "%make-record-type-descriptor",
+    "%memv",
+    "%parameterize",
+    "%record-accessor",
+    "%record-constructor",
+    "%record-mutator",
+    "%record-predicate",
+    "%record-type-descriptor",
+    "%syntax",
+    "%syntax-case",
+];
+
+/// Built-in procedures that generated code calls through a `%`-prefixed alias,
+/// so that programs binding their names don't change what it does.
+static ALIASED: [&str; 14] = [
+    "assertion-violation",
+    "call-with-values",
+    "cons",
+    "enum-set-constructor",
+    "list",
+    "make-enumeration",
+    "make-promise",
+    "make-record-constructor-descriptor",
+    "make-record-type-descriptor",
+    "record-accessor",
+    "record-constructor",
+    "record-mutator",
+    "record-predicate",
+    "record-type-descriptor",
+];
 
 pub type Rib = Vec<RcValue>;
 pub type Stack = LinkedList<Frame>;
@@ -26,12 +80,41 @@ impl Frame {
     }
 }
 
+/// An exception handler, as installed by `with-exception-handler` or
+/// `guard`.
+pub enum Handler {
+    Procedure(RcValue),
+    /// The procedure evaluating the clauses of a `guard`, which takes the
+    /// object raised and a value to return if no clause accepts it, the
+    /// number of parameters bound where the `guard` was installed, and the
+    /// depth of the frame stack its body returns to.
+    Guard(RcValue, usize, usize),
+}
+
 pub struct VM {
     pub acc: RcValue,
     pub memory: Memory,
     pub rib: Rib,
     pub env: CellEnv,
+    /// The environment of primitives and other built-in bindings, around the
+    /// top-level environment of the REPL.
+    pub builtins: CellEnv,
+    /// The built-in procedures generated code calls, around the environments
+    /// of programs and libraries.
+    internals: CellEnv,
     pub stack: Stack,
+    pub conditions: ConditionTypes,
+    pub identifiers: Identifiers,
+    pub libraries: Libraries,
+    /// The files being loaded or included, innermost last, which relative
+    /// paths are resolved against.
+    pub loading: Vec<PathBuf>,
+    /// The exception handlers installed, innermost last.
+    pub handlers: Vec<Handler>,
+    /// The parameters bound by the `parameterize` forms being run, innermost
+    /// last, each with the value it had before.
+    pub parameterized: Vec<(RcValue, RcValue)>,
+    output_port: RcValue,
     instructions: Bytecode,
     pc: usize,
     code: Vec<(Bytecode, usize)>,
@@ -41,61 +124,217 @@ impl VM {
     pub fn new() -> VM {
         let memory = Memory::new();
 
+        let unspecified = memory.unspecified();
+        let builtins = CellEnv::new();
+        let env = CellEnv::wraps(builtins.clone());
+
         let mut vm = VM {
-            acc: memory.unspecified(),
+            acc: unspecified.clone(),
             memory,
             stack: Default::default(),
             rib: Default::default(),
-            env: CellEnv::new(),
+            env: env.clone(),
+            builtins: builtins.clone(),
+            internals: CellEnv::new(),
+            conditions: ConditionTypes::new(),
+            identifiers: Identifiers::new(builtins, env),
+            libraries: Libraries::new(),
+            loading: vec![],
+            handlers: vec![],
+            parameterized: vec![],
+            output_port: unspecified,
             instructions: Default::default(),
             pc: 0,
             code: Default::default(),
         };
 
         vm.init_primitives();
+        vm.init_conditions();
+        vm.init_streams();
+        vm.init_ports();
+        vm.init_libraries();
 
         vm
     }
 
+    /// A top-level environment for a program or a library, which doesn't see
+    /// the built-in bindings it doesn't import.
+    pub fn isolated_env(&self) -> CellEnv {
+        CellEnv::wraps(self.internals.clone())
+    }
+
     pub fn execute(
         &mut self,
         instructions: Bytecode,
     ) -> Result<RcValue, RuntimeError> {
+        let instructions = mem::replace(&mut self.instructions, instructions);
+        let pc = mem::replace(&mut self.pc, 0);
+        let (depth, stack_depth) = (self.code.len(), self.stack.len());
+
+        let result = self.run_until(depth);
+        if result.is_err() {
+            self.unwind(depth, stack_depth);
+        }
+
         self.instructions = instructions;
-        self.pc = 0;
+        self.pc = pc;
+
+        result.map(|()| self.acc.clone())
+    }
 
+    /// Applies `procedure` to `args` and runs it to completion. Primitives use
+    /// this to call back into Scheme procedures.
+    pub fn call(
+        &mut self,
+        procedure: RcValue,
+        args: Vec<RcValue>,
+    ) -> Result<RcValue, RuntimeError> {
+        let instructions = mem::take(&mut self.instructions);
+        let pc = mem::replace(&mut self.pc, 0);
+        let (depth, stack_depth) = (self.code.len(), self.stack.len());
+
+        self.push_frame();
+        self.rib = args;
+        self.acc = procedure;
+
+        let result = self.apply(false).and_then(|()| self.run_until(depth));
+        if result.is_err() {
+            self.unwind(depth, stack_depth);
+        }
+
+        self.instructions = instructions;
+        self.pc = pc;
+
+        result.map(|()| self.acc.clone())
+    }
+
+    /// Runs instructions until the current ones, and every procedure body
+    /// entered from them, are exhausted. Errors are raised to the exception
+    /// handlers where they happen, and the `guard`s escaped to from there
+    /// are resumed if they were entered from these instructions.
+    fn run_until(&mut self, depth: usize) -> Result<(), RuntimeError> {
         loop {
-            use crate::instructions::Instruction::*;
-
-            match self.next_instruction() {
-                Some(instr) => match instr {
-                    LoadConstant(ref value) => self.load_constant(value),
-                    Jump(offset) => self.jump(offset),
-                    JumpOnFalse(offset) => self.jump_on_false(offset),
-                    JumpOnTrue(offset) => self.jump_on_true(offset),
-                    LoadReference(ref reference) => {
-                        self.load_reference(reference)?
-                    }
-                    Assignment(ref reference) => self.assignment(reference),
-                    Replace(ref reference) => self.replace(reference)?,
-                    LoadUnspecified => self.load_unspecified(),
-                    Apply => self.apply()?,
-                    Argument => self.argument(),
-                    Frame => self.push_frame(),
-                    Close {
-                        ref args,
-                        ref args_type,
-                        ref body,
-                    } => self.push_closure(args, *args_type, body),
-                },
-                None => match self.pop_frame(true) {
-                    Ok(()) => (),
-                    Err(_) => break,
-                },
+            match self
+                .run_instructions(depth)
+                .map_err(|error| self.raise_error(error))
+            {
+                Err(RuntimeError::GuardEscape(target, ref value))
+                    if self.escape(target, value, depth) => {}
+                result => return result,
             }
         }
+    }
+
+    fn run_instructions(&mut self, depth: usize) -> Result<(), RuntimeError> {
+        while self.step(depth)? {}
 
-        Ok(self.acc.clone())
+        Ok(())
+    }
+
+    /// Runs the next instruction, returning whether there was one left before
+    /// the code stack drops to `depth`.
+    fn step(&mut self, depth: usize) -> Result<bool, RuntimeError> {
+        use crate::instructions::Instruction::*;
+
+        match self.next_instruction() {
+            Some(instr) => match instr {
+                LoadConstant(ref value) => self.load_constant(value),
+                Jump(offset) => self.jump(offset),
+                JumpOnFalse(offset) => self.jump_on_false(offset)?,
+                JumpOnTrue(offset) => self.jump_on_true(offset)?,
+                LoadReference(ref reference) => self.load_reference(reference)?,
+                Assignment(ref reference) => self.assignment(reference)?,
+                Replace(ref reference) => self.replace(reference)?,
+                LoadUnspecified => self.load_unspecified(),
+                Apply => self.apply(false)?,
+                TailApply => self.apply(true)?,
+                Argument => self.argument()?,
+                Frame => self.push_frame(),
+                Declare(ref reference) => self.declare(reference),
+                Delay => self.delay(Pending::Delay),
+                DelayForce => self.delay(Pending::DelayForce),
+                Close {
+                    ref args,
+                    ref args_type,
+                    ref body,
+                } => self.push_closure(args, *args_type, body),
+                Unbind(depth) => self.unbind_above(depth),
+                Uninstall(depth) => self.handlers.truncate(depth),
+                Resolve => self.resolve()?,
+                ApplyValues => self.apply_values()?,
+            },
+            None if self.code.len() == depth => return Ok(false),
+            None => self.pop_frame(true)?,
+        }
+
+        Ok(true)
+    }
+
+    /// Drops whatever an aborted `run_until` left on the code and frame stacks,
+    /// undoing the dynamic state the code left behind had set up.
+    fn unwind(&mut self, depth: usize, stack_depth: usize) {
+        self.leave(self.instructions.get(self.pc).cloned());
+        while self.code.len() > depth {
+            let (instructions, pc) = self.code.pop().unwrap();
+            self.leave(instructions.get(pc).cloned());
+        }
+
+        if self.stack.len() > stack_depth {
+            let frame = self.stack.split_off(stack_depth).pop_front().unwrap();
+            self.rib = frame.rib;
+            self.env = frame.env;
+        }
+    }
+
+    /// Undoes what code being left for good would have undone when resumed,
+    /// `next` being the instruction it would have resumed at.
+    fn leave(&mut self, next: Option<Instruction>) {
+        match next {
+            Some(Instruction::Unbind(depth)) => self.unbind_above(depth),
+            Some(Instruction::Uninstall(depth)) => self.handlers.truncate(depth),
+            _ => (),
+        }
+    }
+
+    /// Resumes the `guard` whose handler is the `target`th one at the end of
+    /// its body, with `value` as the value of the body, leaving the code run
+    /// since for good. Returns false if the `guard` wasn't entered from the
+    /// code above `depth` on the code stack.
+    fn escape(&mut self, target: usize, value: &RcValue, depth: usize) -> bool {
+        let frames = match self.handlers.get(target) {
+            Some(Handler::Guard(_, _, frames)) => *frames,
+            _ => return false,
+        };
+        let resumes = |instructions: &Bytecode, pc: usize| {
+            instructions.get(pc) == Some(&Instruction::Uninstall(target))
+        };
+
+        if !resumes(&self.instructions, self.pc) {
+            let at = match (depth..self.code.len())
+                .rev()
+                .find(|&i| resumes(&self.code[i].0, self.code[i].1))
+            {
+                Some(at) => at,
+                None => return false,
+            };
+
+            self.leave(self.instructions.get(self.pc).cloned());
+            while self.code.len() > at + 1 {
+                let (instructions, pc) = self.code.pop().unwrap();
+                self.leave(instructions.get(pc).cloned());
+            }
+            let (instructions, pc) = self.code.pop().unwrap();
+            self.instructions = instructions;
+            self.pc = pc;
+        }
+
+        if self.stack.len() > frames {
+            let frame = self.stack.split_off(frames).pop_front().unwrap();
+            self.rib = frame.rib;
+            self.env = frame.env;
+        }
+        self.acc = value.clone();
+        true
     }
 
     fn next_instruction(&mut self) -> Option<Instruction> {
@@ -108,16 +347,20 @@ impl VM {
         self.acc = Value::from_ast(ast, &mut self.memory);
     }
 
-    fn jump_on_false(&mut self, times: usize) {
-        if self.acc == self.memory.b_false() {
+    fn jump_on_false(&mut self, times: usize) -> Result<(), RuntimeError> {
+        if self.single_value()?.is_false() {
             self.jump(times);
         }
+
+        Ok(())
     }
 
-    fn jump_on_true(&mut self, times: usize) {
-        if self.acc != self.memory.b_false() {
+    fn jump_on_true(&mut self, times: usize) -> Result<(), RuntimeError> {
+        if !self.single_value()?.is_false() {
             self.jump(times);
         }
+
+        Ok(())
     }
 
     fn jump(&mut self, times: usize) {
@@ -126,6 +369,9 @@ impl VM {
 
     fn load_reference(&mut self, reference: &str) -> Result<(), RuntimeError> {
         match self.env.get(reference) {
+            Some(ref value) if matches!(**value, Value::Uninitialized) => {
+                Err(RuntimeError::UninitializedVariable(reference.into()))
+            }
             Some(value) => {
                 self.acc = value;
                 Ok(())
@@ -134,13 +380,22 @@ impl VM {
         }
     }
 
-    fn assignment(&mut self, reference: &str) {
-        self.env.set(reference.into(), self.acc.clone());
+    fn declare(&mut self, reference: &str) {
+        let uninitialized = self.memory.uninitialized();
+        self.env.set(reference.into(), uninitialized);
+    }
+
+    fn assignment(&mut self, reference: &str) -> Result<(), RuntimeError> {
+        let value = self.single_value()?;
+        self.env.set(reference.into(), value);
         self.load_unspecified();
+
+        Ok(())
     }
 
     fn replace(&mut self, reference: &str) -> Result<(), RuntimeError> {
-        match self.env.replace(reference.into(), self.acc.clone()) {
+        let value = self.single_value()?;
+        match self.env.replace(reference.into(), value) {
             Some(_) => {
                 self.load_unspecified();
                 Ok(())
@@ -179,11 +434,34 @@ impl VM {
         Ok(())
     }
 
-    fn apply(&mut self) -> Result<(), RuntimeError> {
+    /// Applies the procedure in the accumulator to the rib. A tail call
+    /// replaces the body being run instead of returning to it: the frame saved
+    /// for the call is dropped, and so is the code of the caller.
+    fn apply(&mut self, tail: bool) -> Result<(), RuntimeError> {
         match *self.acc.clone() {
+            Value::PrimitiveFn(ref name) if name == "call-with-values" => {
+                self.call_with_values(tail)
+            }
             Value::PrimitiveFn(ref name) => {
+                let args = mem::take(&mut self.rib);
+                if let Some(result) =
+                    primitives::apply_in_place(name, &args, self, tail)
+                {
+                    return result;
+                }
+
+                let result = primitives::apply(name, &args, self)?;
+
+                self.acc = result;
+
+                self.pop_frame(false)?;
+
+                Ok(())
+            }
+            Value::RecordProcedure(ref procedure) => {
+                let args = mem::take(&mut self.rib);
                 let result =
-                    primitives::apply(name, &self.rib, &mut self.memory)?;
+                    primitives::apply_record_procedure(procedure, &args, self)?;
 
                 self.acc = result;
 
@@ -191,6 +469,46 @@ impl VM {
 
                 Ok(())
             }
+            Value::EnumSetProcedure(ref procedure) => {
+                let args = mem::take(&mut self.rib);
+                let result =
+                    primitives::apply_enum_set_procedure(procedure, &args, self)?;
+
+                self.acc = result;
+
+                self.pop_frame(false)?;
+
+                Ok(())
+            }
+            Value::Parameter(ref parameter) => {
+                let args = mem::take(&mut self.rib);
+                self.acc = primitives::apply_parameter(parameter, &args)?;
+
+                self.pop_frame(false)?;
+
+                Ok(())
+            }
+            Value::CaseLambda(ref clauses) => {
+                let count = self.rib.len();
+                let clause = clauses.iter().find(|clause| {
+                    matches!(clause.arity(), Some((required, more))
+                        if count == required || more && count > required)
+                });
+
+                match clause {
+                    Some(clause) => {
+                        self.acc = clause.clone();
+                        self.apply(tail)
+                    }
+                    None => Err(RuntimeError::NoMatchingArity(
+                        clauses
+                            .iter()
+                            .filter_map(|clause| clause.arity())
+                            .collect(),
+                        count,
+                    )),
+                }
+            }
             Value::Closure {
                 ref name,
                 ref args_type,
@@ -201,9 +519,15 @@ impl VM {
                 let mut instructions = Vec::from_iter(body.clone());
                 mem::swap(&mut self.instructions, &mut instructions);
 
-                self.code.push((instructions, self.pc));
+                if tail {
+                    self.stack
+                        .pop_back()
+                        .ok_or(RuntimeError::CannotPopLastFrame)?;
+                } else {
+                    self.code.push((instructions, self.pc));
+                }
                 self.pc = 0;
-                self.env = closure.clone();
+                self.env = CellEnv::wraps(closure.clone());
 
                 match *args_type {
                     ArgumentsType::Fixed => {
@@ -255,6 +579,139 @@ impl VM {
         }
     }
 
+    /// Calls the producer in place of the `call-with-values` application,
+    /// then `ApplyValues` applies the consumer to its values.
+    fn call_with_values(&mut self, tail: bool) -> Result<(), RuntimeError> {
+        let args = mem::take(&mut self.rib);
+        if args.len() != 2 {
+            return Err(RuntimeError::BadArity(Some("call-with-values".into())));
+        }
+
+        self.call_then(
+            args[0].clone(),
+            vec![],
+            vec![Instruction::ApplyValues],
+            vec![args[1].clone()],
+            tail,
+        )
+    }
+
+    /// Applies the consumer in the rib to the values in the accumulator, in
+    /// tail position.
+    fn apply_values(&mut self) -> Result<(), RuntimeError> {
+        let consumer = self.rib[0].clone();
+        let values = Value::values(&self.acc);
+
+        self.push_frame();
+        self.rib = values;
+        self.acc = consumer;
+        self.apply(true)
+    }
+
+    /// Applies `procedure` to `args` in place of the built-in procedure being
+    /// applied, which then runs `then`, with `rib` as its rib, before it
+    /// returns. This way built-in procedures call back into Scheme code
+    /// without running the VM recursively.
+    pub fn call_then(
+        &mut self,
+        procedure: RcValue,
+        args: Vec<RcValue>,
+        then: Bytecode,
+        rib: Rib,
+        tail: bool,
+    ) -> Result<(), RuntimeError> {
+        self.continue_with(then, rib, tail)?;
+
+        self.push_frame();
+        self.rib = args;
+        self.acc = procedure;
+        self.apply(false)
+    }
+
+    /// Calls `thunk` in place of the built-in procedure being applied, with
+    /// `handler` installed on top of the exception handlers until it returns.
+    pub fn call_with_handler(
+        &mut self,
+        handler: RcValue,
+        thunk: RcValue,
+        tail: bool,
+    ) -> Result<(), RuntimeError> {
+        self.call_handled(thunk, tail, |_| Handler::Procedure(handler))
+    }
+
+    /// Calls `thunk` in place of the `%guard` application, with the handler
+    /// of a `guard` whose clauses `clauses` evaluates installed until it
+    /// returns. The value of the clause accepting an object raised by
+    /// `thunk` is returned in its place.
+    pub fn call_guarded(
+        &mut self,
+        clauses: RcValue,
+        thunk: RcValue,
+        tail: bool,
+    ) -> Result<(), RuntimeError> {
+        let parameterized = self.parameterized.len();
+        self.call_handled(thunk, tail, |frames| {
+            Handler::Guard(clauses, parameterized, frames)
+        })
+    }
+
+    /// Calls `thunk` in place of the built-in procedure being applied, with
+    /// the handler `handler` makes, from the depth of the frame stack
+    /// `thunk` returns to, installed until it returns.
+    fn call_handled<F>(
+        &mut self,
+        thunk: RcValue,
+        tail: bool,
+        handler: F,
+    ) -> Result<(), RuntimeError>
+    where
+        F: FnOnce(usize) -> Handler,
+    {
+        let depth = self.handlers.len();
+        self.continue_with(vec![Instruction::Uninstall(depth)], vec![], tail)?;
+        self.handlers.push(handler(self.stack.len()));
+
+        self.push_frame();
+        self.acc = thunk;
+        self.apply(false)
+    }
+
+    /// Makes the built-in procedure being applied run `then`, with `rib` as
+    /// its rib, before it returns.
+    fn continue_with(
+        &mut self,
+        then: Bytecode,
+        rib: Rib,
+        tail: bool,
+    ) -> Result<(), RuntimeError> {
+        if tail {
+            self.stack
+                .pop_back()
+                .ok_or(RuntimeError::CannotPopLastFrame)?;
+        } else {
+            let instructions = mem::take(&mut self.instructions);
+            self.code.push((instructions, self.pc));
+        }
+        self.instructions = then;
+        self.pc = 0;
+        self.rib = rib;
+
+        Ok(())
+    }
+
+    /// Settles the promise in the rib with the value its thunk returned, then
+    /// forces it again in tail position, so that chains of `delay-force` run
+    /// in constant space.
+    fn resolve(&mut self) -> Result<(), RuntimeError> {
+        let promise = self.rib[0].clone();
+        primitives::settle_promise(&promise, self.acc.clone());
+
+        self.push_frame();
+        self.rib = vec![promise];
+        self.acc = self.memory.primitive("force".into());
+        self.apply(true)
+    }
+
     fn push_closure(
         &mut self,
         args: &[String],
@@ -264,29 +721,260 @@ impl VM {
         self.acc = self.memory.closure(
             args_type,
             args.to_vec(),
-            CellEnv::wraps(self.env.clone()),
+            self.env.clone(),
             body.clone(),
         );
     }
 
-    fn argument(&mut self) {
-        self.rib.push(self.acc.clone());
+    /// Wraps the thunk in the accumulator into a promise.
+    fn delay<F>(&mut self, pending: F)
+    where
+        F: Fn(RcValue) -> Pending,
+    {
+        let thunk = self.acc.clone();
+        self.acc = self.memory.promise(PromiseState::Pending(pending(thunk)));
+    }
+
+    fn argument(&mut self) -> Result<(), RuntimeError> {
+        let value = self.single_value()?;
+        self.rib.push(value);
+
+        Ok(())
+    }
+
+    /// The accumulator, for continuations that accept exactly one value.
+    fn single_value(&self) -> Result<RcValue, RuntimeError> {
+        match *self.acc {
+            Value::Values(ref values) => {
+                Err(RuntimeError::BadValueCount(values.len()))
+            }
+            _ => Ok(self.acc.clone()),
+        }
     }
 
     fn init_primitives(&mut self) {
-        for &name in &primitives::PRIMITIVES {
+        for name in primitives::names() {
             let primitive = self.memory.primitive(name.to_owned());
-            self.env.set(name.to_owned(), primitive);
+            self.builtins.set(name.to_owned(), primitive);
+        }
+
+        for name in ALIASED.iter() {
+            let primitive = self.builtins.get(name).unwrap();
+            self.builtins.set(format!("%{}", name), primitive);
+        }
+    }
+
+    fn init_streams(&mut self) {
+        let nil = self.memory.nil();
+        let stream_null = self.memory.promise(PromiseState::Forced(nil));
+        self.builtins.set("stream-null".into(), stream_null);
+    }
+
+    /// The current ports are parameters whose initial values are the
+    /// standard streams.
+    fn init_ports(&mut self) {
+        for (name, port) in [
+            ("current-input-port", Port::Stdin),
+            ("current-output-port", Port::Stdout),
+            ("current-error-port", Port::Stderr),
+        ] {
+            let port = self.memory.port(port);
+            let parameter = self.memory.parameter(port, None);
+
+            if name == "current-output-port" {
+                self.output_port = parameter.clone();
+            }
+            self.builtins.set(name.into(), parameter);
+        }
+    }
+
+    /// The value of the `current-output-port` parameter.
+    pub fn current_output_port(&self) -> RcValue {
+        match *self.output_port {
+            Value::Parameter(ref parameter) => parameter.get(),
+            _ => self.output_port.clone(),
+        }
+    }
+
+    fn init_libraries(&mut self) {
+        for name in INTERNALS.iter() {
+            let location = self.builtins.location(name).unwrap();
+            self.internals.link((*name).to_owned(), location);
+        }
+
+        for library in rnrs::libraries(self.identifiers.builtins()) {
+            self.libraries.define(library);
+        }
+    }
+
+    fn init_conditions(&mut self) {
+        for (name, value) in self.conditions.bindings(&mut self.memory) {
+            self.builtins.set(name, value);
+        }
+    }
+
+    /// The condition object describing `error`.
+    pub fn condition(&mut self, error: &RuntimeError) -> RcValue {
+        self.conditions.describe(error, &mut self.memory)
+    }
+
+    /// Raises `obj` to the current exception handler, which is called with
+    /// the handlers installed outside it. If the raise isn't `continuable`
+    /// and the handler returns, a `&non-continuable` violation is raised to
+    /// those handlers in turn. With no handler left, `obj` is `Raised`.
+    pub fn raise(
+        &mut self,
+        obj: RcValue,
+        continuable: bool,
+    ) -> Result<RcValue, RuntimeError> {
+        let handler = match self.handlers.pop() {
+            Some(handler) => handler,
+            None => return Err(RuntimeError::Raised(obj)),
+        };
+
+        let result = match handler {
+            Handler::Procedure(ref procedure) => {
+                self.call(procedure.clone(), vec![obj.clone()])
+            }
+            Handler::Guard(ref clauses, parameterized, _) => {
+                self.guard(clauses, parameterized, &obj)
+            }
+        };
+        let result = match result {
+            Ok(_) if !continuable => {
+                let condition =
+                    self.conditions.non_continuable(obj, &mut self.memory);
+                self.raise(condition, false)
+            }
+            Err(error) if !is_raised(&error) => {
+                let condition = self.condition(&error);
+                self.raise(condition, false)
+            }
+            result => result,
+        };
+
+        self.handlers.push(handler);
+        result
+    }
+
+    /// Runs the clauses of the `guard` whose handler is being called on
+    /// `obj`, with the parameters bound as they were where the `guard` was
+    /// installed, `parameterized` of them. The one accepting it escapes to
+    /// the `guard` with its value; if none does, the parameters are bound
+    /// back as they were where `obj` was raised, and it is raised again
+    /// there to the handlers outside the `guard`.
+    fn guard(
+        &mut self,
+        clauses: &RcValue,
+        parameterized: usize,
+        obj: &RcValue,
+    ) -> Result<RcValue, RuntimeError> {
+        let declined = self.memory.store(Value::Unspecified);
+
+        let bound = self.unbind_parameters(parameterized);
+        let result =
+            self.call(clauses.clone(), vec![obj.clone(), declined.clone()]);
+        self.rebind_parameters(parameterized, bound);
+
+        match result? {
+            value if Rc::ptr_eq(&value, &declined) => {
+                self.raise(obj.clone(), true)
+            }
+            value => Err(RuntimeError::GuardEscape(self.handlers.len(), value)),
+        }
+    }
+
+    /// Binds `parameter` to `value` on top of `parameterized`.
+    pub fn bind_parameter(&mut self, parameter: &RcValue, value: RcValue) {
+        let previous = replace_parameter(parameter, value);
+        self.parameterized.push((parameter.clone(), previous));
+    }
+
+    /// Unbinds the parameters bound above the first `depth` ones for good.
+    fn unbind_above(&mut self, depth: usize) {
+        self.unbind_parameters(depth);
+        self.parameterized.truncate(depth);
+    }
+
+    /// Gives the parameters bound above the first `depth` ones the values
+    /// they had before, innermost first. Returns the values they were bound
+    /// to, in that order.
+    pub fn unbind_parameters(&self, depth: usize) -> Vec<RcValue> {
+        self.parameterized[depth..]
+            .iter()
+            .rev()
+            .map(|(parameter, previous)| {
+                replace_parameter(parameter, previous.clone())
+            })
+            .collect()
+    }
+
+    /// Binds the parameters above the first `depth` ones back to the values
+    /// `unbind_parameters` returned.
+    fn rebind_parameters(&self, depth: usize, values: Vec<RcValue>) {
+        for ((parameter, _), value) in self.parameterized[depth..]
+            .iter()
+            .zip(values.into_iter().rev())
+        {
+            replace_parameter(parameter, value);
         }
     }
+
+    /// Raises the condition describing `error` to the exception handlers,
+    /// unless there are none or it was raised already.
+    fn raise_error(&mut self, error: RuntimeError) -> RuntimeError {
+        if self.handlers.is_empty() || is_raised(&error) {
+            return error;
+        }
+
+        let condition = self.condition(&error);
+        match self.raise(condition, false) {
+            Ok(_) => error,
+            Err(raised) => raised,
+        }
+    }
+}
+
+/// Binds the parameter object `parameter` to `value`, returning the value it
+/// had.
+fn replace_parameter(parameter: &RcValue, value: RcValue) -> RcValue {
+    match **parameter {
+        Value::Parameter(ref parameter) => parameter.replace(value),
+        _ => value,
+    }
+}
+
+/// Whether `error` went through the exception handlers already.
+fn is_raised(error: &RuntimeError) -> bool {
+    match *error {
+        RuntimeError::Raised(..) | RuntimeError::GuardEscape(..) => true,
+        RuntimeError::Located(_, ref error) => is_raised(error),
+        _ => false,
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        ast::AST::*, errors::RuntimeError, instructions::Instruction::*, vm::VM,
+        ast::AST::*, compiler::compile_single, errors::RuntimeError,
+        instructions::Instruction::*, parser::parse, vm::VM,
     };
 
+    /// Runs `input` one instruction at a time, returning the deepest the frame
+    /// and code stacks got.
+    fn deepest_stacks(vm: &mut VM, input: &str) -> (usize, usize) {
+        let ast = parse(input).unwrap().remove(0);
+        vm.instructions = compile_single(&ast, &mut vm.identifiers).unwrap();
+        vm.pc = 0;
+
+        let mut deepest = (0, 0);
+        while vm.step(0).unwrap() {
+            deepest.0 = deepest.0.max(vm.stack.len());
+            deepest.1 = deepest.1.max(vm.code.len());
+        }
+        deepest
+    }
+
     #[test]
     fn execute_load_constant() {
         {
@@ -475,6 +1163,59 @@ mod test {
         assert_eq!(Ok(vm.memory.integer(4)), vm.execute(instr));
     }
 
+    #[test]
+    fn call() {
+        let mut vm = VM::new();
+        let plus = vm.env.get("+").unwrap();
+        let args = vec![vm.memory.integer(1), vm.memory.integer(2)];
+
+        assert_eq!(Ok(vm.memory.integer(3)), vm.call(plus, args));
+        assert!(vm.stack.is_empty());
+    }
+
+    #[test]
+    fn call_restores_state_on_error() {
+        let mut vm = VM::new();
+        let car = vm.env.get("car").unwrap();
+        let args = vec![vm.memory.integer(1)];
+
+        assert_eq!(
+            Err(RuntimeError::WrongArgumentType(vm.memory.integer(1))),
+            vm.call(car, args)
+        );
+        assert!(vm.stack.is_empty());
+    }
+
+    #[test]
+    fn tail_calls_run_in_constant_space() {
+        let mut vm = VM::new();
+        for definition in [
+            "(define (loop n) (if (= n 0) 'done (loop (- n 1))))",
+            "(define (even? n) (if (= n 0) #t (odd? (- n 1))))",
+            "(define (odd? n) (and (not (= n 0)) (even? (- n 1))))",
+            "(define (count n acc)
+               (let ((next (- n 1)))
+                 (or (and (= n 0) acc) (count next (+ acc 1)))))",
+        ] {
+            let ast = parse(definition).unwrap().remove(0);
+            let bytecode = compile_single(&ast, &mut vm.identifiers).unwrap();
+            vm.execute(bytecode).unwrap();
+        }
+
+        assert_eq!(
+            deepest_stacks(&mut vm, "(loop 10)"),
+            deepest_stacks(&mut vm, "(loop 10000)")
+        );
+        assert_eq!(
+            deepest_stacks(&mut vm, "(even? 11)"),
+            deepest_stacks(&mut vm, "(even? 10001)")
+        );
+        assert_eq!(
+            deepest_stacks(&mut vm, "(count 10 0)"),
+            deepest_stacks(&mut vm, "(count 10000 0)")
+        );
+    }
+
     #[test]
     fn illegal_frame_apply() {
         let mut vm = VM::new();
@@ -0,0 +1,116 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn dispatches_on_argument_count() {
+    assert_eval(
+        "(define area
+           (case-lambda
+             ((side) (* side side))
+             ((width height) (* width height))))
+         (list (area 3) (area 2 5))",
+        "'(9 10)",
+    );
+}
+
+#[test]
+fn optional_arguments() {
+    assert_eval(
+        "(define make-point
+           (case-lambda
+             (() (make-point 0))
+             ((x) (make-point x 0))
+             ((x y) (list x y))))
+         (list (make-point) (make-point 1) (make-point 1 2))",
+        "'((0 0) (1 0) (1 2))",
+    );
+}
+
+#[test]
+fn rest_arguments() {
+    assert_eval(
+        "(define f
+           (case-lambda
+             ((a) (list 'one a))
+             ((a b . rest) (list 'many a b rest))
+             (all (list 'none all))))
+         (list (f) (f 1) (f 1 2) (f 1 2 3 4))",
+        "'((none ()) (one 1) (many 1 2 ()) (many 1 2 (3 4)))",
+    );
+}
+
+#[test]
+fn first_matching_clause_wins() {
+    assert_eval(
+        "(define f (case-lambda (args 'rest) ((a) 'one)))
+         (f 1)",
+        "'rest",
+    );
+}
+
+#[test]
+fn clauses_are_closures() {
+    assert_eval(
+        "(define (make-counter)
+           (let ((n 0))
+             (case-lambda
+               (() n)
+               ((step) (set! n (+ n step)) n))))
+         (define counter (make-counter))
+         (counter 2)
+         (counter 3)
+         (counter)",
+        "5",
+    );
+    assert_eval(
+        "(define f
+           (case-lambda
+             ((x) (define y (* x 2)) (+ x y))
+             ((x y) (+ x y))))
+         (list (f 1) (f 1 1))",
+        "'(3 2)",
+    );
+}
+
+#[test]
+fn calls_in_tail_position() {
+    assert_eval(
+        "(define count
+           (case-lambda
+             ((n) (count n 0))
+             ((n acc) (if (= n 0) acc (count (- n 1) (+ acc 1))))))
+         (count 100000)",
+        "100000",
+    );
+}
+
+#[test]
+fn is_a_procedure() {
+    assert_eval("(procedure? (case-lambda ((x) x)))", "#t");
+    assert_eval(
+        "(call-with-values (lambda () (values 1 2))
+                           (case-lambda ((a) a) ((a b) (+ a b))))",
+        "3",
+    );
+}
+
+#[test]
+fn no_matching_clause() {
+    assert_eval_err(
+        "(define f (case-lambda ((a) a) ((a b c . rest) a)))
+         (f 1 2)",
+        NoMatchingArity(vec![(1, false), (3, true)], 2),
+    );
+    assert_eval_err("((case-lambda) 1)", NoMatchingArity(vec![], 1));
+    assert_eval_err("(case-lambda (x))", MalformedExpression);
+}
+
+#[test]
+fn display() {
+    assert_eval_val(
+        "(define port (open-output-string))
+         (display (case-lambda ((a) a) ((a . b) a) (c c)) port)
+         (get-output-string port)",
+        string("<case-lambda (a) (a . b) c>"),
+    );
+}
@@ -0,0 +1,92 @@
+use crate::helpers::*;
+
+#[test]
+fn closures_capture_their_own_arguments() {
+    assert_eval(
+        "(define (mk x) (lambda () x))
+         (define one (mk 1))
+         (define two (mk 2))
+         (list (one) (two))",
+        "'(1 2)",
+    );
+}
+
+#[test]
+fn counters_are_independent() {
+    assert_eval(
+        "(define (make-counter)
+           (let ((n 0))
+             (lambda () (set! n (+ n 1)) n)))
+         (define a (make-counter))
+         (define b (make-counter))
+         (a)
+         (a)
+         (b)
+         (list (a) (b))",
+        "'(3 2)",
+    );
+}
+
+#[test]
+fn accumulators_keep_their_state() {
+    assert_eval(
+        "(define (make-accumulator total)
+           (lambda (amount) (set! total (+ total amount)) total))
+         (define acc (make-accumulator 100))
+         (define other (make-accumulator 0))
+         (acc 10)
+         (other 1)
+         (list (acc 10) (other 1))",
+        "'(120 2)",
+    );
+}
+
+#[test]
+fn recursion_captures_arguments() {
+    assert_eval(
+        "(define (thunks n)
+           (if (= n 0)
+               '()
+               (cons (lambda () n) (thunks (- n 1)))))
+         (define (call-all ts)
+           (if (null? ts) '() (cons ((car ts)) (call-all (cdr ts)))))
+         (call-all (thunks 3))",
+        "'(3 2 1)",
+    );
+}
+
+#[test]
+fn arguments_survive_recursive_calls() {
+    assert_eval(
+        "(define (sum n) (if (= n 0) 0 (+ n (sum (- n 1)))))
+         (sum 10)",
+        "55",
+    );
+    assert_eval(
+        "(define (fib n) (if (< n 2) n (+ (fib (- n 1)) (fib (- n 2)))))
+         (fib 15)",
+        "610",
+    );
+}
+
+#[test]
+fn reentrant_calls_do_not_share_bindings() {
+    assert_eval(
+        "(define (f x g) (g) x)
+         (f 1 (lambda () (f 2 (lambda () 3))))",
+        "1",
+    );
+}
+
+#[test]
+fn internal_definitions_are_local_to_the_call() {
+    assert_eval(
+        "(define (f x)
+           (define y (* x 2))
+           (lambda () y))
+         (define a (f 1))
+         (define b (f 2))
+         (list (a) (b))",
+        "'(2 4)",
+    );
+}
@@ -19,3 +19,97 @@ fn if_bad_arity() {
     assert_eval_err("(if)", BadArity(Some("if".into())));
     assert_eval_err("(if a b c d)", BadArity(Some("if".into())));
 }
+
+#[test]
+fn when_and_unless() {
+    assert_eval("(when (= 1 1) 'a 'b)", "'b");
+    assert_eval_val("(when (= 1 2) 'a)", unspecified());
+    assert_eval("(unless (= 1 2) 'a 'b)", "'b");
+    assert_eval_val("(unless (= 1 1) 'a)", unspecified());
+    assert_eval_err("(when #t)", BadArity(Some("when".into())));
+}
+
+#[test]
+fn cond() {
+    assert_eval("(cond ((= 1 2) 'a) ((= 1 1) 'b 'c) (else 'd))", "'c");
+    assert_eval("(cond ((= 1 2) 'a) (else 'b 'c))", "'c");
+    assert_eval_val("(cond ((= 1 2) 'a))", unspecified());
+    assert_eval("(cond ((memv 2 '(1 2 3))) (else 'b))", "'(2 3)");
+    assert_eval("(cond (#f) (else 'b))", "'b");
+}
+
+#[test]
+fn cond_arrow() {
+    assert_eval("(cond ((assv 2 '((1 a) (2 b))) => (lambda (p) (car (cdr p)))) (else 'c))", "'b");
+}
+
+#[test]
+fn cond_else_must_be_last() {
+    assert_eval_err("(cond (else 1) (#t 2))", MalformedExpression);
+    assert_eval_err("(cond 1)", MalformedExpression);
+}
+
+#[test]
+fn case() {
+    assert_eval(
+        "(case (* 2 3) ((2 3 5 7) 'prime) ((1 4 6 8 9) 'composite))",
+        "'composite",
+    );
+    assert_eval_val("(case (car '(c d)) ((a) 'a) ((b) 'b))", unspecified());
+    assert_eval(
+        "(case (car '(c d)) ((a e i o u) 'vowel) ((w y) 'semivowel) (else 'consonant))",
+        "'consonant",
+    );
+    assert_eval("(case #\\a ((#\\a) 'char))", "'char");
+    assert_eval("(case 5 ((1) 'one) (else => (lambda (x) (* x 2))))", "10");
+    assert_eval("(case 1 ((1) => (lambda (x) (+ x 1))) (else 'other))", "2");
+}
+
+#[test]
+fn case_evaluates_key_once() {
+    assert_eval(
+        "(define count 0)
+         (define (key) (set! count (+ count 1)) 3)
+         (case (key) ((1) 'a) ((2) 'b) ((3) 'c))
+         count",
+        "1",
+    );
+}
+
+#[test]
+fn case_ignores_shadowed_memv() {
+    assert_eval("(let ((memv list)) (case 2 ((1) 'a) (else 'b)))", "'b");
+    assert_eval(
+        "(define (memv . args) #t)
+         (case 2 ((1) 'a) (else 'b))",
+        "'b",
+    );
+}
+
+#[test]
+fn case_key_does_not_capture_variables() {
+    assert_eval(
+        "(let ((%case-key 'outer)) (case 1 ((1) %case-key)))",
+        "'outer",
+    );
+}
+
+#[test]
+fn conditionals_in_tail_position() {
+    assert_eval(
+        "(define (loop n)
+           (cond ((= n 0) 'done)
+                 ((= n 1) (loop (- n 1)))
+                 (else (when #t (unless #f (loop (- n 1)))))))
+         (loop 100000)",
+        "'done",
+    );
+    assert_eval(
+        "(define (loop n)
+           (case n
+             ((0) 'done)
+             (else (begin (loop (- n 1))))))
+         (loop 100000)",
+        "'done",
+    );
+}
@@ -0,0 +1,105 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn simple_conditions() {
+    assert_eval(
+        "(define c (make-message-condition \"oops\"))
+         (list (condition? c)
+               (message-condition? c)
+               (error? c)
+               (condition-message c))",
+        "'(#t #t #f \"oops\")",
+    );
+    assert_eval("(condition? 'oops)", "#f");
+}
+
+#[test]
+fn compound_conditions() {
+    assert_eval(
+        "(define c
+           (condition (make-error)
+                      (make-who-condition 'car)
+                      (make-message-condition \"not a pair\")
+                      (make-irritants-condition '(1))))
+         (list (error? c)
+               (serious-condition? c)
+               (violation? c)
+               (condition-who c)
+               (condition-message c)
+               (condition-irritants c)
+               (length (simple-conditions c)))",
+        "'(#t #t #f car \"not a pair\" (1) 4)",
+    );
+}
+
+#[test]
+fn compound_conditions_are_flattened() {
+    assert_eval(
+        "(define c (condition (make-warning) (make-message-condition \"m\")))
+         (length (simple-conditions (condition c (make-error) c)))",
+        "5",
+    );
+    assert_eval("(simple-conditions (condition))", "'()");
+    assert_eval_err("(condition 'oops)", WrongArgumentType(atom("oops")));
+}
+
+#[test]
+fn condition_hierarchy() {
+    assert_eval(
+        "(define c (make-assertion-violation))
+         (list (assertion-violation? c) (violation? c)
+               (serious-condition? c) (error? c))",
+        "'(#t #t #t #f)",
+    );
+    assert_eval(
+        "(define c (make-i/o-error))
+         (list (i/o-error? c) (error? c) (violation? c))",
+        "'(#t #t #f)",
+    );
+    assert_eval(
+        "(define c (make-syntax-violation '(if) 'if))
+         (list (syntax-violation? c)
+               (syntax-violation-form c)
+               (syntax-violation-subform c))",
+        "'(#t (if) if)",
+    );
+    assert_eval(
+        "(list (lexical-violation? (make-lexical-violation))
+               (undefined-violation? (make-undefined-violation))
+               (non-continuable-violation? (make-non-continuable-violation))
+               (implementation-restriction-violation?
+                 (make-implementation-restriction-violation)))",
+        "'(#t #t #t #t)",
+    );
+}
+
+#[test]
+fn accessors_require_the_condition_type() {
+    assert_eval_err("(condition-message 'oops)", WrongArgumentType(atom("oops")));
+}
+
+#[test]
+fn user_defined_condition_types() {
+    assert_eval(
+        "(define-record-type http-error (parent &error) (fields status))
+         (define http-error-status? (condition-predicate http-error))
+         (define status (condition-accessor http-error http-error-status))
+         (define c (condition (make-http-error 404)
+                              (make-message-condition \"not found\")))
+         (list (error? c) (http-error-status? c) (status c)
+               (condition-message c))",
+        "'(#t #t 404 \"not found\")",
+    );
+
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(define-record-type point (fields x))
+         (condition-predicate point)",
+    );
+
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(WrongArgumentType(..)))
+    ));
+}
@@ -62,3 +62,152 @@ fn define_procedure_with_any() {
 fn define_bad_arity() {
     assert_eval_err("(define)", MalformedExpression);
 }
+
+#[test]
+fn begin() {
+    assert_eval("(begin 1 2 3)", "3");
+    assert_eval_val("(begin)", unspecified());
+    assert_eval(
+        "(define x 0)
+         (begin (set! x 5) (+ x 1))",
+        "6",
+    );
+}
+
+#[test]
+fn begin_splices_top_level_definitions() {
+    assert_eval(
+        "(begin (define x 1) (define (f) (+ x 1)))
+         (list x (f))",
+        "'(1 2)",
+    );
+}
+
+#[test]
+fn internal_definitions() {
+    assert_eval(
+        "(define (f x)
+           (define (double y) (* y 2))
+           (define z (double x))
+           (+ z 1))
+         (f 5)",
+        "11",
+    );
+}
+
+#[test]
+fn internal_definitions_are_mutually_recursive() {
+    assert_eval(
+        "(define (f n)
+           (define (even? n) (if (= n 0) #t (odd? (- n 1))))
+           (define (odd? n) (if (= n 0) #f (even? (- n 1))))
+           (even? n))
+         (list (f 10) (f 7))",
+        "'(#t #f)",
+    );
+}
+
+#[test]
+fn internal_definitions_do_not_leak() {
+    assert_eval(
+        "(define x 'outer)
+         (define (f) (define x 'inner) x)
+         (list (f) x)",
+        "'(inner outer)",
+    );
+    assert_eval(
+        "(define x 'outer)
+         (let () (define x 'inner) x)
+         x",
+        "'outer",
+    );
+}
+
+#[test]
+fn internal_definitions_in_lets() {
+    assert_eval(
+        "(let ((a 1))
+           (define b (+ a 1))
+           (list a b))",
+        "'(1 2)",
+    );
+    assert_eval(
+        "(let loop ((i 0))
+           (define next (+ i 1))
+           (if (= i 3) i (loop next)))",
+        "3",
+    );
+}
+
+#[test]
+fn internal_definitions_spliced_from_begin() {
+    assert_eval(
+        "(define (f)
+           (begin (define a 1) (begin (define b 2)))
+           (define c 3)
+           (list a b c))
+         (f)",
+        "'(1 2 3)",
+    );
+}
+
+#[test]
+fn internal_definitions_from_macros() {
+    assert_eval(
+        "(define (f)
+           (define-values (a b) (values 1 2))
+           (define-enumeration color (red blue) color-set)
+           (define-record-type point (fields x))
+           (list a b (color blue) (point-x (make-point 3))))
+         (f)",
+        "'(1 2 blue 3)",
+    );
+}
+
+#[test]
+fn internal_definitions_use_before_initialization() {
+    assert_eval_err(
+        "(define (f)
+           (define a b)
+           (define b 1)
+           a)
+         (f)",
+        UninitializedVariable("b".into()),
+    );
+}
+
+#[test]
+fn internal_definitions_after_expressions() {
+    assert_eval_err(
+        "(define (f)
+           (display 1)
+           (define a 1)
+           a)",
+        MalformedExpression,
+    );
+    assert_eval_err(
+        "(define (f)
+           (define a 1)
+           a
+           (begin (define b 2))
+           b)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn internal_definitions_duplicates() {
+    assert_eval_err(
+        "(define (f)
+           (define a 1)
+           (define a 2)
+           a)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn bodies_need_an_expression() {
+    assert_eval_err("(define (f) (define a 1))", MalformedExpression);
+    assert_eval_err("(lambda () (begin))", MalformedExpression);
+}
@@ -0,0 +1,126 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn define_enumeration() {
+    assert_eval(
+        "(define-enumeration color (red green blue) color-set)
+         (list (color green)
+               (enum-set->list (color-set blue red))
+               (enum-set->list (color-set)))",
+        "'(green (red blue) ())",
+    );
+    assert_eval_err(
+        "(define-enumeration color (red green blue) color-set)
+         (color purple)",
+        MalformedExpression,
+    );
+    assert_eval_err(
+        "(define-enumeration color (red green blue) color-set)
+         (color-set red purple)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn shadowed_enumeration_keywords_are_variables() {
+    assert_eval(
+        "(define-enumeration color (red green blue) color-set)
+         (let ((color list) (color-set vector))
+           (list (color 'purple) (color-set 1)))",
+        "'((purple) #(1))",
+    );
+}
+
+#[test]
+fn make_enumeration() {
+    assert_eval("(enum-set->list (make-enumeration '(a b c b)))", "'(a b c)");
+    assert_eval_err("(make-enumeration '(a 1))", WrongArgumentType(integer(1)));
+}
+
+#[test]
+fn enum_set_universe() {
+    assert_eval(
+        "(define e (make-enumeration '(a b c)))
+         (define s ((enum-set-constructor e) '(b)))
+         (enum-set->list (enum-set-universe s))",
+        "'(a b c)",
+    );
+}
+
+#[test]
+fn enum_set_indexer() {
+    assert_eval(
+        "(define index (enum-set-indexer (make-enumeration '(a b c))))
+         (list (index 'a) (index 'c) (index 'd))",
+        "'(0 2 #f)",
+    );
+}
+
+#[test]
+fn enum_set_constructor() {
+    assert_eval(
+        "(define make (enum-set-constructor (make-enumeration '(a b c))))
+         (enum-set->list (make '(c a)))",
+        "'(a c)",
+    );
+    assert_eval_err(
+        "(define make (enum-set-constructor (make-enumeration '(a b c))))
+         (make '(d))",
+        WrongArgumentType(atom("d")),
+    );
+}
+
+#[test]
+fn enum_set_member() {
+    assert_eval(
+        "(define-enumeration flag (read write exec) flags)
+         (define s (flags read exec))
+         (list (enum-set-member? 'read s)
+               (enum-set-member? 'write s)
+               (enum-set-member? 'other s))",
+        "'(#t #f #f)",
+    );
+}
+
+#[test]
+fn set_operations() {
+    assert_eval(
+        "(define-enumeration flag (read write exec) flags)
+         (list (enum-set->list (enum-set-union (flags read) (flags exec)))
+               (enum-set->list
+                 (enum-set-intersection (flags read write) (flags write exec)))
+               (enum-set->list (enum-set-difference (flags read write) (flags read)))
+               (enum-set->list (enum-set-complement (flags write))))",
+        "'((read exec) (write) (write) (read exec))",
+    );
+
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(enum-set-union (make-enumeration '(a)) (make-enumeration '(a)))",
+    );
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(WrongArgumentType(..)))
+    ));
+}
+
+#[test]
+fn comparisons() {
+    assert_eval(
+        "(define-enumeration flag (read write exec) flags)
+         (list (enum-set-subset? (flags read) (flags read write))
+               (enum-set-subset? (flags read write) (flags read))
+               (enum-set=? (flags read write) (flags write read))
+               (enum-set=? (flags read) (flags write)))",
+        "'(#t #f #t #f)",
+    );
+    assert_eval(
+        "(define small (make-enumeration '(a b)))
+         (define large (make-enumeration '(a b c)))
+         (list (enum-set-subset? small large)
+               (enum-set-subset? large small)
+               (enum-set->list (enum-set-projection large small)))",
+        "'(#t #f (a b))",
+    );
+}
@@ -0,0 +1,184 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn eval() {
+    assert_eval("(eval '(+ 1 2) (environment '(rnrs)))", "3");
+    assert_eval(
+        "(eval '(let ((x 2)) (* x x)) (environment '(rnrs base)))",
+        "4",
+    );
+    assert_eval(
+        "(eval (list 'car (list 'quote (list 1 2))) (environment '(rnrs)))",
+        "1",
+    );
+}
+
+#[test]
+fn environments_only_see_what_they_import() {
+    assert_eval(
+        "(define env (environment '(only (rnrs base) + -)))
+         (eval '(- (+ 1 2) 1) env)",
+        "2",
+    );
+    assert_eval_err(
+        "(eval '(* 1 2) (environment '(only (rnrs base) + -)))",
+        UnboundVariable("*".into()),
+    );
+    assert_eval_err(
+        "(define secret 1) (eval 'secret (environment '(rnrs)))",
+        UnboundVariable("secret".into()),
+    );
+    assert_eval_err(
+        "(eval '(cons 1 2) (environment '(only (rnrs base) car)))",
+        UnboundVariable("cons".into()),
+    );
+    assert_eval(
+        "(eval '(r:+ 1 2) (environment '(prefix (rnrs base) r:)))",
+        "3",
+    );
+}
+
+#[test]
+fn environments_only_have_the_keywords_they_import() {
+    for (form, keyword) in &[
+        ("(include \"tests/files/included.scm\")", "include"),
+        ("(if #t 1 2)", "if"),
+        ("(let ((x 1)) x)", "let"),
+        ("(guard (e (#t e)) 1)", "guard"),
+    ] {
+        assert_eval_err(
+            &format!("(eval '{} (environment '(only (rnrs base) quote)))", form),
+            UnboundVariable((*keyword).into()),
+        );
+    }
+    assert_eval(
+        "(eval '(if (quote #f) 1 2) (environment '(only (rnrs base) if quote)))",
+        "2",
+    );
+}
+
+#[test]
+fn environments_import_macros() {
+    assert_eval(
+        "(library (twice)
+           (export twice)
+           (import (rnrs))
+           (define-syntax twice
+             (syntax-rules () ((_ e) (begin e e)))))
+         (eval '(let ((n 0)) (twice (set! n (+ n 1))) n)
+               (environment '(rnrs) '(twice)))",
+        "2",
+    );
+}
+
+#[test]
+fn environments_are_immutable() {
+    assert_eval_err(
+        "(eval '(define x 1) (environment '(rnrs)))",
+        SyntaxViolation(
+            Some("eval".into()),
+            "definition in an immutable environment".into(),
+            pair(atom("define"), pair(atom("x"), pair(integer(1), nil()))),
+            atom("define"),
+        ),
+    );
+    assert_eval_err(
+        "(eval '(begin 1 (define-syntax k (syntax-rules ())))
+               (environment '(rnrs)))",
+        SyntaxViolation(
+            Some("eval".into()),
+            "definition in an immutable environment".into(),
+            pair(
+                atom("begin"),
+                pair(
+                    integer(1),
+                    pair(
+                        pair(
+                            atom("define-syntax"),
+                            pair(
+                                atom("k"),
+                                pair(
+                                    pair(
+                                        atom("syntax-rules"),
+                                        pair(nil(), nil()),
+                                    ),
+                                    nil(),
+                                ),
+                            ),
+                        ),
+                        nil(),
+                    ),
+                ),
+            ),
+            atom("define-syntax"),
+        ),
+    );
+    assert_eval_err(
+        "(eval '(set! car cdr) (environment '(rnrs)))",
+        SyntaxViolation(
+            Some("set!".into()),
+            "assignment of an immutable variable".into(),
+            pair(atom("set!"), pair(atom("car"), pair(atom("cdr"), nil()))),
+            atom("car"),
+        ),
+    );
+    assert_eval(
+        "(eval '(let ((x 1)) (set! x 2) x) (environment '(rnrs)))",
+        "2",
+    );
+
+    let mut runtime = Runtime::new();
+    let _ = runtime.eval_str("(eval '(set! car cdr) (environment '(rnrs)))");
+    assert_eq!(integer(1), runtime.eval_str("(car '(1 2))").unwrap()[0]);
+}
+
+#[test]
+fn interaction_environment() {
+    assert_eval(
+        "(eval '(define x 5) (interaction-environment))
+         (eval '(set! x (+ x 1)) (interaction-environment))
+         x",
+        "6",
+    );
+}
+
+#[test]
+fn eval_in_programs() {
+    let mut runtime = Runtime::new();
+    let values = runtime
+        .eval_program(
+            "(import (rnrs) (rnrs eval))
+             (eval '(string-length \"four\") (environment '(rnrs)))",
+        )
+        .unwrap();
+    assert_eq!(integer(4), values[0]);
+
+    assert_eq!(
+        Err(UnboundVariable("interaction-environment".into()).into()),
+        runtime.eval_program("(import (rnrs)) (interaction-environment)")
+    );
+}
+
+#[test]
+fn malformed_forms() {
+    assert_eval_err(
+        "(eval '(lambda) (environment '(rnrs)))",
+        MalformedExpression,
+    );
+    assert_eval_err(
+        "(eval '(quote) (environment '(rnrs)))",
+        BadArity(Some("quote".into())),
+    );
+    assert_eval_err("(quote 1 2)", BadArity(Some("quote".into())));
+}
+
+#[test]
+fn bad_arguments() {
+    assert_eval_err("(eval 1)", BadArity(Some("eval".into())));
+    assert_eval_err("(eval '(+ 1 2) 'env)", WrongArgumentType(atom("env")));
+    assert_eval_err(
+        "(environment '(no such library))",
+        UnknownLibrary("(no such library)".into()),
+    );
+}
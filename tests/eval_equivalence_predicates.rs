@@ -0,0 +1,65 @@
+use crate::helpers::*;
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn eq() {
+    assert_eval("(eq? 'a 'a)", "#t");
+    assert_eval("(eq? 'a 'b)", "#f");
+    assert_eval("(eq? '() '())", "#t");
+    assert_eval("(eq? #t #t)", "#t");
+    assert_eval("(eq? car car)", "#t");
+    assert_eval("(eq? (list 'a) (list 'a))", "#f");
+    assert_eval("(let ((x '(a))) (eq? x x))", "#t");
+}
+
+#[test]
+fn eq_closures() {
+    assert_eval("(let ((f (lambda () 1))) (eq? f f))", "#t");
+    assert_eval("(eq? (lambda () 1) (lambda () 1))", "#f");
+    assert_eval(
+        "(define (f) 1)
+         (eq? f f)",
+        "#t",
+    );
+}
+
+#[test]
+fn eqv() {
+    assert_eval("(eqv? 'a 'a)", "#t");
+    assert_eval("(eqv? 2 2)", "#t");
+    assert_eval("(eqv? 2 3)", "#f");
+    assert_eval("(eqv? '() '())", "#t");
+    assert_eval("(eqv? #f #f)", "#t");
+    assert_eval("(eqv? #f '())", "#f");
+    assert_eval("(eqv? (cons 1 2) (cons 1 2))", "#f");
+    assert_eval("(let ((p (lambda (x) x))) (eqv? p p))", "#t");
+}
+
+#[test]
+fn equal() {
+    assert_eval("(equal? 'a 'a)", "#t");
+    assert_eval("(equal? '(a) '(a))", "#t");
+    assert_eval("(equal? '(a (b) c) '(a (b) c))", "#t");
+    assert_eval("(equal? '(a (b) c) '(a (b) d))", "#f");
+    assert_eval("(equal? '(1 . 2) (cons 1 2))", "#t");
+    assert_eval("(equal? 2 2)", "#t");
+    assert_eval("(equal? (lambda (x) x) (lambda (y) y))", "#f");
+}
+
+#[test]
+fn equal_long_lists() {
+    assert_eval(
+        "(define (make-list n acc) (if (= n 0) acc (make-list (- n 1) (cons n acc))))
+         (define l (make-list 50000 '()))
+         (list (equal? l (make-list 50000 '()))
+               (equal? l (make-list 50000 '(0))))",
+        "'(#t #f)",
+    );
+}
+
+#[test]
+fn bad_arity() {
+    assert_eval_err("(eq? 1)", BadArity(Some("eq?".into())));
+    assert_eval_err("(eqv? 1 2 3)", BadArity(Some("eqv?".into())));
+    assert_eval_err("(equal?)", BadArity(Some("equal?".into())));
+}
@@ -0,0 +1,317 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn raise_without_a_handler() {
+    assert_eval_err("(raise 'boom)", Raised(atom("boom")));
+    assert_eval_err("(raise-continuable 'boom)", Raised(atom("boom")));
+}
+
+#[test]
+fn raise_continuable_returns_the_value_of_the_handler() {
+    assert_eval(
+        "(with-exception-handler
+           (lambda (e) (* e 2))
+           (lambda () (+ 1 (raise-continuable 20))))",
+        "41",
+    );
+}
+
+#[test]
+fn handlers_run_with_the_outer_handlers_installed() {
+    assert_eval(
+        "(with-exception-handler
+           (lambda (e) (list 'outer e))
+           (lambda ()
+             (with-exception-handler
+               (lambda (e) (raise-continuable (list 'inner e)))
+               (lambda () (raise-continuable 'x)))))",
+        "'(outer (inner x))",
+    );
+}
+
+#[test]
+fn returning_from_a_non_continuable_raise() {
+    assert_eval(
+        "(guard (e ((non-continuable-violation? e) (condition-irritants e)))
+           (with-exception-handler
+             (lambda (e) 'ignored)
+             (lambda () (raise 'boom))))",
+        "'(boom)",
+    );
+
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(with-exception-handler (lambda (e) 'ignored) (lambda () (raise 'boom)))",
+    );
+    assert!(matches!(result, Err(Error::RuntimeError(Raised(..)))));
+}
+
+#[test]
+fn guard() {
+    assert_eval(
+        "(guard (e (#t (list 'caught e))) (raise 'boom))",
+        "'(caught boom)",
+    );
+    assert_eval(
+        "(guard (e ((symbol? e) 'symbol) ((string? e) 'string))
+           (raise \"boom\"))",
+        "'string",
+    );
+    assert_eval(
+        "(guard (e ((assq 'a e) => cdr) (else 'other)) (raise '((a . 42))))",
+        "42",
+    );
+    assert_eval("(guard (e (else 'other)) (raise 1))", "'other");
+    assert_eval("(guard (e (#f 'never)) (define x 1) (+ x 1))", "2");
+    assert_eval(
+        "(let ((%guard-declined 'outer))
+           (guard (e ((symbol? e) %guard-declined)) (raise 'boom)))",
+        "'outer",
+    );
+}
+
+#[test]
+fn guard_reraises_when_no_clause_matches() {
+    assert_eval(
+        "(guard (outer (#t (list 'outer outer)))
+           (guard (inner ((string? inner) 'inner))
+             (raise 'boom)))",
+        "'(outer boom)",
+    );
+    assert_eval(
+        "(with-exception-handler
+           (lambda (e) 10)
+           (lambda ()
+             (guard (e ((string? e) 'string))
+               (+ 1 (raise-continuable 'boom)))))",
+        "11",
+    );
+    assert_eval_err(
+        "(guard (e ((string? e) 'string)) (raise 'boom))",
+        Raised(atom("boom")),
+    );
+}
+
+#[test]
+fn guard_unwinds_the_body() {
+    assert_eval(
+        "(define log '())
+         (define (note x) (set! log (cons x log)))
+         (define result
+           (guard (e (#t (note 'handled) e))
+             (note 'before)
+             (raise 'boom)
+             (note 'after)))
+         (list result log)",
+        "'(boom (handled before))",
+    );
+    assert_eval(
+        "(define (safe-div a b)
+           (guard (e (#t 'failed))
+             (if (= b 0) (raise 'division-by-zero) (div a b))))
+         (list (safe-div 10 2) (safe-div 1 0) (safe-div 9 3))",
+        "'(5 failed 3)",
+    );
+}
+
+#[test]
+fn guard_bodies_run_on_the_vm_stack() {
+    assert_eval(
+        "(define (f n)
+           (if (= n 0)
+               0
+               (+ 1 (guard (e (#t 'never)) (f (- n 1))))))
+         (f 20000)",
+        "20000",
+    );
+    assert_eval(
+        "(define (f n)
+           (if (= n 0)
+               (raise 'bottom)
+               (guard (e ((eq? e n) 'never)) (f (- n 1)))))
+         (guard (e (#t e)) (f 100))",
+        "'bottom",
+    );
+    assert_eval(
+        "(define (loop n acc)
+           (if (= n 0)
+               acc
+               (loop (- n 1) (guard (e (#t (+ acc e))) (raise n)))))
+         (loop 100000 0)",
+        "5000050000",
+    );
+}
+
+#[test]
+fn guards_escaped_to_from_callbacks() {
+    assert_eval(
+        "(define h (make-eq-hashtable))
+         (define caught
+           (guard (e (#t (list 'caught e)))
+             (hashtable-update! h 'a (lambda (v) (raise 'boom)) 0)))
+         (hashtable-update! h 'a
+                            (lambda (v) (guard (e (#t e)) (raise 'inner)))
+                            0)
+         (list caught (hashtable-ref h 'a #f))",
+        "'((caught boom) inner)",
+    );
+}
+
+#[test]
+fn handler_thunks_run_on_the_vm_stack() {
+    assert_eval(
+        "(define (f n)
+           (if (= n 0)
+               (raise-continuable 0)
+               (+ 1 (with-exception-handler
+                      (lambda (e) (+ e 1))
+                      (lambda () (f (- n 1)))))))
+         (f 20000)",
+        "20001",
+    );
+}
+
+#[test]
+fn guard_clauses_run_in_the_dynamic_environment_of_the_guard() {
+    assert_eval(
+        "(define p (make-parameter 1))
+         (guard (e (#t (p)))
+           (parameterize ((p 2))
+             (raise 'x)))",
+        "1",
+    );
+    assert_eval(
+        "(define p (make-parameter 1))
+         (with-exception-handler
+           (lambda (e) (p))
+           (lambda ()
+             (parameterize ((p 2))
+               (guard (e ((string? e) 'string))
+                 (parameterize ((p 3))
+                   (list (raise-continuable 'x) (p)))))))",
+        "'(3 3)",
+    );
+    assert_eval(
+        "(define p (make-parameter 1))
+         (list (guard (e (#t (p)))
+                 (parameterize ((p 2) (p 3))
+                   (raise 'x)))
+               (p))",
+        "'(1 1)",
+    );
+}
+
+#[test]
+fn error_and_assertion_violation() {
+    assert_eval(
+        "(guard (e ((error? e)
+                    (list (condition-who e) (condition-message e)
+                          (condition-irritants e))))
+           (error 'parse \"unexpected token\" 'foo 42))",
+        "'(parse \"unexpected token\" (foo 42))",
+    );
+    assert_eval(
+        "(guard (e ((assertion-violation? e)
+                    (list (who-condition? e) (condition-message e))))
+           (assertion-violation #f \"bad\"))",
+        "'(#f \"bad\")",
+    );
+    assert_eval_err("(error 'who 'message)", WrongArgumentType(atom("message")));
+    assert_eval_err("(error 42 \"message\")", WrongArgumentType(integer(42)));
+}
+
+#[test]
+fn assert() {
+    assert_eval("(assert (+ 1 2))", "3");
+    assert_eval(
+        "(guard (e ((assertion-violation? e)
+                    (list (condition-who e) (condition-irritants e))))
+           (assert (= 1 2)))",
+        "'(assert ((= 1 2)))",
+    );
+}
+
+#[test]
+fn assert_ignores_shadowed_assertion_violation() {
+    assert_eval(
+        "(define (h assertion-violation) (assert #f))
+         (guard (e ((assertion-violation? e) (condition-who e))) (h 0))",
+        "'assert",
+    );
+}
+
+#[test]
+fn primitive_errors_are_raised() {
+    assert_eval(
+        "(guard (e ((assertion-violation? e) (condition-irritants e)))
+           (car 1))",
+        "'(1)",
+    );
+    assert_eval(
+        "(guard (e ((undefined-violation? e) (condition-irritants e)))
+           (+ 1 missing))",
+        "'(missing)",
+    );
+    assert_eval(
+        "(guard (e ((assertion-violation? e) (condition-message e)))
+           ((lambda (x) x)))",
+        "\"wrong number of arguments\"",
+    );
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(define seen '())
+         (with-exception-handler
+           (lambda (e)
+             (set! seen (cons (non-continuable-violation? e) seen)))
+           (lambda ()
+             (guard (e ((string? e) 'string))
+               (vector-ref (vector) 0))))",
+    );
+    assert!(matches!(result, Err(Error::RuntimeError(Raised(..)))));
+    assert_eq!(
+        pair(bool(true), pair(bool(false), nil())),
+        runtime.eval_str("seen").unwrap()[0].clone()
+    );
+
+    assert_eval_err("(car 1)", WrongArgumentType(integer(1)));
+}
+
+#[test]
+fn errors_in_handlers_go_to_the_outer_handlers() {
+    assert_eval(
+        "(guard (e ((error? e) (condition-message e)))
+           (with-exception-handler
+             (lambda (e) (error 'handler \"failed\"))
+             (lambda () (raise 'boom))))",
+        "\"failed\"",
+    );
+    assert_eval(
+        "(guard (e ((assertion-violation? e) (condition-irritants e)))
+           (guard (e ((car e) 'unreachable))
+             (raise 'not-a-pair)))",
+        "'(not-a-pair)",
+    );
+}
+
+#[test]
+fn handlers_must_be_procedures() {
+    assert_eval_err(
+        "(with-exception-handler 1 (lambda () 2))",
+        WrongArgumentType(integer(1)),
+    );
+}
+
+#[test]
+fn exceptions_in_programs() {
+    assert_eq!(
+        integer(1),
+        Runtime::new()
+            .eval_program(
+                "(import (rnrs base) (rnrs exceptions))
+                 (guard (e ((symbol? e) 1)) (raise 'boom))",
+            )
+            .unwrap()[0]
+            .clone()
+    );
+}
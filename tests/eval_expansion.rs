@@ -0,0 +1,109 @@
+use crate::helpers::*;
+
+const MACROS: &str = "
+    (define-syntax my-if
+      (syntax-rules () ((_ c t e) (cond (c t) (else e)))))
+    (define-syntax my-unless
+      (syntax-rules () ((_ c e) (my-if c #f e))))
+    (define-syntax swap!
+      (syntax-rules ()
+        ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))";
+
+fn with_macros(input: &str) -> String {
+    format!("{}\n{}", MACROS, input)
+}
+
+#[test]
+fn expand() {
+    assert_eval(
+        &with_macros("(expand '(my-unless c 1))"),
+        "'(cond (c #f) (else 1))",
+    );
+    assert_eval(
+        &with_macros("(expand '(f (my-if a b c)))"),
+        "'(f (cond (a b) (else c)))",
+    );
+    assert_eval(&with_macros("(expand 1)"), "1");
+}
+
+#[test]
+fn expand_renames_introduced_bindings() {
+    assert_eval(
+        &with_macros(
+            "(define expanded (expand '(swap! tmp y)))
+             (define binding (car (car (cdr expanded))))
+             (list (car expanded)
+                   (eq? (car binding) 'tmp)
+                   (car (cdr binding))
+                   (car (cdr (cdr expanded))))",
+        ),
+        "'(let #f tmp (set! tmp y))",
+    );
+}
+
+#[test]
+fn expand_once() {
+    assert_eval(
+        &with_macros(
+            "(define expanded (expand-once '(my-unless c 1)))
+             (list (eq? (car expanded) 'my-if) (cdr expanded))",
+        ),
+        "'(#f (c #f 1))",
+    );
+    assert_eval(
+        &with_macros("(expand-once '(f (my-if a b c)))"),
+        "'(f (my-if a b c))",
+    );
+    assert_eval(&with_macros("(expand-once 'x)"), "'x");
+}
+
+#[test]
+fn runtime_expand() {
+    let mut runtime = Runtime::new();
+    runtime.eval_str(MACROS).unwrap();
+
+    let exprs = runtime.parse_str("(my-unless c 1) (f x)").unwrap();
+    assert_eq!(
+        "(cond (c #f) (else 1))",
+        runtime.expand(&exprs[0]).unwrap().to_string()
+    );
+    let expanded = runtime.expand_once(&exprs[0]).unwrap().unwrap();
+    assert!(expanded.to_string().starts_with("(my-if%"));
+    assert!(expanded.to_string().ends_with(" c #f 1)"));
+    assert_eq!(None, runtime.expand_once(&exprs[1]).unwrap());
+}
+
+#[test]
+fn runtime_expand_step() {
+    let mut runtime = Runtime::new();
+    runtime.eval_str(MACROS).unwrap();
+
+    let exprs = runtime
+        .parse_str(
+            "(f (my-unless c 1) (swap! a b))
+             (lambda (my-if) (my-if 1 2 3))
+             '(my-if 1 2 3)",
+        )
+        .unwrap();
+
+    let mut steps = vec![];
+    let mut form = exprs[0].clone();
+    while let Some(expanded) = runtime.expand_step(&form).unwrap() {
+        steps.push(expanded.to_string());
+        form = expanded;
+    }
+    assert_eq!(3, steps.len());
+    assert!(steps[0].starts_with("(f (my-if%"));
+    assert!(steps[0].ends_with(" c #f 1) (swap! a b))"));
+    assert!(steps[1].starts_with("(f (cond%"));
+    assert!(steps[1].ends_with(" 1)) (swap! a b))"));
+    assert!(steps[2].contains(") (let%"));
+    assert!(runtime
+        .expand(&form)
+        .unwrap()
+        .to_string()
+        .starts_with("(f (cond (c #f) (else 1)) (let ((tmp%"));
+
+    assert_eq!(None, runtime.expand_step(&exprs[1]).unwrap());
+    assert_eq!(None, runtime.expand_step(&exprs[2]).unwrap());
+}
@@ -0,0 +1,155 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+use std::{path::Path, process::Command};
+
+#[test]
+fn load_runs_a_file_at_the_top_level() {
+    assert_eval(
+        "(load \"tests/files/main.scm\")
+         (list result loaded-more)",
+        "'(42 yes)",
+    );
+}
+
+#[test]
+fn load_fails_on_missing_files() {
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str("(load \"files/main.scm\")");
+
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(LoadFailed(ref path, _)))
+            if path == "files/main.scm"
+    ));
+}
+
+#[test]
+fn load_takes_a_string() {
+    assert_eval_err("(load 'main)", WrongArgumentType(atom("main")));
+}
+
+#[test]
+fn include_at_the_top_level() {
+    assert_eval(
+        "(include \"tests/files/included.scm\")
+         (triple included)",
+        "3",
+    );
+}
+
+#[test]
+fn include_in_a_body() {
+    assert_eval(
+        "(define (f)
+           (include \"tests/files/body.scm\"))
+         (f)",
+        "10",
+    );
+    assert_eval(
+        "(let ((x (include \"tests/files/included.scm\")))
+           (triple 2))",
+        "6",
+    );
+}
+
+#[test]
+fn include_ci_folds_identifiers() {
+    assert_eval("(include-ci \"tests/files/upper.scm\") shout", "'loud");
+}
+
+#[test]
+fn errors_are_located() {
+    let missing = || UnboundVariable("missing".into());
+
+    assert_eval_err(
+        "(load \"tests/files/broken.scm\")",
+        Located("tests/files/broken.scm:3:3".into(), Box::new(missing())),
+    );
+    assert_eval_err(
+        "(include \"tests/files/broken.scm\")",
+        Located("tests/files/broken.scm:3:3".into(), Box::new(missing())),
+    );
+    assert_eval_err(
+        "(define (f)
+           (include \"tests/files/unbound.scm\"))
+         (f)",
+        Located("tests/files/unbound.scm:1:1".into(), Box::new(missing())),
+    );
+    assert_eval_err(
+        "(let ((x (include \"tests/files/unbound.scm\")))
+           x)",
+        Located("tests/files/unbound.scm:1:1".into(), Box::new(missing())),
+    );
+
+    let mut runtime = Runtime::new();
+    assert!(runtime
+        .eval_str("(load \"tests/files/broken.scm\")")
+        .is_err());
+    assert_eq!(
+        integer(1),
+        runtime.eval_str("before-error").unwrap()[0].clone()
+    );
+    assert!(runtime.eval_str("after-error").is_err());
+}
+
+#[test]
+fn located_errors_can_be_caught() {
+    assert_eval(
+        "(guard (e ((undefined-violation? e) (condition-irritants e)))
+           (load \"tests/files/broken.scm\"))",
+        "'(missing)",
+    );
+}
+
+#[test]
+fn load_failures_are_conditions() {
+    assert_eval(
+        "(guard (e ((i/o-filename-error? e)
+                    (list (i/o-error? e) (i/o-error-filename e))))
+           (load \"tests/files/missing.scm\"))",
+        "'(#t \"tests/files/missing.scm\")",
+    );
+    assert_eval(
+        "(guard (e ((lexical-violation? e) (condition-irritants e)))
+           (load \"tests/files/unbalanced.scm\"))",
+        "'(\"tests/files/unbalanced.scm\")",
+    );
+}
+
+#[test]
+fn syntax_errors_are_located() {
+    assert_eval_err(
+        "(load \"tests/files/unbalanced.scm\")",
+        LexicalViolation(
+            "tests/files/unbalanced.scm".into(),
+            "invalid syntax at line 2, column 1".into(),
+        ),
+    );
+    assert_eval_err(
+        "(load \"tests/files/unfinished.scm\")",
+        LexicalViolation(
+            "tests/files/unfinished.scm".into(),
+            "invalid syntax at line 2, column 1".into(),
+        ),
+    );
+}
+
+#[test]
+fn eval_file_returns_errors() {
+    assert_eq!(
+        Err(Located(
+            "tests/files/broken.scm:3:3".into(),
+            Box::new(UnboundVariable("missing".into()))
+        )),
+        Runtime::new().eval_file(Path::new("tests/files/broken.scm"))
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ostrov"))
+        .arg("tests/files/broken.scm")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("tests/files/broken.scm:3:3"));
+}
@@ -0,0 +1,174 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn eq_hashtables() {
+    assert_eval(
+        "(define h (make-eq-hashtable))
+         (hashtable-set! h 'a 1)
+         (hashtable-set! h 'b 2)
+         (hashtable-set! h 'a 3)
+         (list (hashtable-ref h 'a #f)
+               (hashtable-ref h 'b #f)
+               (hashtable-ref h 'c 'none)
+               (hashtable-size h))",
+        "'(3 2 none 2)",
+    );
+}
+
+#[test]
+fn eq_hashtables_use_identity() {
+    assert_eval(
+        "(define h (make-eq-hashtable))
+         (hashtable-set! h (list 1) 'x)
+         (hashtable-contains? h (list 1))",
+        "#f",
+    );
+}
+
+#[test]
+fn eqv_hashtables() {
+    assert_eval(
+        "(define h (make-eqv-hashtable 10))
+         (hashtable-set! h 100000 'big)
+         (hashtable-set! h #\\a 'char)
+         (list (hashtable-ref h 100000 #f) (hashtable-ref h #\\a #f))",
+        "'(big char)",
+    );
+}
+
+#[test]
+fn custom_hashtables() {
+    assert_eval(
+        "(define h (make-hashtable string-hash equal?))
+         (hashtable-set! h \"one\" 1)
+         (hashtable-set! h (string #\\o #\\n #\\e) 11)
+         (list (hashtable-ref h \"one\" #f) (hashtable-size h))",
+        "'(11 1)",
+    );
+    assert_eval(
+        "(define (mod10 n) (- n (* 10 (/ n 10))))
+         (define h (make-hashtable mod10 =))
+         (hashtable-set! h 1 'one)
+         (hashtable-set! h 11 'eleven)
+         (hashtable-set! h 21 'twenty-one)
+         (hashtable-delete! h 11)
+         (list (hashtable-ref h 1 #f)
+               (hashtable-ref h 11 #f)
+               (hashtable-ref h 21 #f))",
+        "'(one #f twenty-one)",
+    );
+    assert_eval_err(
+        "(define h (make-hashtable (lambda (k) 'oops) eq?))
+         (hashtable-set! h 1 1)",
+        WrongArgumentType(atom("oops")),
+    );
+}
+
+#[test]
+fn custom_hashtables_changed_while_looking_up() {
+    assert_eval(
+        "(define t #f)
+         (define (equiv a b) (hashtable-clear! t) #t)
+         (set! t (make-hashtable (lambda (k) 0) equiv))
+         (hashtable-set! t 1 'one)
+         (hashtable-set! t 2 'two)
+         (list (hashtable-ref t 1 0) (hashtable-size t))",
+        "'(0 0)",
+    );
+}
+
+#[test]
+fn hashtable_update() {
+    assert_eval(
+        "(define h (make-eq-hashtable))
+         (define (count! k) (hashtable-update! h k (lambda (n) (+ n 1)) 0))
+         (count! 'a)
+         (count! 'b)
+         (count! 'a)
+         (list (hashtable-ref h 'a 0) (hashtable-ref h 'b 0))",
+        "'(2 1)",
+    );
+}
+
+#[test]
+fn hashtable_delete_and_contains() {
+    assert_eval(
+        "(define h (make-eqv-hashtable))
+         (hashtable-set! h 1 'a)
+         (hashtable-set! h 2 'b)
+         (hashtable-delete! h 1)
+         (hashtable-delete! h 3)
+         (list (hashtable-contains? h 1)
+               (hashtable-contains? h 2)
+               (hashtable-size h))",
+        "'(#f #t 1)",
+    );
+}
+
+#[test]
+fn hashtable_keys_and_entries() {
+    assert_eval(
+        "(define h (make-eqv-hashtable))
+         (hashtable-set! h 1 'a)
+         (hashtable-keys h)",
+        "#(1)",
+    );
+    assert_eval(
+        "(define h (make-eqv-hashtable))
+         (hashtable-set! h 1 'a)
+         (call-with-values (lambda () (hashtable-entries h)) list)",
+        "'(#(1) #(a))",
+    );
+}
+
+#[test]
+fn hashtable_copy() {
+    assert_eval(
+        "(define h (make-eqv-hashtable))
+         (hashtable-set! h 1 'a)
+         (define c (hashtable-copy h #t))
+         (hashtable-set! c 1 'b)
+         (list (hashtable-ref h 1 #f)
+               (hashtable-ref c 1 #f)
+               (hashtable-mutable? c))",
+        "'(a b #t)",
+    );
+    assert_eval(
+        "(hashtable-mutable? (hashtable-copy (make-eq-hashtable)))",
+        "#f",
+    );
+
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(define h (hashtable-copy (make-eq-hashtable)))
+         (hashtable-set! h 1 1)",
+    );
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(ImmutableValue(..)))
+    ));
+}
+
+#[test]
+fn hashtable_clear() {
+    assert_eval(
+        "(define h (make-eq-hashtable))
+         (hashtable-set! h 'a 1)
+         (hashtable-clear! h)
+         (hashtable-size h)",
+        "0",
+    );
+}
+
+#[test]
+fn hash_functions() {
+    assert_eval("(= (string-hash \"abc\") (string-hash \"abc\"))", "#t");
+    assert_eval("(= (symbol-hash 'abc) (symbol-hash 'abc))", "#t");
+    assert_eval(
+        "(= (equal-hash '(1 #(2))) (equal-hash (list 1 (vector 2))))",
+        "#t",
+    );
+    assert_eval_err("(string-hash 'abc)", WrongArgumentType(atom("abc")));
+    assert_eval_err("(symbol-hash \"abc\")", WrongArgumentType(string("abc")));
+}
@@ -0,0 +1,72 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn do_loop() {
+    assert_eval(
+        "(do ((i 0 (+ i 1))
+              (acc '() (cons i acc)))
+             ((= i 5) acc))",
+        "'(4 3 2 1 0)",
+    );
+}
+
+#[test]
+fn do_with_commands() {
+    assert_eval(
+        "(define v (make-vector 5))
+         (do ((i 0 (+ i 1)))
+             ((= i 5) v)
+           (vector-set! v i (* i i)))",
+        "#(0 1 4 9 16)",
+    );
+}
+
+#[test]
+fn do_variables_without_step() {
+    assert_eval(
+        "(let ((x '(1 3 5 7 9)))
+           (do ((x x (cdr x))
+                (sum 0 (+ sum (car x)))
+                (unchanged 'same))
+               ((null? x) (list sum unchanged))))",
+        "'(25 same)",
+    );
+}
+
+#[test]
+fn do_results() {
+    assert_eval_val("(do ((i 0 (+ i 1))) ((= i 3)))", unspecified());
+    assert_eval("(do ((i 0 (+ i 1))) ((= i 3) 'a 'b))", "'b");
+}
+
+#[test]
+fn do_steps_are_evaluated_together() {
+    assert_eval(
+        "(do ((a 0 b) (b 1 (+ a b)) (n 0 (+ n 1)))
+             ((= n 10) a))",
+        "55",
+    );
+}
+
+#[test]
+fn do_runs_in_constant_space() {
+    assert_eval("(do ((i 0 (+ i 1))) ((= i 100000) i))", "100000");
+}
+
+#[test]
+fn do_loop_does_not_capture_variables() {
+    assert_eval(
+        "(let ((%do-loop 'outer))
+           (do ((i 0 (+ i 1))) ((= i 2) %do-loop)))",
+        "'outer",
+    );
+}
+
+#[test]
+fn do_malformed() {
+    assert_eval_err("(do)", BadArity(Some("do".into())));
+    assert_eval_err("(do ((i 0)) ())", MalformedExpression);
+    assert_eval_err("(do (i) (#t))", MalformedExpression);
+    assert_eval_err("(do ((i 0 1 2)) (#t))", MalformedExpression);
+}
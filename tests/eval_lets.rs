@@ -23,7 +23,13 @@ fn one_definition() {
 #[test]
 fn bad_arity_() {
     assert_eval_err("(let)", BadArity(Some("let".into())));
-    assert_eval_err("(let 1)", BadArity(Some("let".into())));
+}
+
+#[test]
+fn empty_body() {
+    assert_eval_err("(let 1)", MalformedExpression);
+    assert_eval_err("(let ((x 1)))", MalformedExpression);
+    assert_eval_err("(let loop ((x 1)))", MalformedExpression);
 }
 
 #[test]
@@ -34,3 +40,94 @@ fn malformed() {
     assert_eval_err("(let ((a)) 2)", MalformedExpression);
     assert_eval_err("(let ((a) b) 2)", MalformedExpression);
 }
+
+#[test]
+fn let_star() {
+    assert_eval(
+        "(let* ((x 1)
+                (y (+ x 1))
+                (x (* y 10)))
+           (list x y))",
+        "'(20 2)",
+    );
+    assert_eval("(let* () 1)", "1");
+    assert_eval_err("(let* ((x 1)))", MalformedExpression);
+}
+
+#[test]
+fn letrec() {
+    assert_eval(
+        "(letrec ((even? (lambda (n) (if (= n 0) #t (odd? (- n 1)))))
+                  (odd? (lambda (n) (if (= n 0) #f (even? (- n 1))))))
+           (list (even? 100) (odd? 7)))",
+        "'(#t #t)",
+    );
+    assert_eval_err(
+        "(letrec ((a 1) (b (+ a 1))) b)",
+        UninitializedVariable("a".into()),
+    );
+    assert_eval_err(
+        "(letrec ((a b) (b 1)) a)",
+        UninitializedVariable("b".into()),
+    );
+}
+
+#[test]
+fn letrec_star() {
+    assert_eval(
+        "(letrec* ((a 1)
+                   (f (lambda () (+ a b)))
+                   (b (+ a 1)))
+           (f))",
+        "3",
+    );
+    assert_eval_err(
+        "(letrec* ((a b) (b 1)) a)",
+        UninitializedVariable("b".into()),
+    );
+    assert_eval_err(
+        "(letrec* ((f (lambda () b)) (a (f)) (b 1)) a)",
+        UninitializedVariable("b".into()),
+    );
+}
+
+#[test]
+fn letrec_does_not_leak_bindings() {
+    assert_eval(
+        "(define a 'outer)
+         (letrec ((a 1)) a)
+         a",
+        "'outer",
+    );
+}
+
+#[test]
+fn named_let() {
+    assert_eval(
+        "(let loop ((i 0) (acc '()))
+           (if (= i 3) acc (loop (+ i 1) (cons i acc))))",
+        "'(2 1 0)",
+    );
+    assert_eval(
+        "(define (f loop) (let loop ((n loop)) (if (= n 0) 'done (loop (- n 1)))))
+         (f 3)",
+        "'done",
+    );
+}
+
+#[test]
+fn named_let_inits_do_not_see_the_name() {
+    assert_eval(
+        "(define loop 5)
+         (let loop ((n loop)) (if (= n 0) 'done (loop (- n 1))))",
+        "'done",
+    );
+}
+
+#[test]
+fn named_let_runs_in_constant_space() {
+    assert_eval(
+        "(let loop ((i 0)) (if (= i 100000) i (loop (+ i 1))))",
+        "100000",
+    );
+}
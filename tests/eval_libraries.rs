@@ -0,0 +1,282 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+use std::path::PathBuf;
+
+const STACKS: &str = "
+    (library (data stacks)
+      (export make-stack push! (rename (stack-top top)) stack-size)
+      (import (rnrs) (rnrs mutable-pairs))
+      (define (make-stack) (list 'stack))
+      (define (push! stack value) (set-cdr! stack (cons value (cdr stack))))
+      (define (stack-top stack) (car (cdr stack)))
+      (define (stack-size stack) (length (cdr stack))))";
+
+fn with_stacks(input: &str) -> String {
+    format!("{}\n{}", STACKS, input)
+}
+
+#[test]
+fn exports() {
+    assert_eval(
+        &with_stacks(
+            "(import (data stacks))
+             (define stack (make-stack))
+             (push! stack 1)
+             (push! stack 2)
+             (list (top stack) (stack-size stack))",
+        ),
+        "'(2 2)",
+    );
+    assert_eval_err(
+        &with_stacks("(import (data stacks)) (stack-top (make-stack))"),
+        UnboundVariable("stack-top".into()),
+    );
+}
+
+#[test]
+fn import_sets() {
+    assert_eval(
+        &with_stacks(
+            "(import (only (data stacks) make-stack stack-size))
+             (stack-size (make-stack))",
+        ),
+        "0",
+    );
+    assert_eval_err(
+        &with_stacks(
+            "(import (only (data stacks) make-stack stack-size))
+             (push! (make-stack) 1)",
+        ),
+        UnboundVariable("push!".into()),
+    );
+    assert_eval_err(
+        &with_stacks("(import (except (data stacks) top)) (top (make-stack))"),
+        UnboundVariable("top".into()),
+    );
+    assert_eval(
+        &with_stacks(
+            "(import (prefix (data stacks) s:))
+             (define stack (s:make-stack))
+             (s:push! stack 'a)
+             (s:top stack)",
+        ),
+        "'a",
+    );
+    assert_eval(
+        &with_stacks(
+            "(import (rename (data stacks) (make-stack new) (top peek)))
+             (define stack (new))
+             (push! stack 'a)
+             (peek stack)",
+        ),
+        "'a",
+    );
+    assert_eval_err(
+        &with_stacks("(import (only (data stacks) stack-top))"),
+        SyntaxViolation(
+            Some("import".into()),
+            "identifier not exported".into(),
+            pair(
+                atom("only"),
+                pair(
+                    pair(atom("data"), pair(atom("stacks"), nil())),
+                    pair(atom("stack-top"), nil()),
+                ),
+            ),
+            atom("stack-top"),
+        ),
+    );
+}
+
+#[test]
+fn libraries_have_their_own_environment() {
+    assert_eval(
+        "(library (counter)
+           (export next!)
+           (import (rnrs base))
+           (define count 0)
+           (define (next!) (set! count (+ count 1)) count))
+         (import (counter))
+         (define count 10)
+         (next!)
+         (list (next!) count)",
+        "'(2 10)",
+    );
+    assert_eval_err(
+        "(define secret 1)
+         (library (spy)
+           (export peek)
+           (import (only (rnrs base) define))
+           (define (peek) secret))
+         (import (spy))
+         (peek)",
+        UnboundVariable("secret".into()),
+    );
+    assert_eval_err(
+        "(library (bare)
+           (export first)
+           (import (only (rnrs base) define quote))
+           (define (first) (car '(1))))
+         (import (bare))
+         (first)",
+        UnboundVariable("car".into()),
+    );
+}
+
+#[test]
+fn imported_variables_share_their_location() {
+    assert_eval(
+        "(library (counter)
+           (export count increment!)
+           (import (rnrs base))
+           (define count 0)
+           (define (increment!) (set! count (+ count 1))))
+         (import (counter))
+         (increment!)
+         (increment!)
+         count",
+        "2",
+    );
+}
+
+#[test]
+fn imported_variables_are_immutable() {
+    assert_eval_err(
+        "(library (counter)
+           (export count)
+           (import (rnrs base))
+           (define count 0))
+         (import (counter))
+         (set! count 1)",
+        SyntaxViolation(
+            Some("set!".into()),
+            "assignment of an imported variable".into(),
+            pair(atom("set!"), pair(atom("count"), pair(integer(1), nil()))),
+            atom("count"),
+        ),
+    );
+    assert_eval_err(
+        "(library (counter)
+           (export count)
+           (import (rnrs base))
+           (define count 0))
+         (import (counter))
+         (define count 1)",
+        SyntaxViolation(
+            Some("define".into()),
+            "definition of an imported identifier".into(),
+            atom("count"),
+            atom("count"),
+        ),
+    );
+}
+
+#[test]
+fn exported_macros() {
+    assert_eval(
+        "(library (swapping)
+           (export swap!)
+           (import (rnrs base))
+           (define swaps 0)
+           (define (count-swap!) (set! swaps (+ swaps 1)) swaps)
+           (define-syntax swap!
+             (syntax-rules ()
+               ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp) (count-swap!))))))
+         (import (swapping))
+         (define (count-swap!) 'shadowed)
+         (define tmp 1)
+         (define other 2)
+         (swap! tmp other)
+         (list tmp other (swap! tmp other))",
+        "'(2 1 2)",
+    );
+}
+
+#[test]
+fn libraries_import_libraries() {
+    assert_eval(
+        &with_stacks(
+            "(library (data queues)
+               (export make-queue enqueue! queue-size)
+               (import (rnrs base) (prefix (data stacks) stack:))
+               (define (make-queue) (stack:make-stack))
+               (define enqueue! stack:push!)
+               (define queue-size stack:stack-size))
+             (import (data queues))
+             (define queue (make-queue))
+             (enqueue! queue 1)
+             (queue-size queue)",
+        ),
+        "1",
+    );
+}
+
+#[test]
+fn versions() {
+    let library = "(library (versioned (2 1))
+           (export v)
+           (import (rnrs base))
+           (define v 'ok))";
+
+    for reference in [
+        "(versioned)",
+        "(versioned ())",
+        "(versioned (2))",
+        "(versioned (2 1))",
+        "(versioned ((>= 2) (<= 1)))",
+        "(versioned (or (1) (2 (not 0))))",
+    ] {
+        assert_eval(
+            &format!("{} (import (only {} v)) v", library, reference),
+            "'ok",
+        );
+    }
+
+    assert_eval_err(
+        &format!("{} (import (versioned (2 1 0)))", library),
+        UnknownLibrary("(versioned (2 1 0))".into()),
+    );
+    assert_eval_err(
+        &format!("{} (import (versioned ((>= 3))))", library),
+        UnknownLibrary("(versioned ((>= 3)))".into()),
+    );
+}
+
+#[test]
+fn unknown_libraries() {
+    assert_eval_err(
+        "(import (no such library))",
+        UnknownLibrary("(no such library)".into()),
+    );
+}
+
+#[test]
+fn exports_must_be_defined() {
+    assert_eval_err(
+        "(library (broken) (export missing) (import))",
+        SyntaxViolation(
+            Some("library".into()),
+            "exported identifier not defined".into(),
+            pair(atom("export"), pair(atom("missing"), nil())),
+            atom("missing"),
+        ),
+    );
+}
+
+#[test]
+fn library_path() {
+    let mut runtime = Runtime::new();
+    runtime.set_library_path(vec![PathBuf::from("tests/libraries")]);
+
+    let result = runtime.eval_str(
+        "(import (geometry shapes (1)))
+         (list (square-area 2) (circle-area 2))",
+    );
+    assert_eq!(
+        pair(integer(4), pair(integer(12), nil())),
+        result.unwrap()[1]
+    );
+
+    let result = runtime.eval_str("(import (geometry constants)) pi");
+    assert_eq!(integer(3), result.unwrap()[1]);
+}
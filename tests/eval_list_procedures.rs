@@ -119,3 +119,49 @@ fn list_question_mark_bad_arity() {
     assert_eval_err("(list?)", BadArity(Some("list?".into())));
     assert_eval_err("(list? '() '())", BadArity(Some("list?".into())));
 }
+
+#[test]
+fn memq_memv_member() {
+    assert_eval("(memq 'a '(a b c))", "'(a b c)");
+    assert_eval("(memq 'b '(a b c))", "'(b c)");
+    assert_eval("(memq 'a '(b c d))", "#f");
+    assert_eval("(memq (list 'a) '(b (a) c))", "#f");
+    assert_eval("(member (list 'a) '(b (a) c))", "'((a) c)");
+    assert_eval("(memv 101 '(100 101 102))", "'(101 102)");
+    assert_eval("(memv 1 '())", "#f");
+}
+
+#[test]
+fn memv_wrong_argument_type() {
+    assert_eval_err("(memv 1 2)", WrongArgumentType(integer(2)));
+    assert_eval_err(
+        "(memv 1 '(2 . 3))",
+        WrongArgumentType(pair(integer(2), integer(3))),
+    );
+}
+
+#[test]
+fn assq_assv_assoc() {
+    assert_eval("(assq 'a '((a 1) (b 2) (c 3)))", "'(a 1)");
+    assert_eval("(assq 'b '((a 1) (b 2) (c 3)))", "'(b 2)");
+    assert_eval("(assq 'd '((a 1) (b 2) (c 3)))", "#f");
+    assert_eval("(assq (list 'a) '(((a)) ((b)) ((c))))", "#f");
+    assert_eval("(assoc (list 'a) '(((a)) ((b)) ((c))))", "'((a))");
+    assert_eval("(assv 5 '((2 3) (5 7) (11 13)))", "'(5 7)");
+}
+
+#[test]
+fn assv_wrong_argument_type() {
+    assert_eval_err("(assv 1 '(2))", WrongArgumentType(integer(2)));
+}
+
+#[test]
+fn long_lists_are_dropped() {
+    assert_eval(
+        "(define (make-list n acc) (if (= n 0) acc (make-list (- n 1) (cons n acc))))
+         (define l (make-list 200000 '()))
+         (set! l 0)
+         l",
+        "0",
+    );
+}
@@ -0,0 +1,324 @@
+use crate::helpers::*;
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn define_syntax() {
+    assert_eval(
+        "(define-syntax swap!
+           (syntax-rules ()
+             ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))
+         (define x 1)
+         (define y 2)
+         (swap! x y)
+         (list x y)",
+        "'(2 1)",
+    );
+}
+
+#[test]
+fn macros_are_expanded_recursively() {
+    assert_eval(
+        "(define-syntax my-and
+           (syntax-rules ()
+             ((_) #t)
+             ((_ e) e)
+             ((_ e1 e2 ...) (if e1 (my-and e2 ...) #f))))
+         (list (my-and) (my-and 1 2 3) (my-and 1 #f 3))",
+        "'(#t 3 #f)",
+    );
+}
+
+#[test]
+fn no_matching_rule() {
+    assert_eval_err(
+        "(define-syntax one (syntax-rules () ((_ a) a)))
+         (one 1 2)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn keywords_are_not_variables() {
+    assert_eval_err(
+        "(define-syntax one (syntax-rules () ((_ a) a)))
+         (list one)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn introduced_bindings_do_not_capture() {
+    assert_eval(
+        "(define-syntax my-or
+           (syntax-rules ()
+             ((_ a b) (let ((t a)) (if t t b)))))
+         (define t 5)
+         (my-or #f t)",
+        "5",
+    );
+    assert_eval(
+        "(define-syntax my-or
+           (syntax-rules ()
+             ((_ a b) (let ((t a)) (if t t b)))))
+         (let ((t 5)) (my-or #f t))",
+        "5",
+    );
+    assert_eval(
+        "(define-syntax swap!
+           (syntax-rules ()
+             ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))
+         (let ((tmp 1) (other 2))
+           (swap! tmp other)
+           (list tmp other))",
+        "'(2 1)",
+    );
+}
+
+#[test]
+fn free_identifiers_refer_to_the_definition() {
+    assert_eval(
+        "(define x 'global)
+         (define-syntax get-x (syntax-rules () ((_) x)))
+         (let ((x 'local)) (get-x))",
+        "'global",
+    );
+    assert_eval(
+        "(define-syntax my-or
+           (syntax-rules ()
+             ((_ a b) (let ((t a)) (if t t b)))))
+         (let ((if list)) (my-or #f 1))",
+        "1",
+    );
+    assert_eval(
+        "(define (f x)
+           (define-syntax get-x (syntax-rules () ((_) x)))
+           (let ((x 'inner)) (get-x)))
+         (f 'outer)",
+        "'outer",
+    );
+}
+
+#[test]
+fn shadowed_special_forms_are_variables() {
+    assert_eval("(let ((if list)) (if 1 2))", "'(1 2)");
+}
+
+#[test]
+fn quoted_identifiers_are_symbols() {
+    assert_eval(
+        "(define-syntax name (syntax-rules () ((_) 'name)))
+         (name)",
+        "'name",
+    );
+}
+
+#[test]
+fn literals() {
+    assert_eval(
+        "(define-syntax arrow
+           (syntax-rules (=>)
+             ((_ a => b) (list a b))
+             ((_ a b c) 'no-arrow)))
+         (list (arrow 1 => 2) (arrow 1 2 3))",
+        "'((1 2) no-arrow)",
+    );
+    assert_eval(
+        "(define-syntax arrow
+           (syntax-rules (=>)
+             ((_ a => b) (list a b))
+             ((_ a b c) 'no-arrow)))
+         (let ((=> #f)) (arrow 1 => 2))",
+        "'no-arrow",
+    );
+}
+
+#[test]
+fn underscore() {
+    assert_eval(
+        "(define-syntax second (syntax-rules () ((_ _ b . _) b)))
+         (second 1 2 3 4)",
+        "2",
+    );
+}
+
+#[test]
+fn ellipses() {
+    assert_eval(
+        "(define-syntax my-list (syntax-rules () ((_ a ...) (list a ...))))
+         (list (my-list) (my-list 1 2 3))",
+        "'(() (1 2 3))",
+    );
+    assert_eval(
+        "(define-syntax ends
+           (syntax-rules () ((_ first middle ... last) (list first last))))
+         (ends 1 2 3 4)",
+        "'(1 4)",
+    );
+    assert_eval(
+        "(define-syntax rest (syntax-rules () ((_ a ... . r) 'r)))
+         (rest 1 2 . 3)",
+        "3",
+    );
+}
+
+#[test]
+fn nested_ellipses() {
+    assert_eval(
+        "(define-syntax my-let*
+           (syntax-rules ()
+             ((_ () body ...) (let () body ...))
+             ((_ ((x v) rest ...) body ...)
+              (let ((x v)) (my-let* (rest ...) body ...)))))
+         (my-let* ((a 1) (b (+ a 1))) (list a b))",
+        "'(1 2)",
+    );
+    assert_eval(
+        "(define-syntax flatten
+           (syntax-rules () ((_ (a ...) ...) '(a ... ...))))
+         (flatten (1 2) () (3))",
+        "'(1 2 3)",
+    );
+    assert_eval(
+        "(define-syntax my-cond
+           (syntax-rules (else)
+             ((_ (else e ...)) (begin e ...))
+             ((_ (c e ...) clause ...) (if c (begin e ...) (my-cond clause ...)))))
+         (list (my-cond (#f 1) (else 2 3)) (my-cond (#t 4 5) (else 6)))",
+        "'(3 5)",
+    );
+    assert_eval_err(
+        "(define-syntax bad (syntax-rules () ((_ (a ...) (b ...)) '((a b) ...))))
+         (bad (1 2) (3))",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn escaped_ellipses() {
+    assert_eval(
+        "(define-syntax be-like-begin
+           (syntax-rules ()
+             ((_ name)
+              (define-syntax name
+                (syntax-rules ()
+                  ((_ e (... ...)) (begin e (... ...))))))))
+         (be-like-begin sequence)
+         (sequence 1 2 3)",
+        "3",
+    );
+}
+
+#[test]
+fn custom_ellipsis() {
+    assert_eval(
+        "(define-syntax my-quote
+           (syntax-rules etc () ((_ a etc) '(a etc))))
+         (my-quote 1 2 ...)",
+        "'(1 2 ...)",
+    );
+}
+
+#[test]
+fn vector_patterns() {
+    assert_eval(
+        "(define-syntax vector-sum
+           (syntax-rules () ((_ #(a b ...)) (+ a b ...))))
+         (vector-sum #(1 2 3))",
+        "6",
+    );
+    assert_eval(
+        "(define-syntax reversed (syntax-rules () ((_ a b) #(b a))))
+         (reversed 1 2)",
+        "#(2 1)",
+    );
+}
+
+#[test]
+fn macros_expanding_to_definitions() {
+    assert_eval(
+        "(define-syntax define-getter
+           (syntax-rules () ((_ name value) (define (name) value))))
+         (define-getter answer 42)
+         (answer)",
+        "42",
+    );
+    assert_eval(
+        "(define-syntax define-two
+           (syntax-rules ()
+             ((_ a b) (begin (define a 1) (define b 2)))))
+         (define (f)
+           (define-two x y)
+           (+ x y))
+         (f)",
+        "3",
+    );
+}
+
+#[test]
+fn internal_define_syntax() {
+    assert_eval(
+        "(define (f x)
+           (define (g) (double x))
+           (define-syntax double (syntax-rules () ((_ e) (* 2 e))))
+           (g))
+         (f 4)",
+        "8",
+    );
+}
+
+#[test]
+fn let_syntax() {
+    assert_eval(
+        "(let-syntax ((double (syntax-rules () ((_ e) (* 2 e)))))
+           (double 3))",
+        "6",
+    );
+    assert_eval(
+        "(define-syntax f (syntax-rules () ((_) 'outer)))
+         (let-syntax ((f (syntax-rules () ((_) 'inner)))
+                      (g (syntax-rules () ((_) (f)))))
+           (list (f) (g)))",
+        "'(inner outer)",
+    );
+}
+
+#[test]
+fn letrec_syntax() {
+    assert_eval(
+        "(letrec-syntax
+             ((my-or (syntax-rules ()
+                       ((_) #f)
+                       ((_ e) e)
+                       ((_ e r ...) (let ((t e)) (if t t (my-or r ...)))))))
+           (let ((t 5))
+             (my-or #f t)))",
+        "5",
+    );
+}
+
+#[test]
+fn macros_defining_macros() {
+    assert_eval(
+        "(define-syntax define-constant
+           (syntax-rules ()
+             ((_ name value) (define-syntax name (syntax-rules () ((_) value))))))
+         (define-constant five 5)
+         (five)",
+        "5",
+    );
+}
+
+#[test]
+fn while_loop() {
+    assert_eval(
+        "(define-syntax while
+           (syntax-rules ()
+             ((_ condition body ...)
+              (let loop () (when condition body ... (loop))))))
+         (define i 0)
+         (define loop 'user)
+         (while (< i 10) (set! i (+ i 1)))
+         (list i loop)",
+        "'(10 user)",
+    );
+}
@@ -0,0 +1,157 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn set_car() {
+    assert_eval(
+        "(define p (cons 1 2))
+         (set-car! p 3)
+         p",
+        "'(3 . 2)",
+    );
+}
+
+#[test]
+fn set_cdr() {
+    assert_eval(
+        "(define p (list 1 2))
+         (set-cdr! p '(4 5))
+         p",
+        "'(1 4 5)",
+    );
+}
+
+#[test]
+fn mutation_is_visible_through_aliases() {
+    assert_eval(
+        "(define x (list 'a 'b))
+         (define y (cons 'c x))
+         (set-car! x 'z)
+         y",
+        "'(c z b)",
+    );
+    assert_eval(
+        "(define x (list 1 2))
+         (define (poke! p) (set-cdr! p '()))
+         (poke! x)
+         x",
+        "'(1)",
+    );
+}
+
+#[test]
+fn queue() {
+    assert_eval(
+        "(define (make-queue) (cons '() '()))
+         (define (enqueue! q x)
+           (let ((cell (list x)))
+             (if (null? (car q))
+                 (set-car! q cell)
+                 (set-cdr! (cdr q) cell))
+             (set-cdr! q cell)))
+         (define q (make-queue))
+         (enqueue! q 1)
+         (enqueue! q 2)
+         (enqueue! q 3)
+         (car q)",
+        "'(1 2 3)",
+    );
+}
+
+#[test]
+fn circular_lists() {
+    assert_eval(
+        "(define ring (list 1 2 3))
+         (set-cdr! (cdr (cdr ring)) ring)
+         (car (cdr (cdr (cdr ring))))",
+        "1",
+    );
+    assert_eval(
+        "(define ring (list 1 2 3))
+         (set-cdr! (cdr (cdr ring)) ring)
+         (list? ring)",
+        "#f",
+    );
+    assert_eval(
+        "(define a (list 1 2))
+         (define b (list 1 2))
+         (set-cdr! (cdr a) a)
+         (set-cdr! (cdr b) b)
+         (equal? a b)",
+        "#t",
+    );
+}
+
+#[test]
+fn comparing_circular_values() {
+    let mut runtime = Runtime::new();
+    let rings = runtime
+        .eval_str(
+            "(define (ring) (let ((r (list 1 2))) (set-cdr! (cdr r) r) r))
+             (ring)
+             (ring)
+             (let ((v (vector 1 2))) (vector-set! v 1 v) v)
+             (let ((v (vector 1 2))) (vector-set! v 1 v) v)",
+        )
+        .unwrap();
+
+    assert_eq!(rings[1], rings[2]);
+    assert_eq!(rings[3], rings[4]);
+    assert_ne!(rings[1], rings[3]);
+}
+
+#[test]
+fn member_and_assoc_reject_circular_lists() {
+    for (call, list) in &[
+        ("(memq 'x ring)", "ring"),
+        ("(memv 4 ring)", "ring"),
+        ("(member \"x\" ring)", "ring"),
+        ("(assq 'x pairs)", "pairs"),
+        ("(assv 4 pairs)", "pairs"),
+        ("(assoc \"x\" pairs)", "pairs"),
+    ] {
+        let mut runtime = Runtime::new();
+        let input = format!(
+            "(define ring (list 1 2 3))
+             (set-cdr! (cdr (cdr ring)) ring)
+             (define pairs (list '(1 . a) '(2 . b)))
+             (set-cdr! (cdr pairs) pairs)
+             {}",
+            call
+        );
+        let result = runtime.eval_str(&input);
+        let list = runtime.eval_str(list).unwrap().remove(0);
+
+        assert_eq!(Err(Error::from(WrongArgumentType(list))), result);
+    }
+    assert_eval(
+        "(define ring (list 1 2 3))
+         (set-cdr! (cdr (cdr ring)) ring)
+         (list (car (memv 3 ring))
+               (guard (e ((assertion-violation? e) 'circular))
+                 (member 4 ring)))",
+        "'(3 circular)",
+    );
+}
+
+#[test]
+fn literals_are_immutable() {
+    assert_eval_err("(set-car! '(1) 2)", ImmutableValue(pair(integer(1), nil())));
+    assert_eval_err(
+        "(define l '(1 . 2))
+         (set-cdr! l 3)",
+        ImmutableValue(pair(integer(1), integer(2))),
+    );
+}
+
+#[test]
+fn bad_arity() {
+    assert_eval_err("(set-car! (list 1))", BadArity(Some("set-car!".into())));
+    assert_eval_err("(set-cdr!)", BadArity(Some("set-cdr!".into())));
+}
+
+#[test]
+fn wrong_argument_type() {
+    assert_eval_err("(set-car! 1 2)", WrongArgumentType(integer(1)));
+    assert_eval_err("(set-cdr! '() 2)", WrongArgumentType(nil()));
+}
@@ -0,0 +1,134 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn make_parameter() {
+    assert_eval("(define p (make-parameter 10)) (p)", "10");
+    assert_eval(
+        "(define p (make-parameter 10 (lambda (x) (* x 2)))) (p)",
+        "20",
+    );
+    assert_eval_err("(define p (make-parameter 10)) (p 1)", BadArity(None));
+    assert_eval_err("(make-parameter 10 1)", WrongArgumentType(integer(1)));
+}
+
+#[test]
+fn parameterize() {
+    assert_eval(
+        "(define p (make-parameter 1))
+         (define (get) (p))
+         (list (parameterize ((p 2)) (get)) (get))",
+        "'(2 1)",
+    );
+    assert_eval(
+        "(define p (make-parameter 1))
+         (define q (make-parameter 'a))
+         (parameterize ((p 2) (q 'b))
+           (parameterize ((p 3))
+             (list (p) (q))))",
+        "'(3 b)",
+    );
+    assert_eval("(parameterize () 1)", "1");
+}
+
+#[test]
+fn parameterize_converts_values() {
+    assert_eval(
+        "(define p (make-parameter 1 (lambda (x) (* x 10))))
+         (list (parameterize ((p 2)) (p)) (p))",
+        "'(20 10)",
+    );
+}
+
+#[test]
+fn parameterize_values_are_evaluated_outside() {
+    assert_eval(
+        "(define p (make-parameter 1))
+         (define q (make-parameter 2))
+         (parameterize ((p (q)) (q (p))) (list (p) (q)))",
+        "'(2 1)",
+    );
+}
+
+#[test]
+fn parameterize_restores_on_error() {
+    let mut runtime = Runtime::new();
+
+    runtime.eval_str("(define p (make-parameter 1))").unwrap();
+    assert!(runtime
+        .eval_str("(parameterize ((p 2)) (car '()))")
+        .is_err());
+    assert_eq!(integer(1), runtime.eval_str("(p)").unwrap()[0]);
+}
+
+#[test]
+fn parameterize_runs_on_the_vm_stack() {
+    assert_eval(
+        "(define p (make-parameter 0))
+         (define (f n)
+           (if (= n 0) (p) (+ 1 (parameterize ((p n)) (f (- n 1))))))
+         (list (f 20000) (p))",
+        "'(20001 0)",
+    );
+
+    let mut runtime = Runtime::new();
+    runtime
+        .eval_str(
+            "(define p (make-parameter 0))
+             (define (f n)
+               (if (= n 0) (car '()) (parameterize ((p n)) (f (- n 1)))))",
+        )
+        .unwrap();
+    assert!(runtime.eval_str("(f 20000)").is_err());
+    assert_eq!(integer(0), runtime.eval_str("(p)").unwrap()[0]);
+}
+
+#[test]
+fn parameterize_ignores_shadowed_list() {
+    assert_eval("(define (k list) (parameterize () 1)) (k 1)", "1");
+    assert_eval("(define (k %list) (parameterize () 1)) (k 1)", "1");
+    assert_eval(
+        "(define p (make-parameter 1))
+         (define (list . args) #f)
+         (parameterize ((p 2)) (p))",
+        "2",
+    );
+}
+
+#[test]
+fn parameterize_non_parameter() {
+    assert_eval_err("(parameterize ((1 2)) 3)", WrongArgumentType(integer(1)));
+}
+
+#[test]
+fn current_output_port() {
+    assert_eval(
+        "(define port (open-output-string))
+         (parameterize ((current-output-port port))
+           (display \"hello\")
+           (display #\\,)
+           (newline)
+           (display '(1 2)))
+         (get-output-string port)",
+        "\"hello,\n(1 2)\"",
+    );
+    assert_eval(
+        "(define port (open-output-string))
+         (display 1 port)
+         (newline port)
+         (get-output-string port)",
+        "\"1\n\"",
+    );
+}
+
+#[test]
+fn ports() {
+    assert_eval(
+        "(list (port? (current-output-port))
+               (output-port? (current-error-port))
+               (input-port? (current-input-port))
+               (output-port? (current-input-port))
+               (port? 1))",
+        "'(#t #t #t #f #f)",
+    );
+}
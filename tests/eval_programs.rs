@@ -0,0 +1,294 @@
+use crate::helpers::{values::*, *};
+use ostrov::{
+    errors::{Error, RuntimeError, RuntimeError::*},
+    values::RcValue,
+};
+use std::process::Command;
+
+fn eval_program(input: &str) -> RcValue {
+    match Runtime::new().eval_program(input) {
+        Ok(values) => values.last().unwrap().clone(),
+        Err(error) => panic!("{:?} from program {:?}", error, input),
+    }
+}
+
+fn assert_program_err(input: &str, expected: RuntimeError) {
+    match Runtime::new().eval_program(input) {
+        Ok(values) => panic!("expected {:?}, got {:?}", expected, values),
+        Err(error) => assert_eq!(Error::from(expected), error),
+    }
+}
+
+#[test]
+fn programs() {
+    assert_eq!(
+        integer(42),
+        eval_program(
+            "(import (rnrs))
+             (define (double x) (* x 2))
+             (double 21)",
+        )
+    );
+}
+
+#[test]
+fn only_imported_bindings_are_visible() {
+    assert_eq!(
+        integer(1),
+        eval_program("(import (only (rnrs base) car quote)) (car '(1 2))")
+    );
+    assert_program_err(
+        "(import (only (rnrs base) car)) (+ 1 2)",
+        UnboundVariable("+".into()),
+    );
+    assert_program_err(
+        "(import (rnrs base)) (make-eq-hashtable)",
+        UnboundVariable("make-eq-hashtable".into()),
+    );
+    assert_program_err(
+        "(import (rnrs)) (set-car! (list 1) 2)",
+        UnboundVariable("set-car!".into()),
+    );
+}
+
+#[test]
+fn internal_procedures_are_not_visible() {
+    for name in &["cons", "list", "make-enumeration", "record-accessor"] {
+        assert_program_err(
+            &format!("(import (only (rnrs base) car)) {}", name),
+            UnboundVariable((*name).into()),
+        );
+    }
+    assert_eq!(
+        pair(atom("a"), nil()),
+        eval_program(
+            "(import (only (rnrs base) define quote)
+                     (only (rnrs enums) define-enumeration enum-set->list))
+             (define-enumeration letter (a b) letters)
+             (enum-set->list (letters a))",
+        )
+    );
+}
+
+#[test]
+fn programs_dont_see_the_repl_definitions() {
+    let mut runtime = Runtime::new();
+    runtime.eval_str("(define secret 1)").unwrap();
+
+    assert_eq!(
+        Err(Error::from(UnboundVariable("secret".into()))),
+        runtime.eval_program("(import (rnrs)) secret")
+    );
+}
+
+#[test]
+fn standard_libraries() {
+    assert_eq!(
+        pair(integer(2), pair(integer(3), nil())),
+        eval_program(
+            "(import (rnrs base) (rnrs lists))
+             (memv 2 (list 1 2 3))",
+        )
+    );
+    assert_eq!(
+        integer(6),
+        eval_program(
+            "(import (rnrs base) (rnrs control))
+             (do ((i 0 (+ i 1)) (sum 0 (+ sum i))) ((= i 4) sum))",
+        )
+    );
+    assert_eq!(
+        atom("b"),
+        eval_program(
+            "(import (rnrs base) (rnrs hashtables))
+             (define table (make-eqv-hashtable))
+             (hashtable-set! table 1 'b)
+             (hashtable-ref table 1 #f)",
+        )
+    );
+    assert_eq!(
+        integer(2),
+        eval_program(
+            "(import (rnrs) (rnrs mutable-pairs))
+             (define p (list 1))
+             (set-car! p 2)
+             (car p)",
+        )
+    );
+    assert_eq!(integer(3), eval_program("(import (rnrs (6))) (+ 1 2)"));
+}
+
+#[test]
+fn keywords_are_imported() {
+    assert_program_err(
+        "(import (rnrs io simple)) (if #t 1 2)",
+        UnboundVariable("if".into()),
+    );
+    assert_program_err(
+        "(import (only (rnrs base) define)) (define x (quote a))",
+        UnboundVariable("quote".into()),
+    );
+    assert_eq!(
+        integer(1),
+        eval_program("(import (only (rnrs base) if)) (if #t 1 2)")
+    );
+    assert_eq!(
+        integer(2),
+        eval_program(
+            "(import (only (rnrs base) define *))
+             (define (if x) (* x 2))
+             (if 1)",
+        )
+    );
+}
+
+#[test]
+fn imported_identifiers_cant_be_defined() {
+    for (program, name) in &[
+        ("(import (rnrs)) (define car 1)", "car"),
+        ("(import (rnrs)) (define (if x) x)", "if"),
+        (
+            "(import (rnrs) (ostrov)) (define-values (x cdr) (values 1 2))",
+            "cdr",
+        ),
+        (
+            "(import (rnrs)) (define-syntax list (identifier-syntax 1))",
+            "list",
+        ),
+        (
+            "(import (rnrs)) (define-record-type pair (fields x))",
+            "pair?",
+        ),
+    ] {
+        assert_program_err(
+            program,
+            SyntaxViolation(
+                Some("define".into()),
+                "definition of an imported identifier".into(),
+                atom(name),
+                atom(name),
+            ),
+        );
+    }
+}
+
+#[test]
+fn promises() {
+    assert_eq!(
+        pair(integer(1), pair(bool(true), pair(integer(3), nil()))),
+        eval_program(
+            "(import (rnrs) (rnrs r5rs) (ostrov))
+             (list (force (delay 1))
+                   (promise? (make-promise 2))
+                   (force (delay-force (delay 3))))",
+        )
+    );
+}
+
+#[test]
+fn streams() {
+    assert_eq!(
+        pair(integer(2), pair(integer(4), nil())),
+        eval_program(
+            "(import (rnrs) (ostrov))
+             (define s (stream-cons 1 (stream 2 3)))
+             (stream->list (stream-map (lambda (x) (* x 2))
+                                       (stream-take 2 s)))",
+        )
+    );
+}
+
+#[test]
+fn parameters() {
+    assert_eq!(
+        pair(integer(2), pair(integer(1), nil())),
+        eval_program(
+            "(import (rnrs) (ostrov))
+             (define p (make-parameter 1))
+             (list (parameterize ((p 2)) (p)) (p))",
+        )
+    );
+}
+
+#[test]
+fn string_procedures() {
+    assert_eq!(
+        string("a-b"),
+        eval_program(
+            "(import (rnrs) (ostrov))
+             (string-join (string-split (string-trim \"  a b\") #\\space)
+                          \"-\")",
+        )
+    );
+}
+
+#[test]
+fn loading_files() {
+    assert_eq!(
+        pair(integer(42), pair(integer(3), nil())),
+        eval_program(
+            "(import (rnrs) (rnrs eval) (ostrov))
+             (load \"tests/files/main.scm\")
+             (list result (eval '(+ 1 2) (interaction-environment)))",
+        )
+    );
+}
+
+#[test]
+fn special_forms_can_be_renamed() {
+    assert_eq!(
+        integer(3),
+        eval_program(
+            "(import (prefix (rnrs) r:))
+             (r:define (add a b) (r:let ((sum (r:+ a b))) sum))
+             (add 1 2)",
+        )
+    );
+}
+
+#[test]
+fn generated_code_works_without_imports() {
+    assert_eq!(
+        integer(2),
+        eval_program(
+            "(import (only (rnrs base) define car)
+                     (only (rnrs records syntactic) define-record-type))
+             (define-record-type point (fields x y))
+             (point-y (make-point 1 2))",
+        )
+    );
+    assert_eq!(
+        pair(integer(2), pair(integer(1), nil())),
+        eval_program(
+            "(import (rnrs base) (rnrs syntax-case))
+             (define-syntax swap
+               (lambda (form)
+                 (syntax-case form ()
+                   ((_ a b) (with-syntax ((first #'b)) #'(list first a))))))
+             (swap 1 2)",
+        )
+    );
+    assert_eq!(
+        integer(1),
+        eval_program(
+            "(import (rnrs base))
+             (define-syntax one (identifier-syntax 1))
+             one",
+        )
+    );
+}
+
+#[test]
+fn programs_start_with_an_import() {
+    assert_program_err("(+ 1 2)", MalformedExpression);
+}
+
+#[test]
+fn running_files() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ostrov"))
+        .arg("tests/programs/squares.scm")
+        .output()
+        .unwrap();
+
+    assert_eq!("1\n4\n9\n", String::from_utf8_lossy(&output.stdout));
+}
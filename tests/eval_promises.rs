@@ -0,0 +1,165 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn delay_and_force() {
+    assert_eval("(force (delay (+ 1 2)))", "3");
+    assert_eval(
+        "(let ((p (delay (+ 1 2)))) (list (force p) (force p)))",
+        "'(3 3)",
+    );
+    assert_eval("(force 1)", "1");
+}
+
+#[test]
+fn force_memoizes() {
+    assert_eval(
+        "(define count 0)
+         (define p (delay ((lambda () (set! count (+ count 1)) count))))
+         (force p)
+         (force p)
+         (list (force p) count)",
+        "'(1 1)",
+    );
+}
+
+#[test]
+fn delay_force() {
+    assert_eval("(force (delay-force (delay 1)))", "1");
+    assert_eval(
+        "(define (loop n)
+           (if (= n 0) (delay 'done) (delay-force (loop (- n 1)))))
+         (force (loop 100000))",
+        "'done",
+    );
+}
+
+#[test]
+fn force_runs_on_the_vm_stack() {
+    assert_eval(
+        "(define (f n) (if (= n 0) 0 (+ 1 (force (delay (f (- n 1)))))))
+         (f 20000)",
+        "20000",
+    );
+}
+
+#[test]
+fn reentrant_force_keeps_the_first_value() {
+    assert_eval(
+        "(define count 0)
+         (define p
+           (delay (begin (set! count (+ count 1))
+                         (if (> count x) count (force p)))))
+         (define x 5)
+         (define first (force p))
+         (set! x 10)
+         (list first (force p))",
+        "'(6 6)",
+    );
+}
+
+#[test]
+fn make_promise() {
+    assert_eval("(force (make-promise 1))", "1");
+    assert_eval(
+        "(define p (delay 1))
+         (eq? p (make-promise p))",
+        "#t",
+    );
+    assert_eval(
+        "(list (promise? (delay 1)) (promise? (make-promise 1)) (promise? 1))",
+        "'(#t #t #f)",
+    );
+}
+
+#[test]
+fn delay_arity() {
+    assert_eval_err("(delay)", MalformedExpression);
+    assert_eval_err("(delay-force 1 2)", MalformedExpression);
+}
+
+#[test]
+fn stream_cons() {
+    assert_eval(
+        "(define s (stream-cons 1 (stream-cons 2 stream-null)))
+         (list (stream-car s)
+               (stream-car (stream-cdr s))
+               (stream-null? (stream-cdr (stream-cdr s)))
+               (stream-pair? s)
+               (stream? s)
+               (stream? 1))",
+        "'(1 2 #t #t #t #f)",
+    );
+    assert_eval_err("(stream-car 1)", WrongArgumentType(integer(1)));
+}
+
+#[test]
+fn stream_cons_is_lazy() {
+    assert_eval(
+        "(define s (stream-cons (car '()) stream-null))
+         (stream-pair? s)",
+        "#t",
+    );
+}
+
+#[test]
+fn stream_cons_ignores_shadowed_procedures() {
+    assert_eval(
+        "(let ((make-promise 1)) (stream-car (stream-cons 1 2)))",
+        "1",
+    );
+    assert_eval(
+        "(define cons list)
+         (stream-car (stream-cons 1 stream-null))",
+        "1",
+    );
+}
+
+#[test]
+fn infinite_streams() {
+    assert_eval(
+        "(define (integers-from n) (stream-cons n (integers-from (+ n 1))))
+         (stream->list (stream-take 5 (integers-from 0)))",
+        "'(0 1 2 3 4)",
+    );
+    assert_eval(
+        "(define (integers-from n) (stream-cons n (integers-from (+ n 1))))
+         (stream->list 3 (integers-from 10))",
+        "'(10 11 12)",
+    );
+}
+
+#[test]
+fn stream_map() {
+    assert_eval(
+        "(stream->list (stream-map (lambda (x) (* x x)) (stream 1 2 3)))",
+        "'(1 4 9)",
+    );
+    assert_eval(
+        "(stream->list (stream-map + (stream 1 2 3) (list->stream '(10 20))))",
+        "'(11 22)",
+    );
+}
+
+#[test]
+fn stream_filter() {
+    assert_eval(
+        "(define (integers-from n) (stream-cons n (integers-from (+ n 1))))
+         (define (even? n) (= (* 2 (/ n 2)) n))
+         (stream->list
+           (stream-take 3 (stream-filter even? (integers-from 1))))",
+        "'(2 4 6)",
+    );
+}
+
+#[test]
+fn long_streams_are_dropped() {
+    assert_eval(
+        "(define (integers-from n) (stream-cons n (integers-from (+ n 1))))
+         (define s (integers-from 0))
+         (define n (length (stream->list 20000 s)))
+         (set! s 0)
+         n",
+        "20000",
+    );
+}
@@ -0,0 +1,280 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::{Error, RuntimeError::*};
+
+#[test]
+fn define_record_type() {
+    assert_eval(
+        "(define-record-type point (fields x (mutable y)))
+         (define p (make-point 1 2))
+         (point-y-set! p 3)
+         (list (point? p) (point? 1) (point-x p) (point-y p))",
+        "'(#t #f 1 3)",
+    );
+}
+
+#[test]
+fn define_record_type_with_custom_names() {
+    assert_eval(
+        "(define-record-type (point new-point is-point?)
+           (fields (immutable x get-x) (mutable y get-y set-y!)))
+         (define p (new-point 1 2))
+         (set-y! p 5)
+         (list (is-point? p) (get-x p) (get-y p))",
+        "'(#t 1 5)",
+    );
+}
+
+#[test]
+fn define_record_type_ignores_shadowed_procedures() {
+    assert_eval(
+        "(define (g record-accessor)
+           (define-record-type pt (fields x))
+           (pt-x (make-pt 1)))
+         (g 0)",
+        "1",
+    );
+    assert_eval(
+        "(define (record-constructor . args) #f)
+         (define-record-type pt (fields x))
+         (pt-x (make-pt 1))",
+        "1",
+    );
+}
+
+#[test]
+fn immutable_fields_have_no_mutator() {
+    assert_eval_err(
+        "(define-record-type point (fields x))
+         (point-x-set! (make-point 1) 2)",
+        UnboundVariable("point-x-set!".into()),
+    );
+}
+
+#[test]
+fn constructor_arity() {
+    assert_eval_err(
+        "(define-record-type point (fields x y))
+         (make-point 1)",
+        BadArity(Some("make-point".into())),
+    );
+}
+
+#[test]
+fn accessors_check_the_record_type() {
+    assert_eval_err(
+        "(define-record-type point (fields x))
+         (point-x 'a)",
+        WrongArgumentType(atom("a")),
+    );
+}
+
+#[test]
+fn display() {
+    let mut runtime = Runtime::new();
+    let values = runtime
+        .eval_str(
+            "(define-record-type point (fields x y))
+             (define-record-type secret (opaque #t) (fields x))
+             (make-point 1 \"one\")
+             (make-secret 1)",
+        )
+        .unwrap();
+
+    assert_eq!("#<point x: 1 y: \"one\">", format!("{}", values[2]));
+    assert_eq!("#<secret>", format!("{}", values[3]));
+}
+
+#[test]
+fn display_cyclic() {
+    let mut runtime = Runtime::new();
+    let values = runtime
+        .eval_str(
+            "(define-record-type node (fields (mutable next)))
+             (define-record-type secret (opaque #t) (fields (mutable x)))
+             (define n (make-node '()))
+             (node-next-set! n (list n))
+             (define s (make-secret #f))
+             (secret-x-set! s s)
+             n
+             s",
+        )
+        .unwrap();
+
+    assert_eq!("#0=#<node next: (#0#)>", format!("{}", values[6]));
+    assert_eq!("#<secret>", format!("{}", values[7]));
+}
+
+#[test]
+fn parent() {
+    assert_eval(
+        "(define-record-type point (fields x y))
+         (define-record-type point3 (parent point) (fields z))
+         (define p (make-point3 1 2 3))
+         (list (point? p) (point3? p) (point3? (make-point 1 2))
+               (point-x p) (point-y p) (point3-z p))",
+        "'(#t #t #f 1 2 3)",
+    );
+}
+
+#[test]
+fn sealed() {
+    let mut runtime = Runtime::new();
+    let result = runtime.eval_str(
+        "(define-record-type point (sealed #t) (fields x))
+         (define-record-type point3 (parent point) (fields z))",
+    );
+
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(WrongArgumentType(..)))
+    ));
+}
+
+#[test]
+fn protocol() {
+    assert_eval(
+        "(define-record-type point
+           (fields x y)
+           (protocol (lambda (new) (lambda (x) (new x (* x 2))))))
+         (define p (make-point 4))
+         (list (point-x p) (point-y p))",
+        "'(4 8)",
+    );
+}
+
+#[test]
+fn protocol_with_parent() {
+    assert_eval(
+        "(define-record-type point
+           (fields x y)
+           (protocol (lambda (new) (lambda (x) (new x 0)))))
+         (define-record-type point3
+           (parent point)
+           (fields z)
+           (protocol (lambda (n) (lambda (x z) ((n x) z)))))
+         (define p (make-point3 1 3))
+         (list (point-x p) (point-y p) (point3-z p))",
+        "'(1 0 3)",
+    );
+}
+
+#[test]
+fn clauses_are_expanded_in_scope() {
+    assert_eval(
+        "(define-syntax twice (syntax-rules () ((_ e) (* 2 e))))
+         (define-record-type point
+           (fields x)
+           (protocol (lambda (new) (lambda (x) (new (twice x))))))
+         (point-x (make-point 4))",
+        "8",
+    );
+    assert_eval(
+        "(define (f if)
+           (define-record-type point
+             (fields x)
+             (protocol (lambda (new) (lambda () (new if)))))
+           (point-x (make-point)))
+         (f 5)",
+        "5",
+    );
+    assert_eval(
+        "(define-syntax define-point
+           (syntax-rules ()
+             ((_ get)
+              (begin (define-record-type point (fields x))
+                     (define (get)
+                       (let ((rtd (record-type-descriptor point)))
+                         (list (point-x (make-point 1))
+                               (record-type-name rtd))))))))
+         (define (f)
+           (define-point get)
+           (define point 'outer)
+           (list point (get)))
+         (f)",
+        "'(outer (1 point))",
+    );
+}
+
+#[test]
+fn nongenerative() {
+    assert_eval(
+        "(define (define-point)
+           (make-record-type-descriptor 'point #f 'point-uid #f #f
+                                        '#((immutable x))))
+         (list (eq? (define-point) (define-point))
+               (record-type-generative? (define-point))
+               (record-type-uid (define-point)))",
+        "'(#t #f point-uid)",
+    );
+    assert_eval(
+        "(define-record-type point (nongenerative) (fields x))
+         (define p (make-point 1))
+         (define-record-type point (nongenerative) (fields x))
+         (point? p)",
+        "#t",
+    );
+    assert_eval(
+        "(define-record-type point (fields x))
+         (define p (make-point 1))
+         (define-record-type point (fields x))
+         (point? p)",
+        "#f",
+    );
+}
+
+#[test]
+fn procedural_layer() {
+    assert_eval(
+        "(define rtd
+           (make-record-type-descriptor 'node #f #f #f #f
+                                        '#((immutable value) (mutable next))))
+         (define rcd (make-record-constructor-descriptor rtd #f #f))
+         (define make-node (record-constructor rcd))
+         (define node-value (record-accessor rtd 0))
+         (define node-next (record-accessor rtd 1))
+         (define node-next-set! (record-mutator rtd 1))
+         (define n (make-node 1 '()))
+         (node-next-set! n 'end)
+         (list ((record-predicate rtd) n) (node-value n) (node-next n))",
+        "'(#t 1 end)",
+    );
+    assert_eval_err(
+        "(define rtd (make-record-type-descriptor 'node #f #f #f #f
+                                                  '#((immutable value))))
+         (record-mutator rtd 0)",
+        WrongArgumentType(integer(0)),
+    );
+    assert_eval_err(
+        "(define rtd (make-record-type-descriptor 'node #f #f #f #f
+                                                  '#((immutable value))))
+         (record-accessor rtd 1)",
+        IndexOutOfRange(integer(1)),
+    );
+}
+
+#[test]
+fn inspection() {
+    assert_eval(
+        "(define-record-type point (fields x (mutable y)))
+         (define p (make-point 1 2))
+         (list (record? p)
+               (record? '(1 2))
+               (eq? (record-rtd p) (record-type-descriptor point))
+               (record-type-name (record-rtd p))
+               (record-type-field-names (record-rtd p))
+               (record-field-mutable? (record-rtd p) 1)
+               (record-type-parent (record-rtd p))
+               (record-type-sealed? (record-rtd p)))",
+        "'(#t #f #t point #(x y) #t #f #f)",
+    );
+    assert_eval(
+        "(define-record-type secret (opaque #t) (fields x))
+         (record? (make-secret 1))",
+        "#f",
+    );
+    assert_eval_err(
+        "(define-record-type secret (opaque #t) (fields x))
+         (record-rtd 'a)",
+        WrongArgumentType(atom("a")),
+    );
+}
@@ -0,0 +1,191 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn literals() {
+    assert_eval("\"abc\"", "\"abc\"");
+    assert_eval("(string? \"abc\")", "#t");
+    assert_eval("(string? 'abc)", "#f");
+    assert_eval("(char? #\\a)", "#t");
+    assert_eval("(char? \"a\")", "#f");
+}
+
+#[test]
+fn characters() {
+    assert_eval("(char->integer #\\A)", "65");
+    assert_eval("(integer->char 955)", "#\\λ");
+    assert_eval("(char<? #\\a #\\b #\\c)", "#t");
+    assert_eval("(char=? #\\a #\\a #\\b)", "#f");
+    assert_eval("(char-ci=? #\\a #\\A)", "#t");
+    assert_eval("(char-upcase #\\ä)", "#\\Ä");
+    assert_eval("(char-downcase #\\A)", "#\\a");
+    assert_eval("(char-alphabetic? #\\a)", "#t");
+    assert_eval("(char-numeric? #\\1)", "#t");
+    assert_eval("(char-whitespace? #\\tab)", "#t");
+}
+
+#[test]
+fn length_and_ref_count_characters() {
+    assert_eval("(string-length \"\")", "0");
+    assert_eval("(string-length \"λx.x\")", "4");
+    assert_eval("(string-length \"日本語\")", "3");
+    assert_eval("(string-ref \"日本語\" 1)", "#\\本");
+    assert_eval("(string-ref \"abc\" 2)", "#\\c");
+}
+
+#[test]
+fn string_ref_out_of_range() {
+    assert_eval_err("(string-ref \"abc\" 3)", IndexOutOfRange(integer(3)));
+}
+
+#[test]
+fn make_string_and_mutation() {
+    assert_eval("(make-string 3 #\\z)", "\"zzz\"");
+    assert_eval(
+        "(define s (make-string 3 #\\a))
+         (string-set! s 1 #\\λ)
+         s",
+        "\"aλa\"",
+    );
+    assert_eval(
+        "(define s (make-string 4 #\\a))
+         (string-fill! s #\\b)
+         s",
+        "\"bbbb\"",
+    );
+    assert_eval(
+        "(define s (string-copy \"abcde\"))
+         (string-fill! s #\\- 1 3)
+         s",
+        "\"a--de\"",
+    );
+}
+
+#[test]
+fn literals_are_immutable() {
+    assert_eval_err(
+        "(define s \"abc\")
+         (string-set! s 0 #\\z)",
+        ImmutableValue(string("abc")),
+    );
+    assert_eval(
+        "(define s (string-copy \"abc\"))
+         (string-set! s 0 #\\z)
+         s",
+        "\"zbc\"",
+    );
+}
+
+#[test]
+fn copies_are_independent() {
+    assert_eval(
+        "(define a (make-string 2 #\\a))
+         (define b (string-copy a))
+         (string-set! b 0 #\\b)
+         a",
+        "\"aa\"",
+    );
+    assert_eval("(string-copy \"abcdef\" 2)", "\"cdef\"");
+    assert_eval("(string-copy \"abcdef\" 2 4)", "\"cd\"");
+}
+
+#[test]
+fn constructors_and_conversions() {
+    assert_eval("(string #\\a #\\b)", "\"ab\"");
+    assert_eval("(substring \"hello\" 1 3)", "\"el\"");
+    assert_eval("(string-append \"foo\" \"\" \"bar\")", "\"foobar\"");
+    assert_eval("(string->list \"abc\")", "'(#\\a #\\b #\\c)");
+    assert_eval("(list->string '(#\\a #\\b))", "\"ab\"");
+    assert_eval("(string->symbol \"abc\")", "'abc");
+    assert_eval("(eq? (string->symbol \"abc\") 'abc)", "#t");
+    assert_eval("(symbol->string 'abc)", "\"abc\"");
+    assert_eval("(number->string -255 16)", "\"-ff\"");
+    assert_eval("(string->number \"42\")", "42");
+    assert_eval("(string->number \"abc\")", "#f");
+}
+
+#[test]
+fn comparisons() {
+    assert_eval("(string=? \"abc\" \"abc\")", "#t");
+    assert_eval("(string=? \"abc\" \"abd\")", "#f");
+    assert_eval("(string<? \"abc\" \"abd\" \"b\")", "#t");
+    assert_eval("(string-ci=? \"Straße\" \"STRASSE\")", "#t");
+    assert_eval("(string-ci=? \"abc\" \"ABD\")", "#f");
+    assert_eval("(equal? \"abc\" (string #\\a #\\b #\\c))", "#t");
+}
+
+#[test]
+fn case_conversions() {
+    assert_eval("(string-upcase \"hello\")", "\"HELLO\"");
+    assert_eval("(string-upcase \"straße\")", "\"STRASSE\"");
+    assert_eval("(string-downcase \"HeLLo\")", "\"hello\"");
+    assert_eval("(string-titlecase \"hello wORLD\")", "\"Hello World\"");
+    assert_eval("(string-foldcase \"Straße\")", "\"strasse\"");
+}
+
+#[test]
+fn for_each_and_map() {
+    assert_eval(
+        "(define n 0)
+         (string-for-each (lambda (c) (set! n (+ n (char->integer c)))) \"ab\")
+         n",
+        "195",
+    );
+    assert_eval("(string-map char-upcase \"abc\")", "\"ABC\"");
+    assert_eval(
+        "(string-map (lambda (a b) (if (char<? a b) a b)) \"adc\" \"bbbz\")",
+        "\"abb\"",
+    );
+}
+
+#[test]
+fn string_map_must_return_characters() {
+    assert_eval_err(
+        "(string-map (lambda (c) 1) \"a\")",
+        WrongArgumentType(integer(1)),
+    );
+}
+
+#[test]
+fn searching() {
+    assert_eval("(string-contains \"hello world\" \"o w\")", "4");
+    assert_eval("(string-contains \"hello\" \"xyz\")", "#f");
+    assert_eval("(string-index \"hello\" #\\l)", "2");
+    assert_eval("(string-index \"hello\" char-whitespace?)", "#f");
+    assert_eval("(string-index \"ab1c\" char-numeric?)", "2");
+}
+
+#[test]
+fn split_and_join() {
+    assert_eval(
+        "(string-split \"a,b,,c\" #\\,)",
+        "'(\"a\" \"b\" \"\" \"c\")",
+    );
+    assert_eval("(string-join '(\"a\" \"b\" \"c\"))", "\"a b c\"");
+    assert_eval("(string-join '(\"a\" \"b\") \", \")", "\"a, b\"");
+    assert_eval("(string-join '())", "\"\"");
+}
+
+#[test]
+fn padding_and_trimming() {
+    assert_eval("(string-pad \"42\" 5)", "\"   42\"");
+    assert_eval("(string-pad \"42\" 5 #\\0)", "\"00042\"");
+    assert_eval("(string-pad \"12345\" 3)", "\"345\"");
+    assert_eval("(string-pad-right \"ab\" 4 #\\.)", "\"ab..\"");
+    assert_eval("(string-trim \"  hi  \")", "\"hi  \"");
+    assert_eval("(string-trim-right \"  hi  \")", "\"  hi\"");
+    assert_eval("(string-trim-both \"  hi  \")", "\"hi\"");
+    assert_eval("(string-trim-both \"xxhixx\" #\\x)", "\"hi\"");
+}
+
+#[test]
+fn bad_arity() {
+    assert_eval_err("(string-length)", BadArity(Some("string-length".into())));
+    assert_eval_err("(string-ref \"a\")", BadArity(Some("string-ref".into())));
+}
+
+#[test]
+fn wrong_argument_type() {
+    assert_eval_err("(string-length 1)", WrongArgumentType(integer(1)));
+    assert_eval_err("(string-upcase 'a)", WrongArgumentType(atom("a")));
+}
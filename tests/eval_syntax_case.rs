@@ -0,0 +1,237 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn syntax_case() {
+    assert_eval(
+        "(define-syntax swap!
+           (lambda (x)
+             (syntax-case x ()
+               ((_ a b) #'(let ((tmp a)) (set! a b) (set! b tmp))))))
+         (define tmp 1)
+         (define y 2)
+         (swap! tmp y)
+         (list tmp y)",
+        "'(2 1)",
+    );
+    assert_eval(
+        "(syntax->datum
+           (syntax-case '(1 (2 3)) () ((a (b ...)) #'(b ... a))))",
+        "'(2 3 1)",
+    );
+}
+
+#[test]
+fn introduced_bindings_do_not_capture() {
+    assert_eval(
+        "(define-syntax my-or
+           (lambda (x)
+             (syntax-case x ()
+               ((_) #'#f)
+               ((_ e) #'e)
+               ((_ e r ...) #'(let ((t e)) (if t t (my-or r ...)))))))
+         (let ((t 5) (if list)) (my-or #f t))",
+        "5",
+    );
+}
+
+#[test]
+fn fenders() {
+    assert_eval(
+        "(define-syntax kind
+           (lambda (x)
+             (syntax-case x ()
+               ((_ e) (identifier? #'e) #''identifier)
+               ((_ e) #''other))))
+         (list (kind a) (kind 1) (kind (f)))",
+        "'(identifier other other)",
+    );
+}
+
+#[test]
+fn literals() {
+    assert_eval(
+        "(define-syntax my-if
+           (lambda (x)
+             (syntax-case x (then else)
+               ((_ c then t else e) #'(if c t e)))))
+         (list (my-if #t then 1 else 2) (my-if #f then 1 else 2))",
+        "'(1 2)",
+    );
+    assert_eval_err(
+        "(define-syntax my-if
+           (lambda (x)
+             (syntax-case x (then else)
+               ((_ c then t else e) #'(if c t e)))))
+         (let ((then 1)) (my-if #t then 1 else 2))",
+        SyntaxViolation(
+            None,
+            "invalid syntax".into(),
+            pair(
+                atom("my-if"),
+                pair(
+                    bool(true),
+                    pair(
+                        atom("then"),
+                        pair(
+                            integer(1),
+                            pair(atom("else"), pair(integer(2), nil())),
+                        ),
+                    ),
+                ),
+            ),
+            bool(false),
+        ),
+    );
+}
+
+#[test]
+fn computing_expansions() {
+    assert_eval(
+        "(define (sum numbers)
+           (if (null? numbers) 0 (+ (car numbers) (sum (cdr numbers)))))
+         (define-syntax constant-sum
+           (lambda (x)
+             (syntax-case x ()
+               ((_ n ...) (with-syntax ((total (sum (syntax->datum #'(n ...)))))
+                            #''total)))))
+         (constant-sum 1 2 3)",
+        "6",
+    );
+}
+
+#[test]
+fn with_syntax() {
+    assert_eval(
+        "(define-syntax parallel-set!
+           (lambda (x)
+             (syntax-case x ()
+               ((_ (var val) ...)
+                (with-syntax (((tmp ...) (generate-temporaries #'(var ...))))
+                  #'(let ((tmp val) ...) (set! var tmp) ...))))))
+         (define a 1)
+         (define b 2)
+         (parallel-set! (a b) (b a))
+         (list a b)",
+        "'(2 1)",
+    );
+}
+
+#[test]
+fn datum_to_syntax() {
+    assert_eval(
+        "(define-syntax aif
+           (lambda (x)
+             (syntax-case x ()
+               ((k test then otherwise)
+                (with-syntax ((it (datum->syntax #'k 'it)))
+                  #'(let ((it test)) (if it then otherwise)))))))
+         (aif (memv 2 '(1 2 3)) (car it) #f)",
+        "2",
+    );
+    assert_eval(
+        "(define-syntax aif
+           (lambda (x)
+             (syntax-case x ()
+               ((k test then otherwise)
+                (with-syntax ((it (datum->syntax #'k 'it)))
+                  #'(let ((it test)) (if it then otherwise)))))))
+         (define-syntax first-of
+           (syntax-rules () ((_ l) (aif l (car it) #f))))
+         (define it 'outer)
+         (list (first-of '(1 2)) it)",
+        "'(1 outer)",
+    );
+}
+
+#[test]
+fn syntax_to_datum() {
+    assert_eval("(syntax->datum #'(a (b . c) #(d)))", "'(a (b . c) #(d))");
+    assert_eval(
+        "(define-syntax quote-it
+           (lambda (x)
+             (syntax-case x ()
+               ((_ e) (with-syntax ((d (syntax->datum #'e))) #''d)))))
+         (quote-it (a b))",
+        "'(a b)",
+    );
+}
+
+#[test]
+fn identifier_predicates() {
+    assert_eval(
+        "(list (identifier? #'a) (identifier? #'1) (identifier? #'(a)))",
+        "'(#t #f #f)",
+    );
+    assert_eval(
+        "(define-syntax same?
+           (lambda (x)
+             (syntax-case x ()
+               ((_ a b) (if (bound-identifier=? #'a #'b) #''yes #''no)))))
+         (list (same? x x) (same? x y))",
+        "'(yes no)",
+    );
+    assert_eval(
+        "(define-syntax is-else?
+           (lambda (x)
+             (syntax-case x ()
+               ((_ a) (if (free-identifier=? #'a #'else) #'#t #'#f)))))
+         (list (is-else? else) (let ((else 1)) (is-else? else)))",
+        "'(#t #f)",
+    );
+}
+
+#[test]
+fn identifier_syntax() {
+    assert_eval(
+        "(define p (cons 1 2))
+         (define-syntax p.car (identifier-syntax (car p)))
+         (list p.car (+ p.car 1))",
+        "'(1 2)",
+    );
+    assert_eval(
+        "(define p (cons 1 2))
+         (define-syntax p.car
+           (identifier-syntax
+             (p.car (car p))
+             ((set! p.car e) (set-car! p e))))
+         (set! p.car 10)
+         (list p.car p)",
+        "'(10 (10 . 2))",
+    );
+    assert_eval_err(
+        "(define p (cons 1 2))
+         (define-syntax p.car (identifier-syntax (car p)))
+         (set! p.car 10)",
+        MalformedExpression,
+    );
+}
+
+#[test]
+fn syntax_violation() {
+    assert_eval_err(
+        "(define-syntax positive
+           (lambda (x)
+             (syntax-case x ()
+               ((_ n)
+                (if (> (syntax->datum #'n) 0)
+                    #'n
+                    (syntax-violation 'positive \"not positive\" x #'n))))))
+         (positive -1)",
+        SyntaxViolation(
+            Some("positive".into()),
+            "not positive".into(),
+            pair(atom("positive"), pair(integer(-1), nil())),
+            integer(-1),
+        ),
+    );
+}
+
+#[test]
+fn pattern_variables_outside_templates() {
+    assert_eval_err(
+        "(define-syntax bad
+           (lambda (x) (syntax-case x () ((_ a) a))))",
+        MalformedExpression,
+    );
+}
@@ -0,0 +1,178 @@
+use crate::helpers::*;
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn values() {
+    assert_eval("(values 1)", "1");
+    assert_eval("(call-with-values (lambda () (values 1 2)) +)", "3");
+    assert_eval("(call-with-values (lambda () (values)) list)", "'()");
+    assert_eval("(call-with-values (lambda () 1) list)", "'(1)");
+    assert_eval(
+        "(call-with-values (lambda () (values 1 2 3)) (lambda (a . rest) rest))",
+        "'(2 3)",
+    );
+}
+
+#[test]
+fn call_with_values_runs_on_the_vm_stack() {
+    assert_eval(
+        "(define (f n)
+           (if (= n 0)
+               0
+               (call-with-values (lambda () (f (- n 1)))
+                                 (lambda (x) (+ x 1)))))
+         (f 20000)",
+        "20000",
+    );
+    assert_eval(
+        "(define (loop n)
+           (if (= n 0)
+               'done
+               (call-with-values (lambda () (- n 1)) loop)))
+         (loop 100000)",
+        "'done",
+    );
+}
+
+#[test]
+fn call_with_values_arity() {
+    assert_eval_err(
+        "(call-with-values (lambda () (values 1 2)) (lambda (a) a))",
+        BadArity(None),
+    );
+    assert_eval_err(
+        "(call-with-values (lambda () 1))",
+        BadArity(Some("call-with-values".into())),
+    );
+}
+
+#[test]
+fn values_forms_ignore_shadowed_call_with_values() {
+    assert_eval(
+        "(define (f call-with-values) (let-values (((a b) (values 1 2))) a))
+         (f 0)",
+        "1",
+    );
+    assert_eval(
+        "(define (call-with-values . args) #f)
+         (receive (a . rest) (values 1 2) (list a rest))",
+        "'(1 (2))",
+    );
+}
+
+#[test]
+fn values_in_single_value_contexts() {
+    assert_eval_err("(+ 1 (values 2 3))", BadValueCount(2));
+    assert_eval_err("(list (values))", BadValueCount(0));
+    assert_eval_err("(if (values 1 2) 'a 'b)", BadValueCount(2));
+    assert_eval_err("(define x (values 1 2))", BadValueCount(2));
+    assert_eval_err("(define x 1) (set! x (values))", BadValueCount(0));
+}
+
+#[test]
+fn values_in_bodies() {
+    assert_eval("((lambda () (values 1 2) 3))", "3");
+    assert_eval(
+        "(define (f) (values 1 2))
+         (call-with-values f list)",
+        "'(1 2)",
+    );
+}
+
+#[test]
+fn let_values() {
+    assert_eval("(let-values (((a b) (values 1 2))) (list a b))", "'(1 2)");
+    assert_eval(
+        "(let-values (((a b) (values 1 2)) ((c . d) (values 3 4 5)) (e (values)))
+           (list a b c d e))",
+        "'(1 2 3 (4 5) ())",
+    );
+    assert_eval("(let-values () 1)", "1");
+    assert_eval_err("(let-values (((a b) (values 1))) a)", BadArity(None));
+}
+
+#[test]
+fn let_values_scope() {
+    assert_eval(
+        "(define a 'outer)
+         (let-values (((a) (values 1)) ((b) (values a))) (list a b))",
+        "'(1 outer)",
+    );
+    assert_eval(
+        "(define %values-0 'outer)
+         (let-values (((a) (values 1)) ((b) (values %values-0))) (list a b))",
+        "'(1 outer)",
+    );
+}
+
+#[test]
+fn let_star_values() {
+    assert_eval(
+        "(define a 'outer)
+         (let*-values (((a b) (values 1 2)) ((c) (values (+ a b)))) (list a b c))",
+        "'(1 2 3)",
+    );
+    assert_eval("(let*-values () 1)", "1");
+}
+
+#[test]
+fn define_values() {
+    assert_eval(
+        "(define-values (q r) (div-and-mod 7 2))
+         (list q r)",
+        "'(3 1)",
+    );
+    assert_eval(
+        "(define-values (a . rest) (values 1 2 3))
+         (list a rest)",
+        "'(1 (2 3))",
+    );
+    assert_eval(
+        "(define-values all (values 1 2))
+         all",
+        "'(1 2)",
+    );
+    assert_eval(
+        "(define (f)
+           (define-values (x y) (values 1 2))
+           (+ x y))
+         (f)",
+        "3",
+    );
+}
+
+#[test]
+fn receive() {
+    assert_eval(
+        "(receive (a . rest) (values 1 2 3) (list a rest))",
+        "'(1 (2 3))",
+    );
+    assert_eval("(receive all (values) all)", "'()");
+}
+
+#[test]
+fn div_and_mod() {
+    assert_eval("(list (div 7 2) (mod 7 2))", "'(3 1)");
+    assert_eval("(list (div -7 2) (mod -7 2))", "'(-4 1)");
+    assert_eval("(list (div 7 -2) (mod 7 -2))", "'(-3 1)");
+    assert_eval(
+        "(call-with-values (lambda () (div-and-mod -7 -2)) list)",
+        "'(4 1)",
+    );
+}
+
+#[test]
+fn div_and_mod_overflow() {
+    for name in &["div", "mod", "div-and-mod"] {
+        assert_eval_err(
+            &format!("({} (- -9223372036854775807 1) -1)", name),
+            IntegerOverflow((*name).into()),
+        );
+    }
+    assert_eval(
+        "(guard (e ((implementation-restriction-violation? e)
+                    (condition-who e)))
+           (div (- -9223372036854775807 1) -1))",
+        "'div",
+    );
+}
@@ -0,0 +1,78 @@
+use crate::helpers::{values::*, *};
+use ostrov::errors::RuntimeError::*;
+
+#[test]
+fn vector_literals() {
+    assert_eval("#(1 2 3)", "(vector 1 2 3)");
+    assert_eval("'#(a #(b))", "(vector 'a (vector 'b))");
+    assert_eval("#()", "(vector)");
+}
+
+#[test]
+fn vector_predicate() {
+    assert_eval("(vector? #(1))", "#t");
+    assert_eval("(vector? '(1))", "#f");
+    assert_eval("(vector? \"abc\")", "#f");
+}
+
+#[test]
+fn make_vector() {
+    assert_eval("(make-vector 3 'a)", "#(a a a)");
+    assert_eval("(vector-length (make-vector 4))", "4");
+    assert_eval_err("(make-vector 'a)", WrongArgumentType(atom("a")));
+}
+
+#[test]
+fn vector_ref() {
+    assert_eval("(vector-ref #(1 2 3) 1)", "2");
+    assert_eval_err("(vector-ref #(1 2 3) 3)", IndexOutOfRange(integer(3)));
+}
+
+#[test]
+fn vector_set() {
+    assert_eval(
+        "(define v (vector 1 2 3))
+         (vector-set! v 0 'x)
+         v",
+        "#(x 2 3)",
+    );
+    assert_eval_err(
+        "(define v '#(1 2 3))
+         (vector-set! v 0 'x)",
+        ImmutableValue(vector(vec![integer(1), integer(2), integer(3)])),
+    );
+}
+
+#[test]
+fn vector_fill() {
+    assert_eval(
+        "(define v (make-vector 2 0))
+         (vector-fill! v 7)
+         v",
+        "#(7 7)",
+    );
+}
+
+#[test]
+fn conversions() {
+    assert_eval("(vector->list #(1 2 3))", "'(1 2 3)");
+    assert_eval("(list->vector '(1 2 3))", "#(1 2 3)");
+    assert_eval_err(
+        "(list->vector '(1 . 2))",
+        WrongArgumentType(pair(integer(1), integer(2))),
+    );
+}
+
+#[test]
+fn cyclic_vectors_are_printed_with_labels() {
+    let mut runtime = Runtime::new();
+    let values = runtime
+        .eval_str(
+            "(define v (vector 1))
+             (vector-set! v 0 v)
+             v",
+        )
+        .unwrap();
+
+    assert_eq!("#0=#(#0#)", format!("{}", values.last().unwrap()));
+}
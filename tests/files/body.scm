@@ -0,0 +1,2 @@
+(define inner 5)
+(* inner 2)
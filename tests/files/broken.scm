@@ -0,0 +1,4 @@
+(define before-error 1)
+
+  (car missing)
+(define after-error 1)
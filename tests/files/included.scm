@@ -0,0 +1,2 @@
+(define included 1)
+(define (triple x) (* 3 x))
@@ -0,0 +1,2 @@
+(define (double x) (* x 2))
+(load "more.scm")
@@ -0,0 +1 @@
+(define loaded-more 'yes)
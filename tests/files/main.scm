@@ -0,0 +1,2 @@
+(load "lib/helpers.scm")
+(define result (double 21))
@@ -0,0 +1 @@
+(DEFINE SHOUT 'LOUD)
@@ -8,7 +8,7 @@ macro_rules! panic_expected {
         panic!(
             "Expected {:?} from input \"{:?}\" , got {:?}",
             $expected, $input, $actual
-        );
+        )
     };
 }
 
@@ -54,7 +54,7 @@ pub mod values {
         Rc::new(Value::Integer(val))
     }
     pub fn pair(left: RcValue, right: RcValue) -> RcValue {
-        Rc::new(Value::Pair(left, right))
+        Rc::new(Value::pair(left, right, true))
     }
     pub fn nil() -> RcValue {
         Rc::new(Value::Nil)
@@ -65,4 +65,13 @@ pub mod values {
     pub fn bool(val: bool) -> RcValue {
         Rc::new(Value::Bool(val))
     }
+    pub fn atom(name: &str) -> RcValue {
+        Rc::new(Value::Atom(name.into()))
+    }
+    pub fn string(val: &str) -> RcValue {
+        Rc::new(Value::string(val.chars().collect(), true))
+    }
+    pub fn vector(items: Vec<RcValue>) -> RcValue {
+        Rc::new(Value::vector(items, true))
+    }
 }
@@ -0,0 +1,5 @@
+(library (geometry constants)
+  (export pi)
+  (import (rnrs base))
+
+  (define pi 3))
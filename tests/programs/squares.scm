@@ -0,0 +1,11 @@
+(import (rnrs))
+
+(define (square x) (* x x))
+
+(define (show-squares from to)
+  (when (<= from to)
+    (display (square from))
+    (newline)
+    (show-squares (+ from 1) to)))
+
+(show-squares 1 3)
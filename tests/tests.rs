@@ -5,8 +5,30 @@ mod helpers;
 mod eval_application;
 mod eval_assignment;
 mod eval_boolean_procedures;
+mod eval_case_lambda;
+mod eval_closures;
 mod eval_conditionals;
+mod eval_conditions;
 mod eval_definitions;
+mod eval_enumerations;
+mod eval_environments;
+mod eval_equivalence_predicates;
+mod eval_exceptions;
+mod eval_expansion;
+mod eval_files;
+mod eval_hashtables;
 mod eval_integer_procedures;
+mod eval_iteration;
 mod eval_lets;
+mod eval_libraries;
 mod eval_list_procedures;
+mod eval_macros;
+mod eval_pair_mutation;
+mod eval_parameters;
+mod eval_programs;
+mod eval_promises;
+mod eval_records;
+mod eval_string_procedures;
+mod eval_syntax_case;
+mod eval_values;
+mod eval_vector_procedures;